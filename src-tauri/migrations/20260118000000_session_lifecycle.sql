@@ -0,0 +1,21 @@
+-- Migration: Session Lifecycle
+-- Track status changes of test sessions (pending -> active -> paused -> completed/terminated)
+
+ALTER TABLE sessions ADD COLUMN paused_at TIMESTAMP DEFAULT NULL;
+ALTER TABLE sessions ADD COLUMN paused_seconds INTEGER NOT NULL DEFAULT 0; -- Total time spent paused
+ALTER TABLE sessions ADD COLUMN status_reason TEXT DEFAULT NULL;
+ALTER TABLE sessions ADD COLUMN status_changed_at TIMESTAMP DEFAULT NULL;
+
+-- Audit trail of every status transition
+CREATE TABLE IF NOT EXISTS session_transitions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    from_status TEXT NOT NULL,
+    to_status TEXT NOT NULL,
+    reason TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_transitions_session
+ON session_transitions(session_id);
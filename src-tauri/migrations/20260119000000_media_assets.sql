@@ -0,0 +1,13 @@
+-- Media Assets
+-- Content-addressed store for question images and audio. Files live under the
+-- app data `media` directory, named by the SHA-256 of their content.
+
+CREATE TABLE IF NOT EXISTS media_assets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content_hash TEXT NOT NULL UNIQUE, -- Lowercase hex SHA-256
+    original_name TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    storage_path TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,28 @@
+-- Migration: Localized Content
+-- Translations of question text/options and subtest instructions, keyed by locale.
+-- The base rows hold the tool's own language (tools.content_locale); translations are overlays.
+
+ALTER TABLE tools ADD COLUMN content_locale TEXT NOT NULL DEFAULT 'id';
+ALTER TABLE events ADD COLUMN locale TEXT DEFAULT NULL;
+ALTER TABLE event_participants ADD COLUMN locale TEXT DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS question_translations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    question_id INTEGER NOT NULL,
+    locale TEXT NOT NULL,
+    question_text TEXT NOT NULL,
+    options JSON DEFAULT NULL, -- NULL keeps the base options
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (question_id) REFERENCES questions(id) ON DELETE CASCADE,
+    UNIQUE(question_id, locale)
+);
+
+CREATE TABLE IF NOT EXISTS subtest_translations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    subtest_id INTEGER NOT NULL,
+    locale TEXT NOT NULL,
+    instructions JSON NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (subtest_id) REFERENCES tool_subtests(id) ON DELETE CASCADE,
+    UNIQUE(subtest_id, locale)
+);
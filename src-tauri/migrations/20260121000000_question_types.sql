@@ -0,0 +1,38 @@
+-- Migration: Richer Question Types
+-- Adds likert, ranking, most_least and numeric to the questions.question_type CHECK.
+--
+-- SQLite can't alter a CHECK constraint, so the table is rebuilt. Migrations run inside a
+-- transaction, where foreign keys can't be switched off, and dropping the old table cascades
+-- into the tables that reference it. Their rows are parked in temp tables and put back after.
+
+CREATE TEMP TABLE answer_keys_backup AS SELECT * FROM answer_keys;
+CREATE TEMP TABLE question_translations_backup AS SELECT * FROM question_translations;
+
+CREATE TABLE questions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    subtest_id INTEGER NOT NULL,
+    question_text TEXT NOT NULL,
+    question_type TEXT NOT NULL CHECK(question_type IN (
+        'multiple_choice', 'true_false', 'pair', 'text', 'drawing', 'kraepelin_column',
+        'likert', 'ranking', 'most_least', 'numeric'
+    )),
+    options JSON, -- Shape depends on question_type, see src/question_types
+    media_url TEXT, -- For image/audio questions
+    sequence_order INTEGER NOT NULL,
+    FOREIGN KEY (subtest_id) REFERENCES tool_subtests(id) ON DELETE CASCADE,
+    UNIQUE(subtest_id, sequence_order)
+);
+
+INSERT INTO questions_new (id, subtest_id, question_text, question_type, options, media_url, sequence_order)
+SELECT id, subtest_id, question_text, question_type, options, media_url, sequence_order FROM questions;
+
+DROP TABLE questions;
+ALTER TABLE questions_new RENAME TO questions;
+
+CREATE INDEX idx_questions_subtest ON questions(subtest_id);
+
+INSERT INTO answer_keys SELECT * FROM answer_keys_backup;
+INSERT INTO question_translations SELECT * FROM question_translations_backup;
+
+DROP TABLE answer_keys_backup;
+DROP TABLE question_translations_backup;
@@ -0,0 +1,21 @@
+-- Drawing Answers
+-- Vector strokes for drawing questions, kept in drawing order with per-point
+-- timestamps so a psychologist can replay how the drawing was produced.
+
+CREATE TABLE IF NOT EXISTS drawing_answers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    question_id INTEGER NOT NULL,
+    canvas_width INTEGER NOT NULL,
+    canvas_height INTEGER NOT NULL,
+    strokes JSON NOT NULL, -- [{color, width, erase, points: [{x, y, t, pressure}]}]
+    stroke_count INTEGER NOT NULL DEFAULT 0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(session_id, question_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (question_id) REFERENCES questions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_drawing_answers_session ON drawing_answers(session_id);
@@ -0,0 +1,38 @@
+-- Manual Scoring
+-- Rater queue for text and drawing answers. Each item is one answer of one
+-- session; raters score it against the question's rubric.
+
+CREATE TABLE IF NOT EXISTS rating_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id INTEGER NOT NULL,
+    session_id INTEGER NOT NULL,
+    tool_id INTEGER NOT NULL,
+    question_id INTEGER NOT NULL,
+    question_type TEXT NOT NULL,
+    answer TEXT, -- Text answers; drawings live in drawing_answers
+    rubric JSON NOT NULL, -- Copied from the package so later edits don't change scoring
+    status TEXT NOT NULL CHECK(status IN ('pending', 'assigned', 'scored')) DEFAULT 'pending',
+    assigned_to INTEGER,
+    assigned_at DATETIME,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(session_id, question_id),
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (assigned_to) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_rating_items_event ON rating_items(event_id, status);
+CREATE INDEX idx_rating_items_rater ON rating_items(assigned_to);
+
+CREATE TABLE IF NOT EXISTS rating_scores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL,
+    rater_id INTEGER NOT NULL,
+    criteria JSON NOT NULL, -- {"criterion_key": score}
+    total REAL NOT NULL,
+    comment TEXT,
+    scored_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(item_id, rater_id),
+    FOREIGN KEY (item_id) REFERENCES rating_items(id) ON DELETE CASCADE,
+    FOREIGN KEY (rater_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,10 @@
+-- Double Scoring
+-- A second rater per item for inter-rater reliability, and adjudication when
+-- the two scores are too far apart.
+
+ALTER TABLE rating_items ADD COLUMN required_ratings INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE rating_items ADD COLUMN second_rater INTEGER REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE rating_items ADD COLUMN needs_adjudication INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE rating_items ADD COLUMN adjudicated_by INTEGER REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_rating_items_second_rater ON rating_items(second_rater);
@@ -0,0 +1,9 @@
+-- Testing Accommodations
+-- Per-participant adjustments for candidates with disabilities. A non-standard
+-- administration is recorded in the report so norm comparisons can flag it.
+
+ALTER TABLE event_participants ADD COLUMN time_multiplier REAL NOT NULL DEFAULT 1.0;
+ALTER TABLE event_participants ADD COLUMN extra_breaks INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE event_participants ADD COLUMN break_minutes INTEGER NOT NULL DEFAULT 0; -- Length of each extra break
+ALTER TABLE event_participants ADD COLUMN large_font INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE event_participants ADD COLUMN accommodation_notes TEXT DEFAULT NULL;
@@ -0,0 +1,29 @@
+-- Event Blueprint
+-- Ordered steps of an event (tools, subtests, breaks, instruction pages) and
+-- how far each session has got through them.
+
+CREATE TABLE IF NOT EXISTS event_blueprint_steps (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id INTEGER NOT NULL,
+    step_order INTEGER NOT NULL,
+    step_type TEXT NOT NULL CHECK(step_type IN ('tool', 'subtest', 'break', 'instructions')),
+    tool_id INTEGER,
+    subtest_id INTEGER,
+    title TEXT,
+    content JSON, -- Instruction pages
+    duration_seconds INTEGER, -- Break length
+    required INTEGER NOT NULL DEFAULT 1,
+    UNIQUE(event_id, step_order),
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE,
+    FOREIGN KEY (tool_id) REFERENCES tools(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS session_step_progress (
+    session_id INTEGER NOT NULL,
+    step_id INTEGER NOT NULL,
+    status TEXT NOT NULL CHECK(status IN ('completed', 'skipped')),
+    finished_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (session_id, step_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (step_id) REFERENCES event_blueprint_steps(id) ON DELETE CASCADE
+);
@@ -0,0 +1,16 @@
+-- Event Waitlist
+-- Candidates who tried to enroll in a full event. The oldest entry is promoted
+-- into event_participants whenever a place frees up.
+
+CREATE TABLE IF NOT EXISTS event_waitlist (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    notes TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE(event_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_waitlist_event ON event_waitlist(event_id, id);
@@ -0,0 +1,9 @@
+-- Event Schedule
+-- Testing window per event. Times are stored in UTC (ISO 8601, 'Z' suffix) so they
+-- compare as text; the timezone is the UTC offset the admin scheduled in, for display.
+
+ALTER TABLE events ADD COLUMN starts_at TEXT DEFAULT NULL;
+ALTER TABLE events ADD COLUMN ends_at TEXT DEFAULT NULL;
+ALTER TABLE events ADD COLUMN timezone TEXT DEFAULT NULL; -- e.g. '+07:00'
+
+CREATE INDEX IF NOT EXISTS idx_events_schedule ON events(status, starts_at, ends_at);
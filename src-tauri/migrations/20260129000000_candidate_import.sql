@@ -0,0 +1,30 @@
+-- Candidate Import
+-- Identity details of imported candidates, and single-use access tokens issued
+-- instead of passwords.
+
+CREATE TABLE IF NOT EXISTS participant_profiles (
+    user_id INTEGER PRIMARY KEY,
+    full_name TEXT NOT NULL,
+    nik TEXT DEFAULT NULL, -- National ID (NIK), 16 digits
+    date_of_birth TEXT DEFAULT NULL, -- YYYY-MM-DD
+    education TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_participant_profiles_nik ON participant_profiles(nik) WHERE nik IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS access_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    token_hash TEXT NOT NULL UNIQUE, -- SHA-256 of the token; the token itself is only shown once
+    user_id INTEGER NOT NULL,
+    event_id INTEGER NOT NULL,
+    expires_at TEXT NOT NULL, -- UTC
+    used_at TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_access_tokens_user ON access_tokens(user_id, event_id);
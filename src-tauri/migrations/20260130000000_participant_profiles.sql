@@ -0,0 +1,5 @@
+-- Participant Profiles
+-- Demographics used to pick norm groups and shown on reports.
+
+ALTER TABLE participant_profiles ADD COLUMN gender TEXT DEFAULT NULL; -- male, female
+ALTER TABLE participant_profiles ADD COLUMN position_applied TEXT DEFAULT NULL;
@@ -0,0 +1,23 @@
+-- Event Code Lifecycle
+-- Validity window, usage count and revocation for the shared event code, plus a record
+-- of every code that was rotated out or revoked. Times are UTC ('Z' suffix), like the schedule.
+
+ALTER TABLE events ADD COLUMN code_valid_from TEXT DEFAULT NULL;
+ALTER TABLE events ADD COLUMN code_expires_at TEXT DEFAULT NULL;
+ALTER TABLE events ADD COLUMN code_use_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE events ADD COLUMN code_revoked_at TEXT DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS event_code_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id INTEGER NOT NULL,
+    event_code TEXT NOT NULL,
+    action TEXT NOT NULL CHECK(action IN ('rotated', 'revoked')),
+    use_count INTEGER NOT NULL DEFAULT 0, -- enrollments made with the code
+    changed_by INTEGER DEFAULT NULL,
+    changed_at TEXT NOT NULL,
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE,
+    FOREIGN KEY (changed_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_code_history_event ON event_code_history(event_id, changed_at);
+CREATE INDEX IF NOT EXISTS idx_event_code_history_code ON event_code_history(event_code);
@@ -0,0 +1,15 @@
+-- Retake Policy
+-- Retakes start a new attempt instead of deleting the old sessions. Sessions of earlier
+-- attempts are kept, marked superseded, and each event decides how many attempts are
+-- allowed, how long to wait between them and which attempt its reports count.
+
+ALTER TABLE sessions ADD COLUMN attempt_number INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE sessions ADD COLUMN superseded_at TEXT DEFAULT NULL;
+
+ALTER TABLE event_participants ADD COLUMN attempt_number INTEGER NOT NULL DEFAULT 1; -- current attempt
+
+ALTER TABLE events ADD COLUMN max_attempts INTEGER DEFAULT NULL; -- NULL = no limit
+ALTER TABLE events ADD COLUMN retake_cooldown_hours INTEGER DEFAULT NULL;
+ALTER TABLE events ADD COLUMN report_attempt TEXT NOT NULL DEFAULT 'latest' CHECK(report_attempt IN ('best', 'latest', 'first'));
+
+CREATE INDEX IF NOT EXISTS idx_sessions_attempt ON sessions(event_id, user_id, attempt_number);
@@ -0,0 +1,30 @@
+-- Event Ranking
+-- A weighted composite of tool scores per event, minimum cutoffs on tool totals or on
+-- single scales, and the rules that order candidates with the same composite.
+
+CREATE TABLE IF NOT EXISTS event_composite_weights (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id INTEGER NOT NULL,
+    tool_id INTEGER NOT NULL,
+    weight REAL NOT NULL CHECK(weight > 0),
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE,
+    FOREIGN KEY (tool_id) REFERENCES tools(id) ON DELETE CASCADE,
+    UNIQUE(event_id, tool_id)
+);
+
+CREATE TABLE IF NOT EXISTS event_score_cutoffs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id INTEGER NOT NULL,
+    tool_id INTEGER NOT NULL,
+    scale TEXT NOT NULL DEFAULT '', -- '' = the tool's total score
+    min_score REAL NOT NULL,
+    FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE,
+    FOREIGN KEY (tool_id) REFERENCES tools(id) ON DELETE CASCADE,
+    UNIQUE(event_id, tool_id, scale)
+);
+
+CREATE INDEX IF NOT EXISTS idx_composite_weights_event ON event_composite_weights(event_id);
+CREATE INDEX IF NOT EXISTS idx_score_cutoffs_event ON event_score_cutoffs(event_id);
+
+-- JSON array of tie-break rules, applied in order
+ALTER TABLE events ADD COLUMN ranking_tie_breaks TEXT NOT NULL DEFAULT '["highest_weighted_tool","earliest_completion"]';
@@ -0,0 +1,28 @@
+-- Drawing Answers Survive Content Edits
+-- Answers belong to the question as delivered by the event's package, not the live
+-- question row; deleting or re-importing a tool's content must not drop them.
+-- The question reference goes the same way as rating_items.question_id.
+
+CREATE TABLE drawing_answers_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    question_id INTEGER NOT NULL,
+    canvas_width INTEGER NOT NULL,
+    canvas_height INTEGER NOT NULL,
+    strokes JSON NOT NULL, -- [{color, width, erase, points: [{x, y, t, pressure}]}]
+    stroke_count INTEGER NOT NULL DEFAULT 0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(session_id, question_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+INSERT INTO drawing_answers_new (id, session_id, question_id, canvas_width, canvas_height, strokes, stroke_count, duration_ms, created_at, updated_at)
+SELECT id, session_id, question_id, canvas_width, canvas_height, strokes, stroke_count, duration_ms, created_at, updated_at
+FROM drawing_answers;
+
+DROP TABLE drawing_answers;
+ALTER TABLE drawing_answers_new RENAME TO drawing_answers;
+
+CREATE INDEX idx_drawing_answers_session ON drawing_answers(session_id);
@@ -0,0 +1,13 @@
+-- Candidate App: Session Progress
+-- Checkpoint of where a candidate is in a test, used to resume after a crash or power loss
+
+CREATE TABLE IF NOT EXISTS local_session_progress (
+    session_id TEXT PRIMARY KEY,
+    tool_id INTEGER NOT NULL,
+    subtest_id INTEGER NOT NULL,
+    question_index INTEGER NOT NULL DEFAULT 0,
+    time_limit_seconds INTEGER, -- NULL means untimed
+    elapsed_seconds INTEGER NOT NULL DEFAULT 0, -- Time used in the current subtest
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (session_id) REFERENCES local_sessions(session_id) ON DELETE CASCADE
+);
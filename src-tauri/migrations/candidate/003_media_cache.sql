@@ -0,0 +1,10 @@
+-- Candidate App: Media Cache
+-- Question media downloaded ahead of the test so it can run without a connection
+
+CREATE TABLE IF NOT EXISTS local_media_cache (
+    content_hash TEXT PRIMARY KEY,
+    file_path TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    downloaded_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -5,14 +5,22 @@ use axum::{
     Router,
     routing::{post, get},
     extract::{State, Path, Multipart},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use tower_http::cors::{CorsLayer, Any};
 use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 
+use crate::blueprint::{BlueprintStep, NextStep};
+use crate::candidate_import::hash_token;
 use crate::db::Database;
+use crate::db::access_tokens::AccessTokenError;
+use crate::db::blueprint::BlueprintError;
+use crate::db::event_codes::EventCodeError;
+use crate::db::statistics::EventStatistics;
+use crate::media;
 
 // ===== Request/Response Types =====
 
@@ -48,6 +56,42 @@ pub struct EventResponse {
     pub status: String,
 }
 
+/// Error body for failures the client should explain to the candidate
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiError {
+    pub error: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenLoginRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenLoginResponse {
+    pub user_id: i64,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub event_id: i64,
+    pub event_name: String,
+    pub event_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MediaManifestEntry {
+    pub content_hash: String,
+    pub url: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CompleteStepRequest {
+    #[serde(default)]
+    pub skipped: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthResponse {
     pub status: String,
@@ -57,6 +101,38 @@ pub struct HealthResponse {
 
 // ===== API Server =====
 
+/// Every endpoint the candidate app talks to
+pub(crate) fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        // Health check
+        .route("/api/health", get(health_check))
+        // Participant login with a one-time access token
+        .route("/api/auth/token", post(login_with_token))
+        // Event endpoints
+        .route("/api/events/:code", get(get_event_by_code))
+        .route("/api/event-media/:id", get(get_event_media))
+        .route("/api/event-blueprint/:id", get(get_event_blueprint))
+        // Event analytics for dashboards, by event id
+        .route("/api/event-statistics/:id", get(get_event_statistics))
+        // Battery progress
+        .route("/api/sessions/:id/next-step", get(get_next_step))
+        .route("/api/sessions/:id/steps/:step_id/complete", post(complete_step))
+        // Question media
+        .route("/api/media/:hash", get(serve_media))
+        // Test result submission
+        .route("/api/test-results", post(submit_test_result))
+        // Recording upload
+        .route("/api/recordings", post(submit_recording))
+        // CORS
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any)
+        )
+        .with_state(db)
+}
+
 pub struct ApiServer {
     db: Arc<Database>,
     port: u16,
@@ -71,23 +147,7 @@ impl ApiServer {
     }
 
     pub async fn start(self) -> Result<(), Box<dyn std::error::Error>> {
-        let app = Router::new()
-            // Health check
-            .route("/api/health", get(health_check))
-            // Event endpoints
-            .route("/api/events/:code", get(get_event_by_code))
-            // Test result submission
-            .route("/api/test-results", post(submit_test_result))
-            // Recording upload
-            .route("/api/recordings", post(submit_recording))
-            // CORS
-            .layer(
-                CorsLayer::new()
-                    .allow_origin(Any)
-                    .allow_methods(Any)
-                    .allow_headers(Any)
-            )
-            .with_state(self.db);
+        let app = router(self.db);
 
         let addr = format!("0.0.0.0:{}", self.port);
         println!("🚀 API Server listening on http://{}", addr);
@@ -114,11 +174,55 @@ pub(crate) async fn health_check() -> Json<HealthResponse> {
     })
 }
 
+/// Redeem a one-time access token. Unknown tokens are 401; expired or used ones 410,
+/// so the client can tell the candidate to ask for a new invitation.
+pub(crate) async fn login_with_token(
+    State(db): State<Arc<Database>>,
+    Json(request): Json<TokenLoginRequest>,
+) -> Result<Json<TokenLoginResponse>, StatusCode> {
+    println!("📥 POST /api/auth/token");
+
+    let login = db.redeem_access_token(&hash_token(&request.token), chrono::Utc::now()).await.map_err(|e| match e {
+        AccessTokenError::Invalid => StatusCode::UNAUTHORIZED,
+        AccessTokenError::Expired { .. } | AccessTokenError::AlreadyUsed { .. } => StatusCode::GONE,
+        AccessTokenError::NotEnrolled | AccessTokenError::EventArchived => StatusCode::FORBIDDEN,
+        AccessTokenError::DatabaseError(e) => {
+            eprintln!("❌ Error redeeming access token: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+
+    Ok(Json(TokenLoginResponse {
+        user_id: login.user.id,
+        username: login.user.username,
+        full_name: login.full_name,
+        event_id: login.event_id,
+        event_name: login.event_name,
+        event_code: login.event_code,
+    }))
+}
+
+/// Unknown codes are 404; expired, revoked and rotated-out ones 410, not yet valid 403.
+/// The body says which, so the candidate isn't just told the code is wrong.
+fn event_code_error(err: EventCodeError) -> (StatusCode, Json<ApiError>) {
+    let status = match err {
+        EventCodeError::NotFound => StatusCode::NOT_FOUND,
+        EventCodeError::Expired { .. } | EventCodeError::Revoked { .. } | EventCodeError::Replaced => StatusCode::GONE,
+        EventCodeError::NotYetValid { .. } | EventCodeError::NotAllowed => StatusCode::FORBIDDEN,
+        EventCodeError::InvalidWindow(_) => StatusCode::BAD_REQUEST,
+        EventCodeError::DatabaseError(ref e) => {
+            eprintln!("❌ Error fetching event: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, Json(ApiError { error: err.kind().to_string(), message: err.to_string() }))
+}
+
 /// Get event by access code
 pub(crate) async fn get_event_by_code(
     State(db): State<Arc<Database>>,
     Path(code): Path<String>,
-) -> Result<Json<EventResponse>, StatusCode> {
+) -> Result<Json<EventResponse>, (StatusCode, Json<ApiError>)> {
     println!("📥 GET /api/events/{}", code);
 
     match db.get_event_by_code(&code).await {
@@ -131,13 +235,152 @@ pub(crate) async fn get_event_by_code(
                 status: event.status,
             }))
         }
-        Err(e) => {
-            eprintln!("❌ Error fetching event: {:?}", e);
-            Err(StatusCode::NOT_FOUND)
+        Err(e) => Err(event_code_error(e)),
+    }
+}
+
+/// Candidates who already enrolled look the event up by id from here on, so the
+/// code expiring, being revoked or rotated doesn't cut them off mid-test
+async fn ensure_event_exists(db: &Database, event_id: i64) -> Result<(), StatusCode> {
+    db.get_event_details(event_id).await.map(|_| ()).map_err(|e| match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        e => {
+            eprintln!("❌ Error loading event {}: {:?}", event_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })
+}
+
+/// List the media an event's questions use, so candidates can download it before going offline
+pub(crate) async fn get_event_media(
+    State(db): State<Arc<Database>>,
+    Path(event_id): Path<i64>,
+) -> Result<Json<Vec<MediaManifestEntry>>, StatusCode> {
+    println!("📥 GET /api/event-media/{}", event_id);
+
+    ensure_event_exists(&db, event_id).await?;
+    let assets = db.get_event_media_assets(event_id).await.map_err(|e| {
+        eprintln!("❌ Error listing event media: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(assets.into_iter().map(|asset| MediaManifestEntry {
+        url: asset.url(),
+        content_hash: asset.content_hash,
+        mime_type: asset.mime_type,
+        size_bytes: asset.size_bytes,
+    }).collect()))
+}
+
+/// The order the candidate works through the event
+pub(crate) async fn get_event_blueprint(
+    State(db): State<Arc<Database>>,
+    Path(event_id): Path<i64>,
+) -> Result<Json<Vec<BlueprintStep>>, StatusCode> {
+    println!("📥 GET /api/event-blueprint/{}", event_id);
+
+    ensure_event_exists(&db, event_id).await?;
+    let steps = db.get_event_blueprint(event_id).await.map_err(|e| {
+        eprintln!("❌ Error loading blueprint: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(steps))
+}
+
+/// Completion, timing, score distributions and protocol quality for an event
+pub(crate) async fn get_event_statistics(
+    State(db): State<Arc<Database>>,
+    Path(event_id): Path<i64>,
+) -> Result<Json<EventStatistics>, StatusCode> {
+    println!("📥 GET /api/event-statistics/{}", event_id);
+
+    db.get_event_statistics(event_id).await
+        .map(Json)
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            e => {
+                eprintln!("❌ Error computing event statistics: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })
+}
+
+fn blueprint_status(err: BlueprintError) -> StatusCode {
+    match err {
+        BlueprintError::SessionNotFound(_) => StatusCode::NOT_FOUND,
+        BlueprintError::OutOfOrder { .. } | BlueprintError::RequiredStep(_) | BlueprintError::NoToolResult(_) => StatusCode::CONFLICT,
+        BlueprintError::DatabaseError(e) => {
+            eprintln!("❌ Blueprint error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
         }
     }
 }
 
+/// The step a session should run next
+pub(crate) async fn get_next_step(
+    State(db): State<Arc<Database>>,
+    Path(session_id): Path<i64>,
+) -> Result<Json<NextStep>, StatusCode> {
+    db.get_session_next_step(session_id).await
+        .map(Json)
+        .map_err(blueprint_status)
+}
+
+/// Finish the current step; out-of-order steps, skipping required ones and tool steps without a result are rejected with 409
+pub(crate) async fn complete_step(
+    State(db): State<Arc<Database>>,
+    Path((session_id, step_id)): Path<(i64, i64)>,
+    body: Option<Json<CompleteStepRequest>>,
+) -> Result<Json<NextStep>, StatusCode> {
+    println!("📥 POST /api/sessions/{}/steps/{}/complete", session_id, step_id);
+
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    db.complete_session_step(session_id, step_id, request.skipped).await
+        .map(Json)
+        .map_err(blueprint_status)
+}
+
+/// Serve a media file by content hash. The hash doubles as the ETag.
+pub(crate) async fn serve_media(
+    State(db): State<Arc<Database>>,
+    Path(hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if !media::is_valid_hash(&hash) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let asset = db.get_media_asset(&hash).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let etag = format!("\"{}\"", asset.content_hash);
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, media::CACHE_CONTROL.to_string()),
+    ];
+
+    let not_modified = headers.get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"))
+        .unwrap_or(false);
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let data = tokio::fs::read(&asset.storage_path).await.map_err(|e| {
+        eprintln!("❌ Media file missing for {}: {:?}", asset.content_hash, e);
+        StatusCode::NOT_FOUND
+    })?;
+
+    Ok((
+        cache_headers,
+        [(header::CONTENT_TYPE, asset.mime_type)],
+        data,
+    ).into_response())
+}
+
 /// Submit test result from candidate
 pub(crate) async fn submit_test_result(
     State(db): State<Arc<Database>>,
@@ -145,6 +388,18 @@ pub(crate) async fn submit_test_result(
 ) -> Result<Json<SubmitResponse>, StatusCode> {
     println!("📥 POST /api/test-results - Session: {}", payload.session_id);
 
+    match db.is_event_archived(payload.event_id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            eprintln!("❌ Event {} is archived, result rejected", payload.event_id);
+            return Err(StatusCode::FORBIDDEN);
+        }
+        Err(e) => {
+            eprintln!("❌ Error checking event {}: {:?}", payload.event_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
     // Convert answers to JSON
     let answers_json = match serde_json::to_string(&payload.answers) {
         Ok(json) => json,
@@ -5,7 +5,7 @@ mod api_server_tests {
     use super::*;
     use std::sync::Arc;
     use crate::db::Database;
-    use crate::api_server::{health_check, get_event_by_code, submit_test_result};
+    use crate::api_server::{health_check, get_event_by_code, submit_test_result, serve_media};
     use axum::{
         Router,
         routing::{get, post},
@@ -33,6 +33,12 @@ mod api_server_tests {
                 status TEXT DEFAULT 'draft'
             );
 
+            CREATE TABLE IF NOT EXISTS event_code_history (
+                id INTEGER PRIMARY KEY,
+                event_id INTEGER NOT NULL,
+                event_code TEXT NOT NULL
+            );
+
             CREATE TABLE IF NOT EXISTS test_results (
                 id INTEGER PRIMARY KEY,
                 user_id INTEGER NOT NULL,
@@ -171,5 +177,327 @@ mod api_server_tests {
 
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
-}
 
+    #[tokio::test]
+    async fn test_submit_test_result_archived_event() {
+        use crate::db::event_lifecycle::EventStatus;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Arc::new(Database::new(pool));
+        let event_id = db.create_event("Archived Results", None, None).await.unwrap();
+        db.set_event_status(event_id, EventStatus::Archived).await.unwrap();
+
+        let app = Router::new()
+            .route("/api/test-results", post(submit_test_result))
+            .with_state(db);
+        let payload = json!({
+            "session_id": "late-1",
+            "event_id": event_id,
+            "user_id": 1,
+            "answers": [],
+            "completed_at": "2026-01-01T00:00:00Z"
+        });
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/test-results")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&payload).unwrap()))
+                    .unwrap()
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_serve_media_with_cache_headers() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Arc::new(Database::new(pool));
+
+        let dir = tempfile::tempdir().unwrap();
+        let stored = crate::media::store_bytes(dir.path(), "figure.png", b"fake-png").unwrap();
+        db.register_media_asset(&stored).await.unwrap();
+
+        let app = Router::new()
+            .route("/api/media/:hash", get(serve_media))
+            .with_state(db);
+        let uri = format!("/api/media/{}", stored.content_hash);
+
+        let response = app.clone()
+            .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/png");
+        assert_eq!(response.headers()["cache-control"], crate::media::CACHE_CONTROL);
+        let etag = response.headers()["etag"].clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"fake-png");
+
+        // Revalidation with the ETag skips the body
+        let response = app.clone()
+            .oneshot(Request::builder().uri(&uri).header("if-none-match", etag).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = app
+            .oneshot(Request::builder().uri("/api/media/..%2Fetc%2Fpasswd").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_next_step_endpoints() {
+        use crate::api_server::{get_next_step, complete_step};
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Arc::new(Database::new(pool));
+
+        let tool_id = db.create_tool("Battery Tool", "choice", "cognitive", "Next step test").await.unwrap();
+        db.create_subtest(tool_id, "Only", 1, None).await.unwrap();
+        let event_id = db.create_event("Next Step Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        let user_id = db.create_user("battery.user", "hash", "participant").await.unwrap();
+        let session_id = db.create_session(event_id, "P-001", Some(user_id), None).await.unwrap();
+        let tool_run = db.create_session(event_id, "P-001", Some(user_id), Some(json!({"tool_id": tool_id}))).await.unwrap();
+
+        let app = Router::new()
+            .route("/api/sessions/:id/next-step", get(get_next_step))
+            .route("/api/sessions/:id/steps/:step_id/complete", post(complete_step))
+            .with_state(db.clone());
+
+        let response = app.clone()
+            .oneshot(Request::builder().uri(format!("/api/sessions/{}/next-step", session_id)).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let next: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(next["step"]["tool_id"], tool_id);
+        let step_id = next["step"]["id"].as_i64().unwrap();
+
+        // Required steps can't be skipped
+        let response = app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/sessions/{}/steps/{}/complete", session_id, step_id))
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({"skipped": true}).to_string()))
+                    .unwrap()
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // Nor finished before the tool has a result
+        let complete = || Request::builder()
+            .method("POST")
+            .uri(format!("/api/sessions/{}/steps/{}/complete", session_id, step_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(complete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        db.create_report(tool_run, json!({"total_score": 1}), json!({})).await.unwrap();
+
+        let response = app.oneshot(complete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let next: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(next["finished"], true);
+    }
+
+    #[tokio::test]
+    async fn test_login_with_token_endpoint() {
+        use crate::api_server::login_with_token;
+        use crate::candidate_import::hash_token;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Arc::new(Database::new(pool));
+
+        let event_id = db.create_event("Token API Event", None, None).await.unwrap();
+        let user_id = db.create_user("api.token", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+        let expires = chrono::Utc::now() + chrono::Duration::days(1);
+        db.issue_access_token(event_id, user_id, &hash_token("ABCD-EFGH-JKLM-NPQR"), expires).await.unwrap();
+
+        let app = Router::new()
+            .route("/api/auth/token", post(login_with_token))
+            .with_state(db);
+        let request = |token: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/auth/token")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"token": token}).to_string()))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("abcdefghjklmnpqr")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let login: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(login["user_id"], user_id);
+        assert_eq!(login["event_id"], event_id);
+
+        let response = app.clone().oneshot(request("ABCD-EFGH-JKLM-NPQR")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GONE);
+
+        let response = app.oneshot(request("NOPE-NOPE-NOPE-NOPE")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_get_event_by_code_revoked() {
+        use crate::api_server::{get_event_media, get_event_blueprint};
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Arc::new(Database::new(pool));
+
+        let event_id = db.create_event("Revoked Code Event", None, None).await.unwrap();
+        let admin = db.create_user("revoking.admin", "hash", "admin").await.unwrap();
+        db.update_event_code(event_id, "RVK234").await.unwrap();
+        db.revoke_event_code(event_id, admin).await.unwrap();
+
+        let app = Router::new()
+            .route("/api/events/:code", get(get_event_by_code))
+            .route("/api/event-media/:id", get(get_event_media))
+            .route("/api/event-blueprint/:id", get(get_event_blueprint))
+            .with_state(db);
+
+        let response = app.clone()
+            .oneshot(Request::builder().uri("/api/events/RVK234").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::GONE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"], "revoked");
+
+        // Candidates who already enrolled keep their media and blueprint
+        for uri in [format!("/api/event-media/{}", event_id), format!("/api/event-blueprint/{}", event_id)] {
+            let response = app.clone()
+                .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        }
+        let response = app
+            .oneshot(Request::builder().uri(format!("/api/event-media/{}", event_id + 1)).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_event_statistics_endpoint() {
+        use crate::api_server::get_event_statistics;
+        use crate::db::session_lifecycle::SessionStatus;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Arc::new(Database::new(pool.clone()));
+
+        let event_id = db.create_event("Statistics Event", None, None).await.unwrap();
+        let mut sessions = Vec::new();
+        for (name, scores) in [
+            ("stats.one", json!({"total_score": 10, "flags": ["too_fast"]})),
+            ("stats.two", json!({"total_score": 20, "valid": false})),
+            // No total: left out of the distribution instead of counted as 0
+            ("stats.three", json!({"valid": true})),
+        ] {
+            let user_id = db.create_user(name, "hash", "participant").await.unwrap();
+            db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+            let session_id = db.create_session(event_id, name, Some(user_id), Some(json!({"testName": "Stats Tool"}))).await.unwrap();
+            db.transition_session(session_id, SessionStatus::Completed, None).await.unwrap();
+            db.create_report(session_id, scores, json!({})).await.unwrap();
+            sessions.push(session_id);
+        }
+        let leaver = db.create_user("stats.leaver", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, leaver, None).await.unwrap();
+        db.withdraw_participant(event_id, leaver, "Moved away").await.unwrap();
+
+        for (event_type, severity) in [("window_unfocus", "warning"), ("window_unfocus", "warning"), ("multiple_faces", "critical"), ("no_face", "info")] {
+            sqlx::query("INSERT INTO surveillance_logs (session_id, event_type, severity) VALUES (?, ?, ?)")
+                .bind(sessions[0])
+                .bind(event_type)
+                .bind(severity)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let app = Router::new()
+            .route("/api/event-statistics/:id", get(get_event_statistics))
+            .with_state(db);
+
+        let response = app.clone()
+            .oneshot(Request::builder().uri(format!("/api/event-statistics/{}", event_id)).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let stats: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!((stats["participants"].as_i64(), stats["withdrawn"].as_i64()), (Some(4), Some(1)));
+        assert_eq!(stats["completion_rate"], 1.0);
+        assert_eq!(stats["completed_sessions"], 3);
+        assert!(stats["average_duration_seconds"].as_f64().is_some());
+        assert_eq!((stats["flagged_protocols"].as_i64(), stats["invalid_protocols"].as_i64()), (Some(2), Some(1)));
+        assert_eq!(stats["violations"]["total"], 3);
+        assert_eq!(stats["violations"]["critical"], 1);
+        assert_eq!(stats["violations"]["sessions_affected"], 1);
+        assert_eq!(stats["violations"]["by_type"][0]["event_type"], "window_unfocus");
+
+        assert_eq!(stats["tools"].as_array().unwrap().len(), 1);
+        let tool = &stats["tools"][0];
+        assert_eq!(tool["tool_name"], "Stats Tool");
+        assert_eq!((tool["count"].as_i64(), tool["mean"].as_f64()), (Some(2), Some(15.0)));
+        assert!((tool["sd"].as_f64().unwrap() - 50f64.sqrt()).abs() < 1e-9);
+        assert_eq!((tool["q1"].as_f64(), tool["median"].as_f64(), tool["q3"].as_f64()), (Some(10.0), Some(10.0), Some(20.0)));
+        let histogram = tool["histogram"].as_array().unwrap();
+        assert_eq!(histogram.len(), 10);
+        assert_eq!((histogram[0]["count"].as_i64(), histogram[9]["count"].as_i64(), histogram[9]["upper"].as_f64()), (Some(1), Some(1), Some(20.0)));
+
+        let response = app
+            .oneshot(Request::builder().uri("/api/event-statistics/9999").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,163 @@
+// Event Blueprint
+// The order a candidate works through an event: tools, single subtests, breaks and instruction pages
+
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepType {
+    /// A whole tool, all subtests in their own order
+    Tool,
+    /// One subtest of a tool, for batteries that interleave tools
+    Subtest,
+    Break,
+    Instructions,
+}
+
+impl StepType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepType::Tool => "tool",
+            StepType::Subtest => "subtest",
+            StepType::Break => "break",
+            StepType::Instructions => "instructions",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "tool" => Some(StepType::Tool),
+            "subtest" => Some(StepType::Subtest),
+            "break" => Some(StepType::Break),
+            "instructions" => Some(StepType::Instructions),
+            _ => None,
+        }
+    }
+}
+
+fn default_required() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlueprintStep {
+    /// Assigned when the blueprint is saved
+    #[serde(default)]
+    pub id: i64,
+    pub step_type: StepType,
+    #[serde(default)]
+    pub tool_id: Option<i64>,
+    #[serde(default)]
+    pub subtest_id: Option<i64>,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Instruction pages: `{"pages": ["...", "..."]}`
+    #[serde(default)]
+    pub content: Option<serde_json::Value>,
+    /// Break length; candidates may continue early unless the break is required
+    #[serde(default)]
+    pub duration_seconds: Option<i64>,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+/// Check a blueprint against the event's tools. `subtests` maps each tool to its subtest ids.
+pub fn validate_blueprint(steps: &[BlueprintStep], subtests: &HashMap<i64, Vec<i64>>) -> Result<(), String> {
+    if steps.is_empty() {
+        return Err("A blueprint needs at least one step".to_string());
+    }
+
+    let mut seen_tools = HashSet::new();
+    let mut seen_subtests = HashSet::new();
+
+    for (i, step) in steps.iter().enumerate() {
+        let n = i + 1;
+        match step.step_type {
+            StepType::Tool | StepType::Subtest => {
+                let tool_id = step.tool_id.ok_or_else(|| format!("Step {} needs a tool", n))?;
+                let tool_subtests = subtests.get(&tool_id)
+                    .ok_or_else(|| format!("Step {}: tool {} is not part of this event", n, tool_id))?;
+
+                if step.step_type == StepType::Tool {
+                    if !seen_tools.insert(tool_id) || tool_subtests.iter().any(|s| seen_subtests.contains(s)) {
+                        return Err(format!("Step {}: tool {} is already in the blueprint", n, tool_id));
+                    }
+                    seen_subtests.extend(tool_subtests.iter().copied());
+                } else {
+                    let subtest_id = step.subtest_id.ok_or_else(|| format!("Step {} needs a subtest", n))?;
+                    if !tool_subtests.contains(&subtest_id) {
+                        return Err(format!("Step {}: subtest {} does not belong to tool {}", n, subtest_id, tool_id));
+                    }
+                    if seen_tools.contains(&tool_id) || !seen_subtests.insert(subtest_id) {
+                        return Err(format!("Step {}: subtest {} is already in the blueprint", n, subtest_id));
+                    }
+                }
+            }
+            StepType::Break => {
+                if step.duration_seconds.unwrap_or(0) <= 0 {
+                    return Err(format!("Step {}: breaks need a duration", n));
+                }
+            }
+            StepType::Instructions => {
+                let has_pages = step.content.as_ref()
+                    .and_then(|c| c.get("pages"))
+                    .and_then(|p| p.as_array())
+                    .is_some_and(|p| !p.is_empty());
+                if !has_pages {
+                    return Err(format!("Step {}: instruction steps need at least one page", n));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Blueprint of an event that never defined one: its tools in package order, all required
+pub fn default_blueprint(tool_ids: &[i64]) -> Vec<BlueprintStep> {
+    tool_ids.iter().map(|&tool_id| BlueprintStep {
+        id: 0,
+        step_type: StepType::Tool,
+        tool_id: Some(tool_id),
+        subtest_id: None,
+        title: None,
+        content: None,
+        duration_seconds: None,
+        required: true,
+    }).collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NextStep {
+    /// `None` once the battery is finished
+    pub step: Option<BlueprintStep>,
+    /// Zero-based position of `step`
+    pub position: usize,
+    pub total_steps: usize,
+    pub completed_steps: usize,
+    pub finished: bool,
+    /// Extra breaks the candidate's accommodations still allow, taken between steps
+    #[serde(default)]
+    pub extra_breaks_left: i64,
+    #[serde(default)]
+    pub extra_break_seconds: i64,
+}
+
+/// First step the session hasn't completed or skipped yet
+pub fn next_step(steps: &[BlueprintStep], done_step_ids: &HashSet<i64>) -> NextStep {
+    let position = steps.iter().position(|s| !done_step_ids.contains(&s.id));
+
+    NextStep {
+        step: position.map(|p| steps[p].clone()),
+        position: position.unwrap_or(steps.len()),
+        total_steps: steps.len(),
+        completed_steps: steps.iter().filter(|s| done_step_ids.contains(&s.id)).count(),
+        finished: position.is_none(),
+        extra_breaks_left: 0,
+        extra_break_seconds: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,141 @@
+// Event Blueprint Unit Tests
+
+#[cfg(test)]
+mod blueprint_tests {
+    use crate::blueprint::{next_step, validate_blueprint, BlueprintStep, StepType};
+    use crate::db::Database;
+    use crate::db::blueprint::BlueprintError;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use serde_json::json;
+    use std::collections::{HashMap, HashSet};
+
+    fn step(step_type: StepType) -> BlueprintStep {
+        BlueprintStep {
+            id: 0,
+            step_type,
+            tool_id: None,
+            subtest_id: None,
+            title: None,
+            content: None,
+            duration_seconds: None,
+            required: true,
+        }
+    }
+
+    fn tool(tool_id: i64) -> BlueprintStep {
+        BlueprintStep { tool_id: Some(tool_id), ..step(StepType::Tool) }
+    }
+
+    fn subtest(tool_id: i64, subtest_id: i64) -> BlueprintStep {
+        BlueprintStep { tool_id: Some(tool_id), subtest_id: Some(subtest_id), ..step(StepType::Subtest) }
+    }
+
+    fn pause(seconds: i64) -> BlueprintStep {
+        BlueprintStep { duration_seconds: Some(seconds), required: false, ..step(StepType::Break) }
+    }
+
+    #[test]
+    fn test_blueprint_validation() {
+        let subtests = HashMap::from([(1, vec![10, 11]), (2, vec![20])]);
+        let intro = BlueprintStep { content: Some(json!({"pages": ["Welcome"]})), ..step(StepType::Instructions) };
+
+        assert!(validate_blueprint(&[intro.clone(), subtest(1, 10), pause(300), tool(2), subtest(1, 11)], &subtests).is_ok());
+
+        assert!(validate_blueprint(&[], &subtests).is_err());
+        assert!(validate_blueprint(&[tool(3)], &subtests).is_err());
+        assert!(validate_blueprint(&[subtest(1, 20)], &subtests).is_err());
+        assert!(validate_blueprint(&[tool(1), subtest(1, 10)], &subtests).is_err());
+        assert!(validate_blueprint(&[tool(2), tool(2)], &subtests).is_err());
+        assert!(validate_blueprint(&[pause(0)], &subtests).is_err());
+        assert!(validate_blueprint(&[step(StepType::Instructions)], &subtests).is_err());
+    }
+
+    #[test]
+    fn test_next_step_follows_order() {
+        let steps: Vec<BlueprintStep> = [tool(1), pause(60), tool(2)].into_iter()
+            .enumerate()
+            .map(|(i, s)| BlueprintStep { id: i as i64 + 1, ..s })
+            .collect();
+
+        let first = next_step(&steps, &HashSet::new());
+        assert_eq!(first.step.unwrap().id, 1);
+        assert_eq!(first.total_steps, 3);
+
+        let after_break = next_step(&steps, &HashSet::from([1, 2]));
+        assert_eq!(after_break.position, 2);
+        assert_eq!(after_break.completed_steps, 2);
+
+        let done = next_step(&steps, &HashSet::from([1, 2, 3]));
+        assert!(done.finished);
+        assert!(done.step.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_walks_the_blueprint() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let first = db.create_tool("Verbal", "choice", "cognitive", "Blueprint test").await.unwrap();
+        db.create_subtest(first, "Synonyms", 1, Some(300)).await.unwrap();
+        let second = db.create_tool("Numeric", "choice", "cognitive", "Blueprint test").await.unwrap();
+        db.create_subtest(second, "Series", 1, Some(300)).await.unwrap();
+
+        // Tools come back in the order they were added to the event, not by id
+        let event_id = db.create_event("Battery", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![second, first]).await.unwrap();
+        let order: Vec<i64> = db.get_event_packages(event_id).await.unwrap().into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(order, vec![second, first]);
+
+        // Without a blueprint the tools run in package order
+        let default = db.get_event_blueprint(event_id).await.unwrap();
+        assert_eq!(default.iter().map(|s| s.tool_id.unwrap()).collect::<Vec<_>>(), vec![second, first]);
+        // Looking at where a session is doesn't save the default
+        let early = db.create_session(event_id, "P-000", None, None).await.unwrap();
+        assert_eq!(db.get_session_next_step(early).await.unwrap().step.unwrap().tool_id, Some(second));
+        assert!(db.get_stored_blueprint(event_id).await.unwrap().is_empty());
+
+        let saved = db.set_event_blueprint(event_id, &[tool(first), pause(120), tool(second)]).await.unwrap();
+        assert!(saved.iter().all(|s| s.id > 0));
+        assert_eq!(saved[1].step_type, StepType::Break);
+
+        let user_id = db.create_user("walker", "hash", "participant").await.unwrap();
+        let session_id = db.create_session(event_id, "P-001", Some(user_id), None).await.unwrap();
+        let next = db.get_session_next_step(session_id).await.unwrap();
+        assert_eq!(next.step.as_ref().unwrap().tool_id, Some(first));
+
+        // Steps can't be jumped, and required ones can't be skipped
+        assert!(matches!(
+            db.complete_session_step(session_id, saved[2].id, false).await,
+            Err(BlueprintError::OutOfOrder { .. })
+        ));
+        assert!(matches!(
+            db.complete_session_step(session_id, saved[0].id, true).await,
+            Err(BlueprintError::RequiredStep(_))
+        ));
+
+        // A tool step needs a result for its tool
+        assert!(matches!(
+            db.complete_session_step(session_id, saved[0].id, false).await,
+            Err(BlueprintError::NoToolResult(tool_id)) if tool_id == first
+        ));
+        for tool_id in [first, second] {
+            let run = db.create_session(event_id, "P-001", Some(user_id), Some(json!({"tool_id": tool_id}))).await.unwrap();
+            db.create_report(run, json!({"total_score": 5}), json!({})).await.unwrap();
+        }
+
+        let next = db.complete_session_step(session_id, saved[0].id, false).await.unwrap();
+        assert_eq!(next.step.as_ref().unwrap().id, saved[1].id);
+        // The break is optional
+        let next = db.complete_session_step(session_id, saved[1].id, true).await.unwrap();
+        assert_eq!(next.step.as_ref().unwrap().tool_id, Some(second));
+        let next = db.complete_session_step(session_id, saved[2].id, false).await.unwrap();
+        assert!(next.finished);
+
+        assert!(matches!(db.get_session_next_step(9999).await, Err(BlueprintError::SessionNotFound(_))));
+    }
+}
@@ -0,0 +1,184 @@
+// Tool Bundle Files
+// Zip container for a tool bundle: bundle.json plus the media files it references
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::collections::HashSet;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+
+use crate::db::bundles::{ToolBundle, BUNDLE_SCHEMA_VERSION};
+use crate::media::{self, StoredMedia};
+
+const MANIFEST_NAME: &str = "bundle.json";
+const MEDIA_PREFIX: &str = "media/";
+
+/// Upper bounds on what a single archive entry may unpack to
+const MAX_MANIFEST_BYTES: u64 = 16 * 1024 * 1024;
+const MAX_MEDIA_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum BundleError {
+    IoError(std::io::Error),
+    ZipError(zip::result::ZipError),
+    SerializationError(serde_json::Error),
+    UnsupportedVersion(i64),
+    InvalidMediaPath(String),
+    EntryTooLarge(String),
+    InvalidContent(String),
+}
+
+impl From<std::io::Error> for BundleError {
+    fn from(err: std::io::Error) -> Self {
+        BundleError::IoError(err)
+    }
+}
+
+impl From<zip::result::ZipError> for BundleError {
+    fn from(err: zip::result::ZipError) -> Self {
+        BundleError::ZipError(err)
+    }
+}
+
+impl From<serde_json::Error> for BundleError {
+    fn from(err: serde_json::Error) -> Self {
+        BundleError::SerializationError(err)
+    }
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleError::IoError(e) => write!(f, "File error: {}", e),
+            BundleError::ZipError(e) => write!(f, "Invalid bundle archive: {}", e),
+            BundleError::SerializationError(e) => write!(f, "Invalid bundle manifest: {}", e),
+            BundleError::UnsupportedVersion(v) => write!(
+                f,
+                "Bundle schema version {} is newer than supported version {}",
+                v, BUNDLE_SCHEMA_VERSION
+            ),
+            BundleError::InvalidMediaPath(p) => write!(f, "Invalid media path in bundle: {}", p),
+            BundleError::EntryTooLarge(p) => write!(f, "Bundle entry {} is too large", p),
+            BundleError::InvalidContent(e) => write!(f, "Invalid bundle content: {}", e),
+        }
+    }
+}
+
+/// Media file read from a bundle. It is only written to the store once the import went through.
+#[derive(Debug)]
+pub struct BundleMedia {
+    pub original_name: String,
+    pub data: Vec<u8>,
+}
+
+/// Write a bundle to `path`, packing every question media file from the store in `media_dir`.
+/// External URLs stay as they are; each stored file is packed once.
+pub fn write_bundle(path: &Path, mut bundle: ToolBundle, media_dir: &Path) -> Result<(), BundleError> {
+    let file = File::create(path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    let mut packed = HashSet::new();
+
+    for subtest in bundle.subtests.iter_mut() {
+        for question in subtest.questions.iter_mut() {
+            let url = match question.media_url.as_deref() {
+                Some(url) => url,
+                None => continue,
+            };
+            let (hash, source) = match (media::hash_from_url(url), media::local_path(media_dir, url)) {
+                (Some(hash), Some(source)) if source.is_file() => (hash.to_string(), source),
+                _ => continue,
+            };
+
+            let entry = format!("{}{}", MEDIA_PREFIX, hash);
+            if packed.insert(entry.clone()) {
+                zip.start_file(entry.as_str(), options)?;
+                zip.write_all(&std::fs::read(&source)?)?;
+            }
+            question.media_name.get_or_insert(hash);
+            question.media_file = Some(entry);
+        }
+    }
+
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&bundle)?)?;
+    zip.finish()?;
+
+    Ok(())
+}
+
+/// Read and validate a bundle from `path`. Each question with packed media is pointed at
+/// the store URL its file will have; the files themselves are returned for `store_media`,
+/// so nothing is written until the caller has imported the content.
+pub fn read_bundle(path: &Path) -> Result<(ToolBundle, Vec<BundleMedia>), BundleError> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+
+    let manifest = read_entry(&mut archive, MANIFEST_NAME, MAX_MANIFEST_BYTES)?;
+    let mut bundle: ToolBundle = serde_json::from_slice(&manifest)?;
+
+    if bundle.schema_version > BUNDLE_SCHEMA_VERSION {
+        return Err(BundleError::UnsupportedVersion(bundle.schema_version));
+    }
+    bundle.validate().map_err(BundleError::InvalidContent)?;
+
+    let mut files: Vec<BundleMedia> = Vec::new();
+    let mut hashes = HashSet::new();
+
+    for subtest in bundle.subtests.iter_mut() {
+        for question in subtest.questions.iter_mut() {
+            let entry = match question.media_file.as_deref() {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            let entry_name = media_entry_name(entry)?;
+            let original_name = question.media_name.clone().unwrap_or_else(|| entry_name.to_string());
+
+            let data = read_entry(&mut archive, entry, MAX_MEDIA_BYTES)?;
+            let hash = media::content_hash(&data);
+
+            question.media_url = Some(media::media_url(&hash));
+            if hashes.insert(hash) {
+                files.push(BundleMedia { original_name, data });
+            }
+        }
+    }
+
+    Ok((bundle, files))
+}
+
+/// Add media read from a bundle to the store in `media_dir`. The stored files are
+/// returned so the caller can register them.
+pub fn store_media(media_dir: &Path, files: &[BundleMedia]) -> Result<Vec<StoredMedia>, BundleError> {
+    let mut stored = Vec::new();
+    for file in files {
+        stored.push(media::store_bytes(media_dir, &file.original_name, &file.data)?);
+    }
+    Ok(stored)
+}
+
+/// Read a whole entry, refusing anything that unpacks to more than `limit` bytes
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str, limit: u64) -> Result<Vec<u8>, BundleError> {
+    let entry = archive.by_name(name)?;
+    if entry.size() > limit {
+        return Err(BundleError::EntryTooLarge(name.to_string()));
+    }
+
+    // The declared size can't be trusted, so stop reading just past the limit
+    let mut data = Vec::new();
+    entry.take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(BundleError::EntryTooLarge(name.to_string()));
+    }
+    Ok(data)
+}
+
+/// Only plain file names under `media/` are accepted
+fn media_entry_name(entry: &str) -> Result<&str, BundleError> {
+    entry.strip_prefix(MEDIA_PREFIX)
+        .filter(|n| !n.is_empty() && !n.contains('/') && !n.contains('\\') && *n != "..")
+        .ok_or_else(|| BundleError::InvalidMediaPath(entry.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,201 @@
+// Tool Bundle Unit Tests
+
+#[cfg(test)]
+mod bundle_tests {
+    use crate::bundle::{write_bundle, read_bundle, store_media, media_entry_name, read_entry, BundleError};
+    use crate::media;
+    use crate::db::Database;
+    use crate::db::bundles::ConflictStrategy;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> Database {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .unwrap();
+
+        Database::new(pool)
+    }
+
+    async fn seed_tool(db: &Database, media_url: &str) -> i64 {
+        let tool_id = db.create_tool("Bundle Tool", "choice", "cognitive", "Bundle test").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Figures", 1, Some(180)).await.unwrap();
+        let q_id = db.create_question(sub_id, "Which figure?", "multiple_choice", serde_json::json!({"choices": ["A", "B"]}), 1).await.unwrap();
+
+        sqlx::query("UPDATE questions SET media_url = ? WHERE id = ?")
+            .bind(media_url)
+            .bind(q_id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO answer_keys (tool_id, question_id, correct_answer) VALUES (?, ?, 'B')")
+            .bind(tool_id)
+            .bind(q_id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        tool_id
+    }
+
+    #[tokio::test]
+    async fn test_bundle_roundtrip_with_media() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("media");
+
+        let db = setup_test_db().await;
+        let file = media::store_bytes(&store, "figure.png", b"fake-png").unwrap();
+        db.register_media_asset(&file).await.unwrap();
+        let tool_id = seed_tool(&db, &media::media_url(&file.content_hash)).await;
+
+        let bundle_path = dir.path().join("tool.zip");
+        let bundle = db.build_tool_bundle(tool_id).await.unwrap();
+        write_bundle(&bundle_path, bundle, &store).unwrap();
+
+        // Reading alone writes nothing
+        let media_dir = dir.path().join("imported");
+        let (bundle, files) = read_bundle(&bundle_path).unwrap();
+        assert!(!media_dir.exists());
+        let question = &bundle.subtests[0].questions[0];
+        assert_eq!(question.answer_key.as_ref().unwrap().correct_answer, "B");
+
+        // Same name exists, so Rename creates a second tool
+        let report = db.import_tool_bundle(&bundle, ConflictStrategy::Rename).await.unwrap();
+        assert_eq!(report.tool_name, "Bundle Tool (2)");
+        assert_eq!(report.questions_created, 1);
+
+        let keys = db.get_answer_keys_by_tool(report.tool_id).await.unwrap();
+        assert_eq!(keys.len(), 1);
+
+        // Imported media lands in the content store
+        let stored = store_media(&media_dir, &files).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].original_name, "figure.png");
+        assert_eq!(stored[0].mime_type, "image/png");
+        assert_eq!(question.media_url.as_deref(), Some(media::media_url(&stored[0].content_hash).as_str()));
+        assert_eq!(std::fs::read(media_dir.join(&stored[0].content_hash)).unwrap(), b"fake-png");
+    }
+
+    #[tokio::test]
+    async fn test_bundle_never_packs_local_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("secret.txt");
+        std::fs::write(&secret, b"not for export").unwrap();
+
+        let db = setup_test_db().await;
+        let tool_id = seed_tool(&db, secret.to_str().unwrap()).await;
+
+        let bundle_path = dir.path().join("tool.zip");
+        write_bundle(&bundle_path, db.build_tool_bundle(tool_id).await.unwrap(), &dir.path().join("media")).unwrap();
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&bundle_path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 1); // bundle.json only
+
+        let (bundle, files) = read_bundle(&bundle_path).unwrap();
+        assert!(files.is_empty());
+        assert!(bundle.subtests[0].questions[0].media_file.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bundle_with_invalid_options_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = setup_test_db().await;
+        let tool_id = seed_tool(&db, "https://example.com/figure.png").await;
+
+        let mut bundle = db.build_tool_bundle(tool_id).await.unwrap();
+        bundle.tool.name = "Broken Tool".to_string();
+        bundle.subtests[0].questions[0].question_type = "ranking".to_string();
+        bundle.subtests[0].questions[0].options = Some(serde_json::json!({"items": ["A", "A"]}));
+
+        assert!(db.import_tool_bundle(&bundle, ConflictStrategy::Rename).await.is_err());
+        assert!(db.get_tool_by_name("Broken Tool").await.is_err());
+
+        let bundle_path = dir.path().join("broken.zip");
+        write_bundle(&bundle_path, bundle, &dir.path().join("media")).unwrap();
+        assert!(matches!(read_bundle(&bundle_path), Err(BundleError::InvalidContent(_))));
+    }
+
+    #[test]
+    fn test_oversized_entry_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        zip.start_file("media/big", zip::write::SimpleFileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut zip, &[0u8; 64]).unwrap();
+        zip.finish().unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(read_entry(&mut archive, "media/big", 64).unwrap().len(), 64);
+        assert!(matches!(read_entry(&mut archive, "media/big", 63), Err(BundleError::EntryTooLarge(_))));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_merge_and_replace() {
+        let db = setup_test_db().await;
+        let tool_id = seed_tool(&db, "https://example.com/figure.png").await;
+        let mut bundle = db.build_tool_bundle(tool_id).await.unwrap();
+
+        // Merge skips the question that already exists
+        let report = db.import_tool_bundle(&bundle, ConflictStrategy::Merge).await.unwrap();
+        assert_eq!(report.tool_id, tool_id);
+        assert_eq!(report.questions_created, 0);
+        assert_eq!(report.questions_skipped, 1);
+
+        // Replace swaps out the content
+        bundle.subtests[0].questions[0].question_text = "Replaced question".to_string();
+        let report = db.import_tool_bundle(&bundle, ConflictStrategy::Replace).await.unwrap();
+        assert_eq!(report.tool_id, tool_id);
+
+        let structure = db.get_full_tool_structure(tool_id).await.unwrap();
+        assert_eq!(structure.subtests.len(), 1);
+        assert_eq!(structure.subtests[0].questions.len(), 1);
+        assert_eq!(structure.subtests[0].questions[0].question_text, "Replaced question");
+    }
+
+    #[tokio::test]
+    async fn test_bundle_packs_shared_store_media_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("media");
+        let db = setup_test_db().await;
+
+        let file = media::store_bytes(&store, "matrix.png", b"matrix").unwrap();
+        db.register_media_asset(&file).await.unwrap();
+        let url = media::media_url(&file.content_hash);
+
+        let tool_id = seed_tool(&db, &url).await;
+        let sub_id = db.get_subtests_by_tool(tool_id).await.unwrap()[0].id;
+        let q_id = db.create_question(sub_id, "Same figure again", "multiple_choice", serde_json::json!({"choices": ["A", "B"]}), 2).await.unwrap();
+        sqlx::query("UPDATE questions SET media_url = ? WHERE id = ?")
+            .bind(&url)
+            .bind(q_id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let bundle_path = dir.path().join("tool.zip");
+        write_bundle(&bundle_path, db.build_tool_bundle(tool_id).await.unwrap(), &store).unwrap();
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&bundle_path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 2); // bundle.json + one media file
+
+        let (bundle, files) = read_bundle(&bundle_path).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].original_name, "matrix.png");
+        for question in &bundle.subtests[0].questions {
+            assert_eq!(question.media_url.as_deref(), Some(url.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_media_path_traversal_rejected() {
+        assert!(media_entry_name("media/../../etc/passwd").is_err());
+        assert!(media_entry_name("other/file.png").is_err());
+        assert!(media_entry_name("media/figure.png").is_ok());
+    }
+}
@@ -0,0 +1,419 @@
+// Bulk Candidate Import
+// Reads candidate rows from CSV/XLSX/JSON, validates them, issues credentials and lays out the printable sheet
+
+use chrono::NaiveDate;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::qr;
+use crate::question_import::{self, ImportError, RowError};
+
+/// Columns recognised in the header row (case-insensitive). Only `name` is required.
+pub const COLUMNS: &[&str] = &["name", "nik", "email", "date_of_birth", "education", "gender", "position"];
+
+/// Other headings seen in client spreadsheets, mapped to the column they mean
+const ALIASES: &[(&str, &str)] = &[
+    ("full_name", "name"),
+    ("nama", "name"),
+    ("national_id", "nik"),
+    ("dob", "date_of_birth"),
+    ("birth_date", "date_of_birth"),
+    ("tanggal_lahir", "date_of_birth"),
+    ("pendidikan", "education"),
+    ("sex", "gender"),
+    ("jenis_kelamin", "gender"),
+    ("position_applied", "position"),
+    ("posisi", "position"),
+];
+
+/// How long printed access tokens stay valid unless told otherwise
+pub const DEFAULT_TOKEN_VALID_DAYS: i64 = 14;
+
+/// Education levels as used on Indonesian forms, lowest first
+pub const EDUCATION_LEVELS: &[&str] = &["SD", "SMP", "SMA", "SMK", "D1", "D2", "D3", "D4", "S1", "S2", "S3"];
+
+/// Credentials avoid characters that are easily misread on paper
+const CREDENTIAL_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghjkmnpqrstuvwxyz23456789";
+const TOKEN_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const PASSWORD_LENGTH: usize = 10;
+const TOKEN_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Default)]
+pub struct CandidateRow {
+    pub row_number: usize,
+    pub name: String,
+    pub nik: String,
+    pub email: String,
+    pub date_of_birth: String,
+    pub education: String,
+    pub gender: String,
+    pub position: String,
+}
+
+/// A validated row, ready for insertion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCandidate {
+    pub row_number: usize,
+    pub full_name: String,
+    pub nik: Option<String>,
+    pub email: Option<String>,
+    /// YYYY-MM-DD
+    pub date_of_birth: Option<String>,
+    /// One of `EDUCATION_LEVELS`
+    pub education: Option<String>,
+    /// male or female
+    pub gender: Option<String>,
+    pub position_applied: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    /// A random password for the normal login
+    Password,
+    /// A single-use access token bound to the event
+    Token,
+}
+
+/// What gets printed for one candidate. Secrets are only ever returned here, never stored in clear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedCredential {
+    pub user_id: i64,
+    pub full_name: String,
+    pub nik: Option<String>,
+    pub username: String,
+    pub password: Option<String>,
+    pub token: Option<String>,
+    pub token_expires_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CandidateImportReport {
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub errors: Vec<RowError>,
+    /// Empty unless every row was valid and the import was committed
+    pub credentials: Vec<IssuedCredential>,
+    pub sheet_html: Option<String>,
+}
+
+pub fn read_rows(path: &Path) -> Result<Vec<CandidateRow>, ImportError> {
+    let is_json = path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let table = if is_json {
+        table_from_json(&std::fs::read_to_string(path)?)?
+    } else {
+        question_import::read_table(path)?
+    };
+    rows_from_table(table)
+}
+
+/// A JSON list of objects, laid out like a sheet so both go through the same column handling
+fn table_from_json(contents: &str) -> Result<Vec<Vec<String>>, ImportError> {
+    let records: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_str(contents)
+        .map_err(|e| ImportError::SpreadsheetError(format!("Expected a JSON list of candidates: {}", e)))?;
+
+    let mut header: Vec<String> = Vec::new();
+    for record in &records {
+        for key in record.keys() {
+            if !header.contains(key) {
+                header.push(key.clone());
+            }
+        }
+    }
+
+    let mut table = vec![header.clone()];
+    for record in &records {
+        table.push(header.iter().map(|key| match record.get(key) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(serde_json::Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        }).collect());
+    }
+    Ok(table)
+}
+
+fn rows_from_table(table: Vec<Vec<String>>) -> Result<Vec<CandidateRow>, ImportError> {
+    let mut lines = table.into_iter();
+    let header: Vec<String> = lines.next()
+        .unwrap_or_default()
+        .iter()
+        .map(|h| {
+            let h = h.trim().to_lowercase().replace(' ', "_");
+            ALIASES.iter().find(|(alias, _)| *alias == h).map(|(_, c)| c.to_string()).unwrap_or(h)
+        })
+        .collect();
+
+    let column = |name: &str| header.iter().position(|h| h == name);
+    if column("name").is_none() {
+        return Err(ImportError::MissingColumn("name".to_string()));
+    }
+    let indexes: Vec<Option<usize>> = COLUMNS.iter().map(|c| column(c)).collect();
+
+    let mut rows = Vec::new();
+    for (i, line) in lines.enumerate() {
+        if line.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+        let cell = |col: usize| -> String {
+            indexes[col]
+                .and_then(|idx| line.get(idx))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        rows.push(CandidateRow {
+            row_number: i + 2,
+            name: cell(0),
+            nik: cell(1),
+            email: cell(2),
+            date_of_birth: cell(3),
+            education: cell(4),
+            gender: cell(5),
+            position: cell(6),
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Validate every row, collecting all problems. NIKs and emails must also be unique within the file.
+pub fn validate_rows(rows: &[CandidateRow]) -> (Vec<NewCandidate>, Vec<RowError>) {
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen_niks = HashSet::new();
+    let mut seen_emails = HashSet::new();
+
+    for row in rows {
+        let mut row_errors = Vec::new();
+        let mut error = |column: &str, message: String| row_errors.push(RowError {
+            row: row.row_number,
+            column: column.to_string(),
+            message,
+        });
+
+        if row.name.is_empty() {
+            error("name", "Name is required".to_string());
+        }
+
+        let nik = (!row.nik.is_empty()).then(|| row.nik.replace([' ', '.'], ""));
+        if let Some(nik) = &nik {
+            if let Err(message) = validate_nik(nik) {
+                error("nik", message);
+            } else if !seen_niks.insert(nik.clone()) {
+                error("nik", format!("NIK {} appears more than once", nik));
+            }
+        }
+
+        let email = (!row.email.is_empty()).then(|| row.email.to_lowercase());
+        if let Some(email) = &email {
+            if !is_valid_email(email) {
+                error("email", format!("'{}' is not a valid email address", email));
+            } else if !seen_emails.insert(email.clone()) {
+                error("email", format!("{} appears more than once", email));
+            }
+        }
+
+        let date_of_birth = if row.date_of_birth.is_empty() {
+            None
+        } else {
+            match parse_birth_date(&row.date_of_birth) {
+                Ok(date) => Some(date.format("%Y-%m-%d").to_string()),
+                Err(message) => {
+                    error("date_of_birth", message);
+                    None
+                }
+            }
+        };
+
+        let education = optional(&row.education, normalize_education).unwrap_or_else(|message| {
+            error("education", message);
+            None
+        });
+        let gender = optional(&row.gender, normalize_gender).unwrap_or_else(|message| {
+            error("gender", message);
+            None
+        });
+
+        if row_errors.is_empty() {
+            valid.push(NewCandidate {
+                row_number: row.row_number,
+                full_name: row.name.clone(),
+                nik,
+                email,
+                date_of_birth,
+                education,
+                gender,
+                position_applied: (!row.position.is_empty()).then(|| row.position.clone()),
+            });
+        } else {
+            errors.append(&mut row_errors);
+        }
+    }
+
+    (valid, errors)
+}
+
+/// Indonesian national ID: 16 digits
+pub fn validate_nik(nik: &str) -> Result<(), String> {
+    if nik.len() != 16 || !nik.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("NIK must be 16 digits, got '{}'", nik));
+    }
+    Ok(())
+}
+
+/// Blank cells are fine; anything else has to normalize
+fn optional(value: &str, normalize: fn(&str) -> Result<String, String>) -> Result<Option<String>, String> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    normalize(value).map(Some)
+}
+
+/// `male`/`female`, also accepting M/F and the Indonesian L/P
+pub fn normalize_gender(value: &str) -> Result<String, String> {
+    match value.trim().to_lowercase().as_str() {
+        "m" | "male" | "l" | "laki-laki" | "pria" => Ok("male".to_string()),
+        "f" | "female" | "p" | "perempuan" | "wanita" => Ok("female".to_string()),
+        _ => Err(format!("'{}' is not a recognised gender, use male or female", value)),
+    }
+}
+
+/// Canonical level code: "s1" and "S-1" become "S1", "SMA/SMK" keeps the first
+pub fn normalize_education(value: &str) -> Result<String, String> {
+    let code: String = value.split('/').next().unwrap_or("")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+
+    EDUCATION_LEVELS.iter()
+        .find(|level| **level == code)
+        .map(|level| level.to_string())
+        .ok_or_else(|| format!("'{}' is not an education level ({})", value, EDUCATION_LEVELS.join(", ")))
+}
+
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => !local.is_empty()
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && !email.contains(char::is_whitespace),
+        None => false,
+    }
+}
+
+/// ISO dates, or day-first as written on Indonesian forms (31/12/1999, 31-12-1999)
+pub fn parse_birth_date(value: &str) -> Result<NaiveDate, String> {
+    let date = ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y"].iter()
+        .find_map(|format| NaiveDate::parse_from_str(value.trim(), format).ok())
+        .ok_or_else(|| format!("'{}' is not a date, use YYYY-MM-DD or DD/MM/YYYY", value))?;
+
+    if date > chrono::Local::now().date_naive() {
+        return Err(format!("Date of birth {} is in the future", date));
+    }
+    Ok(date)
+}
+
+/// `firstname.lastname` in plain lowercase ASCII; the database adds a number when it's taken
+pub fn username_base(full_name: &str) -> String {
+    let words: Vec<String> = full_name.split_whitespace()
+        .map(|w| w.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    match words.as_slice() {
+        [] => "candidate".to_string(),
+        [only] => only.clone(),
+        [first, .., last] => format!("{}.{}", first, last),
+    }
+}
+
+fn random_string(charset: &[u8], length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length).map(|_| charset[rng.gen_range(0..charset.len())] as char).collect()
+}
+
+pub fn generate_password() -> String {
+    random_string(CREDENTIAL_CHARSET, PASSWORD_LENGTH)
+}
+
+/// Grouped in fours so it can be typed from paper: `ABCD-EFGH-JKLM-NPQR`
+pub fn generate_token() -> String {
+    let raw = random_string(TOKEN_CHARSET, TOKEN_LENGTH);
+    raw.as_bytes()
+        .chunks(4)
+        .map(|c| String::from_utf8_lossy(c).to_string())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Tokens are stored hashed; dashes and case don't matter when typed back in
+pub fn hash_token(token: &str) -> String {
+    let normalized: String = token.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    format!("{:x}", Sha256::digest(normalized.as_bytes()))
+}
+
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Printable page of credential slips, one per candidate, to be cut and handed out
+pub fn credential_sheet_html(event_name: &str, event_code: Option<&str>, credentials: &[IssuedCredential]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title><style>\
+         body{{font-family:sans-serif;margin:1cm}}\
+         .slip{{border:1px dashed #999;padding:8px 12px;margin:0 0 8px;page-break-inside:avoid}}\
+         .slip h2{{font-size:14pt;margin:0 0 4px}}\
+         .secret{{font-family:monospace;font-size:13pt}}\
+         .qr{{float:right;width:3cm;height:3cm;margin-left:8px}}.qr svg{{width:100%;height:100%}}\
+         .slip::after{{content:\"\";display:block;clear:both}}\
+         </style></head><body><h1>{title}</h1>",
+        title = escape_html(event_name),
+    );
+    if let Some(code) = event_code {
+        html.push_str(&format!("<p>Event code: <b>{}</b></p>", escape_html(code)));
+    }
+
+    for credential in credentials {
+        html.push_str("<div class=\"slip\">");
+        // Scanning beats typing sixteen characters on a shared test PC
+        if let Some(svg) = credential.token.as_deref().and_then(|token| qr::render_svg(token).ok()) {
+            html.push_str(&format!("<div class=\"qr\">{}</div>", svg));
+        }
+        html.push_str(&format!("<h2>{}</h2>", escape_html(&credential.full_name)));
+        if let Some(nik) = &credential.nik {
+            html.push_str(&format!("<div>NIK: {}</div>", escape_html(nik)));
+        }
+        html.push_str(&format!("<div>Username: <span class=\"secret\">{}</span></div>", escape_html(&credential.username)));
+        if let Some(password) = &credential.password {
+            html.push_str(&format!("<div>Password: <span class=\"secret\">{}</span></div>", escape_html(password)));
+        }
+        if let Some(token) = &credential.token {
+            html.push_str(&format!("<div>Access token: <span class=\"secret\">{}</span></div>", escape_html(token)));
+        }
+        if let Some(expires) = &credential.token_expires_at {
+            let local = chrono::DateTime::parse_from_rfc3339(expires)
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|_| expires.clone());
+            html.push_str(&format!("<div>Valid until {}</div>", escape_html(&local)));
+        }
+        html.push_str("</div>");
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+#[cfg(test)]
+mod tests;
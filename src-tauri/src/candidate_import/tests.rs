@@ -0,0 +1,120 @@
+// Bulk Candidate Import Unit Tests
+
+#[cfg(test)]
+mod candidate_import_tests {
+    use crate::candidate_import::*;
+    use crate::db::Database;
+    use crate::db::participants::PreparedCandidate;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::io::Write;
+
+    fn write_file(suffix: &str, contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_csv_rows_with_aliases() {
+        let file = write_file(".csv",
+            "Nama,NIK,Email,Tanggal Lahir,Pendidikan\n\
+             Siti Rahayu,3171 0123 4567 8901,Siti@Example.com,31/12/1999,S1\n\
+             Budi,,,,\n\
+             ,,,,\n"
+        );
+
+        let rows = read_rows(file.path()).unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let (valid, errors) = validate_rows(&rows);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+        assert_eq!(valid[0].nik.as_deref(), Some("3171012345678901"));
+        assert_eq!(valid[0].email.as_deref(), Some("siti@example.com"));
+        assert_eq!(valid[0].date_of_birth.as_deref(), Some("1999-12-31"));
+        assert_eq!(valid[1].nik, None);
+    }
+
+    #[test]
+    fn test_json_rows_and_validation_errors() {
+        let file = write_file(".json", r#"[
+            {"name": "Ayu", "nik": 3171012345678901, "email": "ayu@example.com"},
+            {"name": "Ayu Twin", "nik": "3171012345678901", "email": "not-an-email"},
+            {"name": "", "date_of_birth": "2999-01-01"},
+            {"name": "Short NIK", "nik": "12345"}
+        ]"#);
+
+        let rows = read_rows(file.path()).unwrap();
+        let (valid, errors) = validate_rows(&rows);
+        assert_eq!(valid.len(), 1);
+        assert_eq!(valid[0].nik.as_deref(), Some("3171012345678901"));
+
+        let problems: Vec<(usize, &str)> = errors.iter().map(|e| (e.row, e.column.as_str())).collect();
+        assert_eq!(problems, vec![(3, "nik"), (3, "email"), (4, "name"), (4, "date_of_birth"), (5, "nik")]);
+    }
+
+    #[test]
+    fn test_name_column_required() {
+        let file = write_file(".csv", "Email\na@example.com\n");
+        assert!(read_rows(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_credentials() {
+        assert_eq!(username_base("Siti  Nur Rahayu"), "siti.rahayu");
+        assert_eq!(username_base("Budi"), "budi");
+        assert_eq!(username_base("Ñ"), "candidate");
+
+        assert_eq!(generate_password().len(), 10);
+        let token = generate_token();
+        assert_eq!(token.len(), 19);
+        assert_eq!(hash_token(&token), hash_token(&token.replace('-', "").to_lowercase()));
+        assert_ne!(generate_token(), token);
+    }
+
+    #[tokio::test]
+    async fn test_import_creates_profiles_and_enrollments() {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let event_id = db.create_event("Import Event", None, None).await.unwrap();
+        db.create_user("siti.rahayu", "hash", "participant").await.unwrap();
+
+        let rows = vec![
+            CandidateRow { row_number: 2, name: "Siti Rahayu".to_string(), nik: "3171012345678901".to_string(), ..Default::default() },
+            CandidateRow { row_number: 3, name: "Budi".to_string(), email: "budi@example.com".to_string(), ..Default::default() },
+        ];
+        let (valid, _) = validate_rows(&rows);
+        let prepared: Vec<PreparedCandidate> = valid.iter().map(|c| PreparedCandidate {
+            candidate: c.clone(),
+            password_hash: None,
+            token_hash: Some(hash_token(&format!("TOKEN{}", c.row_number))),
+        }).collect();
+
+        let created = db.import_candidates(event_id, &prepared, Some("2030-01-01T00:00:00Z")).await.unwrap();
+        // The taken username gets a number
+        assert_eq!(created[0].1, "siti.rahayu2");
+        assert_eq!(created[1].1, "budi");
+
+        assert!(db.nik_registered("3171012345678901").await.unwrap());
+        assert!(db.email_registered("BUDI@example.com").await.unwrap());
+        assert_eq!(db.get_event_participants(event_id).await.unwrap().len(), 2);
+
+        // A failing row rolls back the whole batch
+        let duplicate = vec![prepared[1].clone(), prepared[0].clone()];
+        assert!(db.import_candidates(event_id, &duplicate, None).await.is_err());
+        assert_eq!(db.get_event_participants(event_id).await.unwrap().len(), 2);
+
+        let sheet = credential_sheet_html("Import <Event>", Some("ABC123"), &[IssuedCredential {
+            user_id: created[1].0,
+            full_name: "Budi".to_string(),
+            nik: None,
+            username: "budi".to_string(),
+            password: Some("s3cret".to_string()),
+            token: None,
+            token_expires_at: None,
+        }]);
+        assert!(sheet.contains("Import &lt;Event&gt;"));
+        assert!(sheet.contains("s3cret"));
+    }
+}
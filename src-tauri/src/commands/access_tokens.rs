@@ -0,0 +1,94 @@
+// Access token commands
+// One-time login tokens for enrolled participants, printed as QR codes on invitations
+
+use base64::{Engine as _, engine::general_purpose};
+use tauri::State;
+
+use crate::candidate_import::{self, IssuedCredential};
+use crate::db::Database;
+use crate::db::access_tokens::AccessToken;
+use crate::db::event_lifecycle::to_stored;
+use crate::db::withdrawal::has_left;
+use crate::qr;
+
+#[derive(Debug, serde::Serialize)]
+pub struct IssuedAccessTokens {
+    pub credentials: Vec<IssuedCredential>,
+    /// Printable invitations with a QR code per participant
+    pub sheet_html: String,
+}
+
+/// Issue a fresh token to each enrolled participant (or just `user_ids`). Earlier unused
+/// tokens stop working. The tokens are only ever returned here, so print or send them now.
+#[tauri::command]
+pub async fn issue_access_tokens(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_ids: Option<Vec<i64>>,
+    valid_days: Option<i64>,
+) -> Result<IssuedAccessTokens, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+
+    let participants: Vec<_> = db.get_event_participants(event_id).await.map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|p| !has_left(&p.status))
+        .filter(|p| user_ids.as_ref().map(|ids| ids.contains(&p.user_id)).unwrap_or(true))
+        .collect();
+    if let Some(ids) = &user_ids {
+        if let Some(missing) = ids.iter().find(|id| !participants.iter().any(|p| p.user_id == **id)) {
+            return Err(format!("User {} is not enrolled in this event", missing));
+        }
+    }
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(valid_days.unwrap_or(candidate_import::DEFAULT_TOKEN_VALID_DAYS).max(1));
+    let mut credentials = Vec::with_capacity(participants.len());
+    for participant in participants {
+        let token = candidate_import::generate_token();
+        db.issue_access_token(event_id, participant.user_id, &candidate_import::hash_token(&token), expires_at)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let profile = db.get_participant_profile(participant.user_id).await.map_err(|e| e.to_string())?;
+        credentials.push(IssuedCredential {
+            user_id: participant.user_id,
+            full_name: profile.as_ref().map(|p| p.full_name.clone()).unwrap_or_else(|| participant.username.clone()),
+            nik: profile.and_then(|p| p.nik),
+            username: participant.username,
+            password: None,
+            token: Some(token),
+            token_expires_at: Some(to_stored(expires_at)),
+        });
+    }
+
+    let event = db.get_event_details(event_id).await.map_err(|e| e.to_string())?;
+    // Tokens replace the shared code on these invitations
+    let sheet_html = candidate_import::credential_sheet_html(&event.event_name, None, &credentials);
+
+    Ok(IssuedAccessTokens { credentials, sheet_html })
+}
+
+#[tauri::command]
+pub async fn get_event_access_tokens(db: State<'_, Database>, event_id: i64) -> Result<Vec<AccessToken>, String> {
+    db.get_event_access_tokens(event_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn revoke_access_token(db: State<'_, Database>, token_id: i64) -> Result<(), String> {
+    db.revoke_access_token(token_id).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => "Token not found or already used".to_string(),
+        e => e.to_string(),
+    })
+}
+
+/// QR code for a token: SVG markup, or base64 PNG
+#[tauri::command]
+pub async fn render_access_token_qr(token: String, format: String, scale: Option<u32>) -> Result<String, String> {
+    match format.as_str() {
+        "svg" => qr::render_svg(&token),
+        "png" => {
+            let png = qr::render_png(&token, scale)?;
+            Ok(general_purpose::STANDARD.encode(png))
+        }
+        other => Err(format!("Unsupported QR format '{}'", other)),
+    }
+}
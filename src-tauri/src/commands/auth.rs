@@ -1,6 +1,8 @@
 use tauri::State;
 use crate::db::Database;
 use crate::db::models::User;
+use crate::db::access_tokens::TokenLogin;
+use crate::candidate_import::hash_token;
 use bcrypt::{hash, verify, DEFAULT_COST};
 
 #[tauri::command]
@@ -57,6 +59,17 @@ pub async fn login_user(
     }
 }
 
+/// Log a participant in with a printed access token instead of a password. The token is used up.
+#[tauri::command]
+pub async fn login_with_token(
+    db: State<'_, Database>,
+    token: String
+) -> Result<TokenLogin, String> {
+    db.redeem_access_token(&hash_token(&token), chrono::Utc::now())
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn update_avatar(
     db: State<'_, Database>,
@@ -0,0 +1,62 @@
+// Event blueprint commands
+// Define the order of tools, breaks and instruction pages in an event, and walk candidates through it
+
+use tauri::State;
+use std::collections::HashMap;
+
+use crate::blueprint::{self, BlueprintStep, NextStep};
+use crate::db::Database;
+
+/// The event's blueprint; events without one list their tools in package order
+#[tauri::command]
+pub async fn get_event_blueprint(
+    db: State<'_, Database>,
+    event_id: i64
+) -> Result<Vec<BlueprintStep>, String> {
+    db.get_event_blueprint(event_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_event_blueprint(
+    db: State<'_, Database>,
+    event_id: i64,
+    steps: Vec<BlueprintStep>
+) -> Result<Vec<BlueprintStep>, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    // Progress is recorded per step, so the order is fixed once candidates start
+    let sessions = db.count_event_sessions(event_id).await.map_err(|e| e.to_string())?;
+    if sessions > 0 {
+        return Err("Cannot change the blueprint once sessions have started".to_string());
+    }
+
+    // Subtests come from the event's frozen packages, not the live tools
+    let mut subtests: HashMap<i64, Vec<i64>> = HashMap::new();
+    for (tool_id, _, _) in db.get_event_packages(event_id).await.map_err(|e| e.to_string())? {
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.map_err(|e| e.to_string())?;
+        subtests.insert(tool_id, snapshot.structure.subtests.iter().map(|s| s.subtest.id).collect());
+    }
+    blueprint::validate_blueprint(&steps, &subtests)?;
+
+    db.set_event_blueprint(event_id, &steps).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_next_step(
+    db: State<'_, Database>,
+    session_id: i64
+) -> Result<NextStep, String> {
+    db.get_session_next_step(session_id).await.map_err(|e| e.to_string())
+}
+
+/// Finish (or skip, if optional) the current step and return the next one
+#[tauri::command]
+pub async fn complete_step(
+    db: State<'_, Database>,
+    session_id: i64,
+    step_id: i64,
+    skipped: Option<bool>
+) -> Result<NextStep, String> {
+    db.complete_session_step(session_id, step_id, skipped.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
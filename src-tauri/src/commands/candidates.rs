@@ -0,0 +1,149 @@
+// Candidate commands
+// Demographic profiles, and bulk import of candidates into an event with generated credentials
+
+use std::path::PathBuf;
+use tauri::State;
+
+use crate::candidate_import::{self, CandidateImportReport, CredentialKind, IssuedCredential};
+use crate::db::Database;
+use crate::db::event_lifecycle::to_stored;
+use crate::db::participants::{ParticipantProfile, PreparedCandidate};
+use crate::question_import::RowError;
+
+#[tauri::command]
+pub async fn get_participant_profile(db: State<'_, Database>, user_id: i64) -> Result<Option<ParticipantProfile>, String> {
+    db.get_participant_profile(user_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_participant_profile(
+    db: State<'_, Database>,
+    user_id: i64,
+    mut profile: ParticipantProfile,
+) -> Result<ParticipantProfile, String> {
+    profile.normalize()?;
+
+    db.save_participant_profile(user_id, &profile).await.map_err(|e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => "NIK is already registered to another participant".to_string(),
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => "User not found".to_string(),
+        e => e.to_string(),
+    })
+}
+
+#[tauri::command]
+pub async fn delete_participant_profile(db: State<'_, Database>, user_id: i64) -> Result<(), String> {
+    db.delete_participant_profile(user_id).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => "Participant has no profile".to_string(),
+        e => e.to_string(),
+    })
+}
+
+/// Import candidates from a CSV/XLSX/JSON file, creating their accounts and enrolling
+/// them in the event. Nothing is written unless every row is valid; the report then
+/// carries each candidate's credentials and a printable sheet, the only time they're shown.
+#[tauri::command]
+pub async fn import_candidates(
+    db: State<'_, Database>,
+    event_id: i64,
+    path: String,
+    credential: CredentialKind,
+    token_valid_days: Option<i64>,
+    dry_run: Option<bool>,
+) -> Result<CandidateImportReport, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+
+    let rows = candidate_import::read_rows(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+    let (mut valid, mut errors) = candidate_import::validate_rows(&rows);
+
+    // Candidates who are already registered can't be imported a second time
+    let mut registered = Vec::new();
+    for candidate in &valid {
+        if let Some(nik) = &candidate.nik {
+            if db.nik_registered(nik).await.map_err(|e| e.to_string())? {
+                registered.push(RowError { row: candidate.row_number, column: "nik".to_string(), message: format!("NIK {} is already registered", nik) });
+            }
+        }
+        if let Some(email) = &candidate.email {
+            if db.email_registered(email).await.map_err(|e| e.to_string())? {
+                registered.push(RowError { row: candidate.row_number, column: "email".to_string(), message: format!("{} is already registered", email) });
+            }
+        }
+    }
+    valid.retain(|c| !registered.iter().any(|e| e.row == c.row_number));
+    errors.append(&mut registered);
+    errors.sort_by_key(|e| e.row);
+
+    let mut report = CandidateImportReport {
+        total_rows: rows.len(),
+        valid_rows: valid.len(),
+        errors,
+        credentials: Vec::new(),
+        sheet_html: None,
+    };
+    if !report.errors.is_empty() || dry_run.unwrap_or(false) || valid.is_empty() {
+        return Ok(report);
+    }
+
+    if let Some(remaining) = db.remaining_places(event_id).await.map_err(|e| e.to_string())? {
+        if valid.len() as i64 > remaining {
+            return Err(format!("The event only has room for {} more participants, the file has {}", remaining, valid.len()));
+        }
+    }
+
+    // bcrypt is deliberately slow, so hash the whole batch off the async runtime
+    let secrets: Vec<String> = valid.iter()
+        .map(|_| match credential {
+            CredentialKind::Password => candidate_import::generate_password(),
+            CredentialKind::Token => candidate_import::generate_token(),
+        })
+        .collect();
+    let hashes = {
+        let secrets = secrets.clone();
+        tokio::task::spawn_blocking(move || -> Result<Vec<String>, String> {
+            secrets.iter()
+                .map(|secret| match credential {
+                    CredentialKind::Password => bcrypt::hash(secret, bcrypt::DEFAULT_COST).map_err(|e| e.to_string()),
+                    CredentialKind::Token => Ok(candidate_import::hash_token(secret)),
+                })
+                .collect()
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    };
+
+    let prepared: Vec<PreparedCandidate> = valid.iter().zip(hashes)
+        .map(|(candidate, hash)| PreparedCandidate {
+            candidate: candidate.clone(),
+            password_hash: (credential == CredentialKind::Password).then(|| hash.clone()),
+            token_hash: (credential == CredentialKind::Token).then_some(hash),
+        })
+        .collect();
+    let token_expires_at = (credential == CredentialKind::Token).then(|| {
+        to_stored(chrono::Utc::now() + chrono::Duration::days(token_valid_days.unwrap_or(candidate_import::DEFAULT_TOKEN_VALID_DAYS).max(1)))
+    });
+
+    let created = db.import_candidates(event_id, &prepared, token_expires_at.as_deref())
+        .await
+        .map_err(|e| format!("Import failed, no candidates were created: {}", e))?;
+
+    report.credentials = valid.iter().zip(created).zip(secrets)
+        .map(|((candidate, (user_id, username)), secret)| IssuedCredential {
+            user_id,
+            full_name: candidate.full_name.clone(),
+            nik: candidate.nik.clone(),
+            username,
+            password: (credential == CredentialKind::Password).then(|| secret.clone()),
+            token: (credential == CredentialKind::Token).then_some(secret),
+            token_expires_at: token_expires_at.clone(),
+        })
+        .collect();
+
+    let event = db.get_event_details(event_id).await.map_err(|e| e.to_string())?;
+    report.sheet_html = Some(candidate_import::credential_sheet_html(
+        &event.event_name,
+        event.event_code.as_deref(),
+        &report.credentials,
+    ));
+
+    Ok(report)
+}
@@ -10,11 +10,27 @@ pub async fn generate_ai_review(db: State<'_, Database>, result_id: i64) -> Resu
     let result = db.get_test_result_by_id(result_id).await.map_err(|e| e.to_string())?;
     
     // 2. Extract context
-    let candidate_name = &result.candidate_name;
+    let candidate_name = result.full_name.as_ref().unwrap_or(&result.candidate_name);
+    // Only what's on file; norms differ by age and education, so the model should know them
+    let profile = [
+        result.age.map(|age| format!("{} years old", age)),
+        result.gender.clone(),
+        result.education.as_ref().map(|e| format!("education {}", e)),
+        result.position_applied.as_ref().map(|p| format!("applying for {}", p)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    let profile = if profile.is_empty() { "Not recorded".to_string() } else { profile.join(", ") };
     let tool_name = &result.tool_name;
     let score = result.score;
     let raw_score = result.raw_score;
     let percentile = result.percentile.unwrap_or(0);
+    let administration = if result.standard_administration {
+        "Standard"
+    } else {
+        "Non-standard (testing accommodations were given; compare against norms with caution)"
+    };
     
     // Construct Enhanced Psychometric Prompt
     let prompt = format!(
@@ -23,10 +39,12 @@ pub async fn generate_ai_review(db: State<'_, Database>, result_id: i64) -> Resu
         Avoid technical jargon where possible, and focus on practical implications.
 
 Candidate Name: {}
+Candidate Profile: {}
 Assessment Tool: {}
 Final Score: {}
 Raw Score: {}
 Percentile: {}%
+Administration: {}
 
 Please provide the interpretation in a structured way:
 1. Executive Summary
@@ -34,7 +52,7 @@ Please provide the interpretation in a structured way:
 3. Areas for Development
 
 Interpretation:",
-        candidate_name, tool_name, score, raw_score, percentile
+        candidate_name, profile, tool_name, score, raw_score, percentile, administration
     );
 
     // 3. Call Ollama (gemma2:2b)
@@ -117,6 +135,7 @@ pub async fn create_event(
         println!("DEBUG: tool_ids is Some. Length: {}", tools.len());
         if !tools.is_empty() {
             println!("DEBUG: Processing tool ids: {:?}", tools);
+            db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
             db.add_tools_to_event(event_id, tools)
                 .await
                 .map_err(|e| {
@@ -153,7 +172,7 @@ pub async fn create_candidate(
     db: State<'_, Database>,
     username: String,
     password: String,
-    _name: Option<String>
+    name: Option<String>
 ) -> Result<i64, String> {
     // Basic password hashing
     let hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST).map_err(|e| e.to_string())?;
@@ -161,14 +180,14 @@ pub async fn create_candidate(
     // We strictly create "participant" role here
     let role = "participant";
     
-    // Note: Database::create_user takes (username, password_hash, role)
-    // We might want to store 'name' somewhere? 
-    // Checking User struct: id, username, role. No 'name' field in current User struct.
-    // We'll stick to username for now, or maybe the user meant username IS the name.
-    // If 'name' is distinct from 'username', we need a column for it.
-    // For now I'll just use username.
-    
-    db.create_user(&username, &hash, role).await.map_err(|e| e.to_string())
+    let user_id = db.create_user(&username, &hash, role).await.map_err(|e| e.to_string())?;
+
+    // The full name lives in the participant profile
+    if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+        db.set_participant_name(user_id, name.trim()).await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(user_id)
 }
 
 #[tauri::command]
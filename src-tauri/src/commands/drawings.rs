@@ -0,0 +1,93 @@
+// Drawing answer commands
+// Save stroke data, render it for reports and replay the order it was drawn in
+
+use tauri::State;
+use base64::{Engine as _, engine::general_purpose};
+
+use crate::db::Database;
+use crate::db::drawings::DrawingAnswer;
+use crate::db::session_lifecycle::SessionStatus;
+use crate::drawing::{self, Drawing, ReplayTimeline};
+
+#[tauri::command]
+pub async fn save_drawing_answer(
+    db: State<'_, Database>,
+    session_id: i64,
+    question_id: i64,
+    drawing: Drawing,
+) -> Result<i64, String> {
+    drawing.validate()?;
+
+    let session = db.get_session_by_id(session_id).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => format!("Session {} not found", session_id),
+        e => e.to_string(),
+    })?;
+    if session.status != SessionStatus::Active.as_str() {
+        return Err(format!("Session {} is {}; answers can only be saved while it is active", session_id, session.status));
+    }
+
+    // Check against the content the event delivers, not the live (possibly edited) question
+    let question = db.get_event_question(session.event_id, question_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Question {} is not part of this session's event", question_id))?;
+    if question.question_type != "drawing" {
+        return Err(format!("Question {} is not a drawing question", question_id));
+    }
+
+    db.save_drawing_answer(session_id, question_id, &drawing)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_drawing_answer(
+    db: State<'_, Database>,
+    session_id: i64,
+    question_id: i64,
+) -> Result<Option<DrawingAnswer>, String> {
+    db.get_drawing_answer(session_id, question_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Render a drawing as `svg` markup or a base64 `png`. `at_ms` renders the drawing as it
+/// was that far into the session, for replay scrubbing.
+#[tauri::command]
+pub async fn render_drawing(
+    db: State<'_, Database>,
+    session_id: i64,
+    question_id: i64,
+    format: String,
+    at_ms: Option<i64>,
+    scale: Option<f32>,
+) -> Result<String, String> {
+    let answer = db.get_drawing_answer(session_id, question_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No drawing saved for this question".to_string())?;
+    let drawing = answer.drawing.at(at_ms);
+
+    match format.as_str() {
+        "svg" => Ok(drawing::render_svg(&drawing)),
+        "png" => {
+            let png = drawing::render_png(&drawing, scale.unwrap_or(1.0))?;
+            Ok(general_purpose::STANDARD.encode(png))
+        }
+        other => Err(format!("Unsupported drawing format '{}'", other)),
+    }
+}
+
+#[tauri::command]
+pub async fn get_drawing_replay(
+    db: State<'_, Database>,
+    session_id: i64,
+    question_id: i64,
+) -> Result<ReplayTimeline, String> {
+    let answer = db.get_drawing_answer(session_id, question_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No drawing saved for this question".to_string())?;
+
+    Ok(answer.drawing.replay_timeline())
+}
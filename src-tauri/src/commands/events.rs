@@ -1,5 +1,15 @@
 use crate::db::Database;
 use crate::db::models::{EventDetails, ParticipantInfo, Event};
+use crate::db::accommodations::Accommodations;
+use crate::db::enrollment::{self, EnrollmentError, EnrollmentOutcome, WaitlistEntry};
+use crate::db::event_lifecycle::{EventSchedule, EventStatus};
+use crate::db::event_clone::CloneEventOptions;
+use crate::db::event_codes::{EventCodeError, EventCodeHistoryEntry, EventCodeStatus};
+use crate::db::progress::ParticipantProgress;
+use crate::db::retakes::{AttemptSummary, ReportAttempt, RetakePolicy};
+use crate::db::withdrawal::ExitOutcome;
+use crate::db::statistics::EventStatistics;
+use serde::Serialize;
 use tauri::State;
 
 #[tauri::command]
@@ -22,30 +32,72 @@ pub async fn get_event_participants(
         .map_err(|e| format!("Failed to get participants: {}", e))
 }
 
+/// Completion, timing, score distributions and protocol quality for an event
+#[tauri::command]
+pub async fn get_event_statistics(
+    db: State<'_, Database>,
+    event_id: i64,
+) -> Result<EventStatistics, String> {
+    db.get_event_statistics(event_id).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => "Event not found".to_string(),
+        e => format!("Failed to get event statistics: {}", e),
+    })
+}
+
+/// Which of the event's tools a participant has finished
+#[tauri::command]
+pub async fn get_participant_progress(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+) -> Result<ParticipantProgress, String> {
+    db.get_participant_progress(event_id, user_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => "Participant is not enrolled in this event".to_string(),
+            e => e.to_string(),
+        })
+}
+
+/// Why joining an event failed. `kind` is what the UI branches on (`event_full`,
+/// `deadline_passed`, `expired`, ...); `message` is shown as is.
+#[derive(Debug, Serialize)]
+pub struct EnrollmentFailure {
+    pub kind: String,
+    pub message: String,
+}
+
+impl From<EnrollmentError> for EnrollmentFailure {
+    fn from(err: EnrollmentError) -> Self {
+        EnrollmentFailure { kind: err.kind().to_string(), message: err.to_string() }
+    }
+}
+
+impl From<EventCodeError> for EnrollmentFailure {
+    fn from(err: EventCodeError) -> Self {
+        EnrollmentFailure { kind: err.kind().to_string(), message: err.to_string() }
+    }
+}
+
+impl From<sqlx::Error> for EnrollmentFailure {
+    fn from(err: sqlx::Error) -> Self {
+        EnrollmentError::DatabaseError(err).into()
+    }
+}
+
 #[tauri::command]
 pub async fn enroll_candidate_to_event(
     db: State<'_, Database>,
     event_code: String,
     user_id: i64,
-) -> Result<i64, String> {
+) -> Result<EnrollmentOutcome, EnrollmentFailure> {
     // Find event by code
-    let event = db.get_event_by_code(&event_code)
-        .await
-        .map_err(|_| "Invalid event code".to_string())?;
-    
-    // Check if already enrolled
-    let already_enrolled = db.check_participant_access(event.id, user_id)
-        .await
-        .map_err(|e| format!("Failed to check enrollment: {}", e))?;
-    
-    if already_enrolled {
-        return Err("Already enrolled in this event".to_string());
-    }
-    
-    // Enroll
-    db.add_participant_to_event(event.id, user_id, None)
-        .await
-        .map_err(|e| format!("Failed to enroll: {}", e))
+    let event = db.get_event_by_code(&event_code).await?;
+
+    // Candidates who find the event full are put on its waitlist
+    let outcome = db.enroll_participant(event.id, user_id, None, true).await?;
+    db.record_event_code_use(event.id).await?;
+    Ok(outcome)
 }
 
 #[tauri::command]
@@ -53,11 +105,9 @@ pub async fn add_participant_to_event(
     db: State<'_, Database>,
     event_id: i64,
     user_id: i64,
-) -> Result<(), String> {
-    db.add_participant_to_event(event_id, user_id, None)
-        .await
-        .map_err(|e| format!("Failed to add participant: {}", e))?;
-    Ok(())
+    waitlist: Option<bool>,
+) -> Result<EnrollmentOutcome, EnrollmentFailure> {
+    Ok(db.enroll_participant(event_id, user_id, None, waitlist.unwrap_or(false)).await?)
 }
 
 #[tauri::command]
@@ -66,22 +116,194 @@ pub async fn remove_participant_from_event(
     event_id: i64,
     user_id: i64,
 ) -> Result<(), String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
     db.remove_participant_from_event(event_id, user_id)
         .await
         .map_err(|e| format!("Failed to remove participant: {}", e))
 }
 
+/// Record that a participant withdrew. Open sessions are ended and their place is released.
+#[tauri::command]
+pub async fn withdraw_participant(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+    reason: String,
+) -> Result<ExitOutcome, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.withdraw_participant(event_id, user_id, &reason)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn mark_participant_no_show(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+    reason: Option<String>,
+) -> Result<ExitOutcome, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.mark_participant_no_show(event_id, user_id, reason.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_event_waitlist(
+    db: State<'_, Database>,
+    event_id: i64,
+) -> Result<Vec<WaitlistEntry>, String> {
+    db.get_event_waitlist(event_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn leave_event_waitlist(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+) -> Result<(), String> {
+    db.leave_waitlist(event_id, user_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => "Candidate is not on the waitlist for this event".to_string(),
+            e => format!("Failed to leave waitlist: {}", e),
+        })
+}
+
+/// Returns the candidates promoted off the waitlist by a larger capacity
+#[tauri::command]
+pub async fn set_event_enrollment_settings(
+    db: State<'_, Database>,
+    event_id: i64,
+    max_participants: Option<i64>,
+    enrollment_deadline: Option<String>,
+) -> Result<Vec<i64>, String> {
+    enrollment::validate_enrollment_settings(max_participants, enrollment_deadline.as_deref())?;
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.set_event_enrollment_settings(event_id, max_participants, enrollment_deadline)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => "Event not found".to_string(),
+            e => format!("Failed to save enrollment settings: {}", e),
+        })
+}
+
+#[tauri::command]
+pub async fn get_event_schedule(
+    db: State<'_, Database>,
+    event_id: i64,
+) -> Result<EventSchedule, String> {
+    db.get_event_schedule(event_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Times without an offset are read in `timezone` ("+07:00", "WIB", ...). Leave both empty to unschedule.
+#[tauri::command]
+pub async fn set_event_schedule(
+    db: State<'_, Database>,
+    event_id: i64,
+    starts_at: Option<String>,
+    ends_at: Option<String>,
+    timezone: Option<String>,
+) -> Result<EventSchedule, String> {
+    db.set_event_schedule(event_id, starts_at.as_deref(), ends_at.as_deref(), timezone.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_event_status(
+    db: State<'_, Database>,
+    event_id: i64,
+    status: String,
+) -> Result<EventSchedule, String> {
+    let status = EventStatus::parse(&status).ok_or_else(|| format!("Unknown event status '{}'", status))?;
+    db.set_event_status(event_id, status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Let a participant start over. Earlier sessions and reports are kept as a previous
+/// attempt; returns the new attempt number.
 #[tauri::command]
 pub async fn reset_participant(
     db: State<'_, Database>,
     event_id: i64,
     user_id: i64
-) -> Result<(), String> {
-    db.reset_participant_session(event_id, user_id)
+) -> Result<i64, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.start_retake(event_id, user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_retake_policy(db: State<'_, Database>, event_id: i64) -> Result<RetakePolicy, String> {
+    db.get_retake_policy(event_id).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => "Event not found".to_string(),
+        e => e.to_string(),
+    })
+}
+
+#[tauri::command]
+pub async fn set_retake_policy(
+    db: State<'_, Database>,
+    event_id: i64,
+    max_attempts: Option<i64>,
+    cooldown_hours: Option<i64>,
+    report_attempt: String,
+) -> Result<RetakePolicy, String> {
+    let report_attempt = ReportAttempt::parse(&report_attempt)
+        .ok_or_else(|| format!("Unknown report attempt '{}', expected best, latest or first", report_attempt))?;
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.set_retake_policy(event_id, &RetakePolicy { max_attempts, cooldown_hours, report_attempt })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_participant_attempts(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+) -> Result<Vec<AttemptSummary>, String> {
+    db.get_participant_attempts(event_id, user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_participant_accommodations(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+) -> Result<Accommodations, String> {
+    db.get_participant_accommodations(event_id, user_id)
         .await
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn set_participant_accommodations(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+    accommodations: Accommodations,
+) -> Result<(), String> {
+    accommodations.validate()?;
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.set_participant_accommodations(event_id, user_id, &accommodations)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => "Candidate is not enrolled in this event".to_string(),
+            e => format!("Failed to save accommodations: {}", e),
+        })
+}
+
 #[tauri::command]
 pub async fn get_my_events(
     db: State<'_, Database>,
@@ -92,21 +314,82 @@ pub async fn get_my_events(
         .map_err(|e| e.to_string())
 }
 
+/// Rotate the event's code. The old one is kept in the code history and stops working.
 #[tauri::command]
 pub async fn generate_event_code_cmd(
     db: State<'_, Database>,
     event_id: i64,
+    rotated_by: i64,
 ) -> Result<String, String> {
-    let code = db.generate_event_code()
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.rotate_event_code(event_id, rotated_by)
         .await
-        .map_err(|e| format!("Failed to generate code: {}", e))?;
-    
-    // Update event with code
-    db.update_event_code(event_id, &code)
+        .map_err(|e| format!("Failed to generate code: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_event_code_status(
+    db: State<'_, Database>,
+    event_id: i64,
+) -> Result<EventCodeStatus, String> {
+    db.get_event_code_status(event_id).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => "Event not found".to_string(),
+        e => e.to_string(),
+    })
+}
+
+/// Limit when the event code can be used; times are in the event's timezone
+#[tauri::command]
+pub async fn set_event_code_validity(
+    db: State<'_, Database>,
+    event_id: i64,
+    valid_from: Option<String>,
+    expires_at: Option<String>,
+) -> Result<EventCodeStatus, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.set_event_code_validity(event_id, valid_from.as_deref(), expires_at.as_deref())
         .await
-        .map_err(|e| format!("Failed to update event: {}", e))?;
-    
-    Ok(code)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn revoke_event_code(
+    db: State<'_, Database>,
+    event_id: i64,
+    revoked_by: i64,
+) -> Result<EventCodeStatus, String> {
+    db.revoke_event_code(event_id, revoked_by).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_event_code_history(
+    db: State<'_, Database>,
+    event_id: i64,
+) -> Result<Vec<EventCodeHistoryEntry>, String> {
+    db.get_event_code_history(event_id).await.map_err(|e| e.to_string())
+}
+
+/// Copy an event's packages, order and settings into a new draft with its own code
+#[tauri::command]
+pub async fn clone_event(
+    db: State<'_, Database>,
+    event_id: i64,
+    options: Option<CloneEventOptions>,
+) -> Result<EventDetails, String> {
+    let options = options.unwrap_or_default();
+    let new_id = db.clone_event(event_id, &options)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => "Event not found".to_string(),
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                "An event with that name already exists".to_string()
+            }
+            e => format!("Failed to clone event: {}", e),
+        })?;
+
+    db.get_event_details(new_id)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -114,6 +397,10 @@ pub async fn delete_events(
     db: State<'_, Database>,
     event_ids: Vec<i64>,
 ) -> Result<(), String> {
+    // Check them all first so an archived event doesn't leave the batch half deleted
+    for id in &event_ids {
+        db.ensure_event_editable(*id).await.map_err(|e| e.to_string())?;
+    }
     for id in event_ids {
         db.delete_event(id).await.map_err(|e| e.to_string())?;
     }
@@ -1,4 +1,5 @@
 use crate::db::Database;
+use crate::db::session_lifecycle::SessionStatus;
 use tauri::State;
 
 #[tauri::command]
@@ -19,9 +20,11 @@ pub async fn create_kraepelin_session(
         "test_type": "kraepelin"
     });
     
+    db.check_session_window(event_id_val).await.map_err(|e| e.to_string())?;
+
     // Create session
     let session_id = db
-        .create_session(event_id_val, &participant_id, Some(metadata))
+        .create_session(event_id_val, &participant_id, Some(user_id), Some(metadata))
         .await
         .map_err(|e| format!("Failed to create session: {}", e))?;
     
@@ -71,8 +74,7 @@ pub async fn complete_kraepelin_session(
         .await
         .map_err(|e| format!("Failed to calculate statistics: {}", e))?;
     
-    // For now, just log the completion
-    // In a full implementation, you'd update session status and create a report
+    // Log the completion
     println!("Kraepelin session {} completed:", session_id);
     println!("  Total correct: {}", total_correct);
     println!("  Total answered: {}", total_answered);
@@ -81,8 +83,22 @@ pub async fn complete_kraepelin_session(
     println!("  Avg correct per column: {:.2}", stats.avg_correct_per_column);
     println!("  Avg time per column: {:.2}s", stats.avg_time_per_column);
     
-    // TODO: Implement session status update and report creation
-    // This would require adding those methods to the Database struct
+    db.transition_session(session_id, SessionStatus::Completed, None)
+        .await
+        .map_err(|e| format!("Failed to complete session: {}", e))?;
+
+    let scores = serde_json::json!({
+        "total_score": total_correct,
+        "raw_score": total_answered,
+        "accuracy": accuracy,
+        "total_columns": stats.total_columns,
+        "avg_correct_per_column": stats.avg_correct_per_column,
+        "avg_time_per_column": stats.avg_time_per_column,
+    });
+
+    db.create_report(session_id, scores, serde_json::json!({}))
+        .await
+        .map_err(|e| format!("Failed to create report: {}", e))?;
     
     Ok(())
 }
@@ -0,0 +1,95 @@
+// Localized content commands
+// Translations per locale, language choice per event/candidate, and translation completeness
+
+use tauri::State;
+
+use crate::db::Database;
+use crate::localization::{self, TranslationReport};
+
+#[tauri::command]
+pub async fn set_tool_content_locale(
+    db: State<'_, Database>,
+    tool_id: i64,
+    locale: String
+) -> Result<(), String> {
+    db.set_tool_content_locale(tool_id, &locale).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_question_translation(
+    db: State<'_, Database>,
+    question_id: i64,
+    locale: String,
+    text: String,
+    options: Option<serde_json::Value>
+) -> Result<(), String> {
+    let question = db.get_question_by_id(question_id).await.map_err(|e| e.to_string())?;
+    localization::validate_translated_options(question.options.as_ref(), options.as_ref())?;
+
+    db.upsert_question_translation(question_id, &locale, &text, options).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_question_translation(
+    db: State<'_, Database>,
+    question_id: i64,
+    locale: String
+) -> Result<(), String> {
+    db.delete_question_translation(question_id, &locale).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_subtest_translation(
+    db: State<'_, Database>,
+    subtest_id: i64,
+    locale: String,
+    instructions: serde_json::Value
+) -> Result<(), String> {
+    db.upsert_subtest_translation(subtest_id, &locale, instructions).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_subtest_translation(
+    db: State<'_, Database>,
+    subtest_id: i64,
+    locale: String
+) -> Result<(), String> {
+    db.delete_subtest_translation(subtest_id, &locale).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_event_locale(
+    db: State<'_, Database>,
+    event_id: i64,
+    locale: Option<String>
+) -> Result<(), String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.set_event_locale(event_id, locale.as_deref()).await.map_err(|e| e.to_string())
+}
+
+/// Override the event language for one candidate
+#[tauri::command]
+pub async fn set_participant_locale(
+    db: State<'_, Database>,
+    event_id: i64,
+    user_id: i64,
+    locale: Option<String>
+) -> Result<(), String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.set_participant_locale(event_id, user_id, locale.as_deref()).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => "Candidate is not enrolled in this event".to_string(),
+        e => e.to_string(),
+    })
+}
+
+/// How much of a tool is translated into each locale. Leave `locales` empty to report every locale in use.
+#[tauri::command]
+pub async fn get_translation_report(
+    db: State<'_, Database>,
+    tool_id: i64,
+    locales: Option<Vec<String>>
+) -> Result<TranslationReport, String> {
+    let structure = db.get_full_tool_structure(tool_id).await.map_err(|e| e.to_string())?;
+    let translations = db.get_tool_translations(tool_id).await.map_err(|e| e.to_string())?;
+    Ok(localization::translation_report(&structure, &translations, locales.as_deref()))
+}
@@ -0,0 +1,94 @@
+// Question media commands
+// Upload files into the content-addressed store (Admin) and pre-download them for offline tests (Candidate)
+
+use tauri::{AppHandle, Manager, State};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::commands::sync::SyncState;
+use crate::db::Database;
+use crate::db::candidate::CandidateDatabase;
+use crate::db::media::MediaAsset;
+use crate::media;
+use crate::sync::{MediaDownloadReport, SyncService};
+
+/// Directory of the media store in the app data dir
+pub(crate) fn media_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_handle.path().app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("media"))
+}
+
+fn media_cache_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_handle.path().app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("media_cache"))
+}
+
+/// Add a file to the media store. Use the returned asset's URL as a question's `media_url`.
+#[tauri::command]
+pub async fn upload_media(
+    app_handle: AppHandle,
+    db: State<'_, Database>,
+    path: String
+) -> Result<serde_json::Value, String> {
+    let stored = media::store_file(&media_dir(&app_handle)?, Path::new(&path))
+        .map_err(|e| format!("Failed to store media: {}", e))?;
+    let asset = db.register_media_asset(&stored).await.map_err(|e| e.to_string())?;
+
+    Ok(asset_json(&asset))
+}
+
+#[tauri::command]
+pub async fn get_media_asset(
+    db: State<'_, Database>,
+    content_hash: String
+) -> Result<Option<serde_json::Value>, String> {
+    let asset = db.get_media_asset(&content_hash).await.map_err(|e| e.to_string())?;
+    Ok(asset.as_ref().map(asset_json))
+}
+
+fn asset_json(asset: &MediaAsset) -> serde_json::Value {
+    serde_json::json!({
+        "asset": asset,
+        "url": asset.url(),
+    })
+}
+
+/// Download all media for an event so the test can run without a connection (Candidate only)
+#[tauri::command]
+pub async fn predownload_event_media(
+    app_handle: AppHandle,
+    event_id: i64,
+    state: State<'_, SyncState>,
+    db_state: State<'_, Arc<CandidateDatabase>>,
+) -> Result<MediaDownloadReport, String> {
+    let server_url = state.server_url.lock().await.clone()
+        .ok_or("Server URL not set".to_string())?;
+
+    let db = (**db_state).clone();
+    let sync_service = SyncService::new(db, server_url);
+
+    sync_service.download_event_media(event_id, &media_cache_dir(&app_handle)?)
+        .await
+        .map_err(|e| format!("Media download failed: {:?}", e))
+}
+
+/// Local file for a question's `media_url`, if it has been downloaded (Candidate only)
+#[tauri::command]
+pub async fn get_cached_media_path(
+    media_url: String,
+    db_state: State<'_, Arc<CandidateDatabase>>,
+) -> Result<Option<String>, String> {
+    let hash = match media::hash_from_url(&media_url) {
+        Some(hash) => hash,
+        None => return Ok(None),
+    };
+
+    let cached = db_state.get_cached_media(hash).await
+        .map_err(|e| format!("Database error: {:?}", e))?;
+
+    Ok(cached
+        .filter(|c| Path::new(&c.file_path).is_file())
+        .map(|c| c.file_path))
+}
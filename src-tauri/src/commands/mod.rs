@@ -6,6 +6,16 @@ pub mod kraepelin;
 pub mod events;
 pub mod server;
 pub mod sync;
+pub mod sessions;
+pub mod resume;
+pub mod media;
+pub mod localization;
+pub mod drawings;
+pub mod rating;
+pub mod blueprint;
+pub mod candidates;
+pub mod access_tokens;
+pub mod ranking;
 
 use tauri::State;
 use crate::db::Database;
@@ -20,10 +30,12 @@ pub async fn get_tools(db: State<'_, Database>) -> Result<Vec<Tool>, String> {
 pub async fn create_session(
     event_id: i64, 
     participant_id: String,
+    user_id: Option<i64>,
     metadata: Option<serde_json::Value>,
     db: State<'_, Database>
 ) -> Result<i64, String> {
-    db.create_session(event_id, &participant_id, metadata)
+    db.check_session_window(event_id).await.map_err(|e| e.to_string())?;
+    db.create_session(event_id, &participant_id, user_id, metadata)
         .await
         .map_err(|e| e.to_string())
 }
@@ -0,0 +1,42 @@
+// Ranking commands
+// Composite weights, cutoffs and the ranked shortlist of an event
+
+use tauri::State;
+
+use crate::db::Database;
+use crate::db::ranking::RankingError;
+use crate::ranking::{RankedCandidate, RankingConfig};
+
+#[tauri::command]
+pub async fn get_ranking_config(db: State<'_, Database>, event_id: i64) -> Result<RankingConfig, String> {
+    db.get_ranking_config(event_id).await.map_err(|e| e.to_string())
+}
+
+/// Replace the event's tool weights, cutoffs and tie-break rules
+#[tauri::command]
+pub async fn set_ranking_config(
+    db: State<'_, Database>,
+    event_id: i64,
+    config: RankingConfig,
+) -> Result<RankingConfig, String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    db.set_ranking_config(event_id, &config).await.map_err(|e| match e {
+        RankingError::DatabaseError(sqlx::Error::Database(db_err)) if db_err.is_foreign_key_violation() => "Tool not found".to_string(),
+        e => e.to_string(),
+    })
+}
+
+/// Participants ranked on their composite, best first. `passed_only` keeps those who
+/// met every cutoff.
+#[tauri::command]
+pub async fn get_event_ranking(
+    db: State<'_, Database>,
+    event_id: i64,
+    passed_only: Option<bool>,
+) -> Result<Vec<RankedCandidate>, String> {
+    let ranking = db.get_event_ranking(event_id).await.map_err(|e| e.to_string())?;
+    Ok(match passed_only {
+        Some(true) => ranking.into_iter().filter(|c| c.passed).collect(),
+        _ => ranking,
+    })
+}
@@ -0,0 +1,201 @@
+// Manual scoring commands
+// Rater queue for text and drawing answers: queue, assign, score against a rubric, merge into the report.
+// Items can be double-scored for inter-rater reliability, with adjudication of large disagreements.
+
+use tauri::State;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::db::Database;
+use crate::db::rating::{RatingItem, RatingScore};
+use crate::rating::{self, ManualScoreSummary};
+use crate::rating::reliability::{self, ReliabilityReport};
+use crate::scoring::SubmittedAnswer;
+
+/// Queue a session's text and drawing answers for one tool. Returns the number of new items.
+#[tauri::command]
+pub async fn queue_manual_scoring(
+    db: State<'_, Database>,
+    event_id: i64,
+    tool_id: i64,
+    session_id: i64,
+    answers: Vec<SubmittedAnswer>
+) -> Result<i64, String> {
+    let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.map_err(|e| e.to_string())?;
+    db.enqueue_rating_items(event_id, session_id, &snapshot, &answers)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Unscored items of an event by default; pass a status or rater to narrow it down
+#[tauri::command]
+pub async fn get_rating_queue(
+    db: State<'_, Database>,
+    event_id: i64,
+    status: Option<String>,
+    rater_id: Option<i64>
+) -> Result<Vec<RatingItem>, String> {
+    let mut items = db.get_rating_queue(event_id, status.as_deref(), rater_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if status.is_none() {
+        items.retain(|i| i.status != "scored");
+    }
+    Ok(items)
+}
+
+#[tauri::command]
+pub async fn assign_rating_items(
+    db: State<'_, Database>,
+    item_ids: Vec<i64>,
+    rater_id: i64
+) -> Result<i64, String> {
+    if !db.is_rater(rater_id).await.map_err(|e| e.to_string())? {
+        return Err("Only admins and operators can score answers".to_string());
+    }
+
+    db.assign_rating_items(&item_ids, rater_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn submit_rating(
+    db: State<'_, Database>,
+    item_id: i64,
+    rater_id: i64,
+    criteria: BTreeMap<String, f64>,
+    comment: Option<String>
+) -> Result<RatingScore, String> {
+    let mut item = db.get_rating_item(item_id).await.map_err(|e| e.to_string())?;
+    if item.adjudicated_by.is_some() {
+        return Err("This item has already been adjudicated".to_string());
+    }
+    // Whoever scores an unclaimed item first becomes its first rater, so a second rater's
+    // score always has a first one to pair with
+    let claims_first = item.assigned_to.is_none() && item.second_rater != Some(rater_id);
+    let raters = [item.assigned_to, item.second_rater];
+    if !claims_first && raters.iter().any(|r| r.is_some()) && !raters.contains(&Some(rater_id)) {
+        return Err("This item is assigned to another rater".to_string());
+    }
+    if !db.is_rater(rater_id).await.map_err(|e| e.to_string())? {
+        return Err("Only admins and operators can score answers".to_string());
+    }
+
+    let rubric = item.rubric()?;
+    let total = rubric.total(&criteria)?;
+    let comment = comment.filter(|c| !c.trim().is_empty());
+
+    if claims_first {
+        if !db.claim_rating_item(item_id, rater_id).await.map_err(|e| e.to_string())? {
+            return Err("This item is assigned to another rater".to_string());
+        }
+        item.assigned_to = Some(rater_id);
+    }
+
+    let score = db.record_rating(item_id, rater_id, &criteria, total, comment.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Once both raters are in, flag large disagreements for adjudication
+    if item.required_ratings > 1 {
+        let ratings = db.get_item_ratings(item_id).await.map_err(|e| e.to_string())?;
+        let total_of = |rater: Option<i64>| ratings.iter().find(|r| Some(r.rater_id) == rater).map(|r| r.total);
+        if let (Some(first), Some(second)) = (total_of(item.assigned_to), total_of(item.second_rater)) {
+            db.set_needs_adjudication(item_id, rubric.needs_adjudication(first, second))
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(score)
+}
+
+/// Double-score items: a second rater scores them independently of the first
+#[tauri::command]
+pub async fn assign_second_rater(
+    db: State<'_, Database>,
+    item_ids: Vec<i64>,
+    rater_id: i64
+) -> Result<i64, String> {
+    if !db.is_rater(rater_id).await.map_err(|e| e.to_string())? {
+        return Err("Only admins and operators can score answers".to_string());
+    }
+
+    db.assign_second_rater(&item_ids, rater_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_adjudication_queue(
+    db: State<'_, Database>,
+    event_id: i64
+) -> Result<Vec<RatingItem>, String> {
+    db.get_adjudication_queue(event_id).await.map_err(|e| e.to_string())
+}
+
+/// Settle a flagged disagreement. The adjudicator's score becomes the final one,
+/// so it must come from someone other than the two raters.
+#[tauri::command]
+pub async fn adjudicate_rating(
+    db: State<'_, Database>,
+    item_id: i64,
+    adjudicator_id: i64,
+    criteria: BTreeMap<String, f64>,
+    comment: Option<String>
+) -> Result<RatingScore, String> {
+    let item = db.get_rating_item(item_id).await.map_err(|e| e.to_string())?;
+    if !item.needs_adjudication {
+        return Err("This item has not been flagged for adjudication".to_string());
+    }
+    if [item.assigned_to, item.second_rater].contains(&Some(adjudicator_id)) {
+        return Err("Adjudication must come from a third rater".to_string());
+    }
+    if !db.is_rater(adjudicator_id).await.map_err(|e| e.to_string())? {
+        return Err("Only admins and operators can score answers".to_string());
+    }
+
+    let total = item.rubric()?.total(&criteria)?;
+    let comment = comment.filter(|c| !c.trim().is_empty());
+
+    let score = db.record_rating(item_id, adjudicator_id, &criteria, total, comment.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+    db.mark_adjudicated(item_id, adjudicator_id).await.map_err(|e| e.to_string())?;
+    Ok(score)
+}
+
+/// Cohen's kappa, weighted kappa and ICC per rubric over the event's double-scored items
+#[tauri::command]
+pub async fn get_reliability_report(
+    db: State<'_, Database>,
+    event_id: i64
+) -> Result<Vec<ReliabilityReport>, String> {
+    let pairs = db.get_double_scored_pairs(event_id).await.map_err(|e| e.to_string())?;
+    Ok(reliability::reliability_reports(&pairs))
+}
+
+#[tauri::command]
+pub async fn get_item_ratings(
+    db: State<'_, Database>,
+    item_id: i64
+) -> Result<Vec<RatingScore>, String> {
+    db.get_item_ratings(item_id).await.map_err(|e| e.to_string())
+}
+
+/// Merge the manual scores into the session report. Only possible once every item is scored.
+#[tauri::command]
+pub async fn finalize_manual_scores(
+    db: State<'_, Database>,
+    session_id: i64
+) -> Result<ManualScoreSummary, String> {
+    let items = db.get_session_rating_items(session_id).await.map_err(|e| e.to_string())?;
+    if items.is_empty() {
+        return Err("This session has no manually scored answers".to_string());
+    }
+
+    let mut ratings = HashMap::new();
+    for item in &items {
+        ratings.insert(item.id, db.get_item_ratings(item.id).await.map_err(|e| e.to_string())?);
+    }
+
+    let summary = rating::summarize_session(&items, &ratings)?;
+    db.merge_manual_scores(session_id, &summary).await.map_err(|e| e.to_string())?;
+    Ok(summary)
+}
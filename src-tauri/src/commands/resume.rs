@@ -0,0 +1,41 @@
+// Candidate session recovery commands
+// Checkpoint test progress locally and resume after a crash or power loss
+
+use tauri::State;
+use std::sync::Arc;
+
+use crate::db::candidate::{CandidateDatabase, ResumeState};
+
+/// Save the candidate's current position in the test (Candidate only)
+#[tauri::command]
+pub async fn save_session_progress(
+    session_id: String,
+    tool_id: i64,
+    subtest_id: i64,
+    question_index: i64,
+    time_limit_seconds: Option<i64>,
+    elapsed_seconds: i64,
+    db_state: State<'_, Arc<CandidateDatabase>>,
+) -> Result<(), String> {
+    db_state.save_session_progress(
+        &session_id,
+        tool_id,
+        subtest_id,
+        question_index,
+        time_limit_seconds,
+        elapsed_seconds,
+    )
+    .await
+    .map_err(|e| format!("Database error: {:?}", e))
+}
+
+/// Find an interrupted session and return everything needed to continue it
+#[tauri::command]
+pub async fn resume_candidate_session(
+    user_id: i64,
+    db_state: State<'_, Arc<CandidateDatabase>>,
+) -> Result<Option<ResumeState>, String> {
+    db_state.resume_session(user_id)
+        .await
+        .map_err(|e| format!("Database error: {:?}", e))
+}
@@ -0,0 +1,120 @@
+// Session lifecycle commands
+// Validated status transitions: start, pause, resume, complete, terminate
+
+use tauri::State;
+use crate::db::Database;
+use crate::db::accommodations::EXTRA_BREAK_REASON;
+use crate::db::models::Session;
+use crate::db::session_lifecycle::{SessionStatus, SessionTransition};
+
+#[tauri::command]
+pub async fn start_session(
+    db: State<'_, Database>,
+    session_id: i64,
+) -> Result<Session, String> {
+    let session = db.get_session_by_id(session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    db.check_session_window(session.event_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    db.transition_session(session_id, SessionStatus::Active, None)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pause_session(
+    db: State<'_, Database>,
+    session_id: i64,
+    reason: Option<String>,
+) -> Result<Session, String> {
+    db.transition_session(session_id, SessionStatus::Paused, reason.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn resume_session(
+    db: State<'_, Database>,
+    session_id: i64,
+) -> Result<Session, String> {
+    let session = db.get_session_by_id(session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Resuming only makes sense from a pause; starting a pending session goes through start_session
+    if session.status != SessionStatus::Paused.as_str() {
+        return Err(format!("Cannot resume a session that is '{}'", session.status));
+    }
+
+    let resumed = db.transition_session(session_id, SessionStatus::Active, None)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // An extra break only stops the clock for its allowed length
+    match (session.status_reason.as_deref(), session.paused_at) {
+        (Some(EXTRA_BREAK_REASON), Some(paused_at)) => {
+            let accommodations = db.get_session_accommodations(session_id).await.map_err(|e| e.to_string())?;
+            let paused_for = (chrono::Utc::now().naive_utc() - paused_at).num_seconds();
+            db.charge_break_overrun(session_id, paused_for - accommodations.break_seconds())
+                .await
+                .map_err(|e| e.to_string())
+        }
+        _ => Ok(resumed),
+    }
+}
+
+/// Pause the session for one of the candidate's extra breaks (testing accommodations)
+#[tauri::command]
+pub async fn take_extra_break(
+    db: State<'_, Database>,
+    session_id: i64,
+) -> Result<Session, String> {
+    let breaks_left = db.get_extra_breaks_left(session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if breaks_left == 0 {
+        return Err("No extra breaks left for this candidate".to_string());
+    }
+
+    db.transition_session(session_id, SessionStatus::Paused, Some(EXTRA_BREAK_REASON))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn complete_session(
+    db: State<'_, Database>,
+    session_id: i64,
+) -> Result<Session, String> {
+    db.transition_session(session_id, SessionStatus::Completed, None)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn terminate_session(
+    db: State<'_, Database>,
+    session_id: i64,
+    reason: String,
+) -> Result<Session, String> {
+    if reason.trim().is_empty() {
+        return Err("A reason is required to terminate a session".to_string());
+    }
+
+    db.transition_session(session_id, SessionStatus::Terminated, Some(&reason))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_session_history(
+    db: State<'_, Database>,
+    session_id: i64,
+) -> Result<Vec<SessionTransition>, String> {
+    db.get_session_transitions(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
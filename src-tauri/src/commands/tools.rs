@@ -1,39 +1,20 @@
-use tauri::State;
+use tauri::{AppHandle, State};
+use std::path::PathBuf;
+use crate::bundle;
+use crate::commands::media::media_dir;
 use crate::db::Database;
-use crate::db::models::{Tool, ToolSubtest, Question};
-use serde::{Serialize, Deserialize};
+use crate::db::bundles::{ConflictStrategy, ImportReport};
+use crate::db::models::PackageInfo;
+use crate::localization::{self, LocalizedToolContent};
+use crate::question_import::{self, QuestionImportReport};
+use crate::question_types::{self, QuestionType};
+use crate::scoring::{self, ScoreSummary, SubmittedAnswer};
 
-#[derive(Serialize, Deserialize)]
-pub struct FullToolStructure {
-    pub tool: Tool,
-    pub subtests: Vec<FullSubtest>,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct FullSubtest {
-    pub subtest: ToolSubtest,
-    pub questions: Vec<Question>,
-}
+pub use crate::db::models::{FullToolStructure, FullSubtest};
 
 #[tauri::command]
 pub async fn get_tool_structure(db: State<'_, Database>, tool_id: i64) -> Result<FullToolStructure, String> {
-    let tool = db.get_tool_by_id(tool_id).await.map_err(|e| e.to_string())?;
-    let subtests = db.get_subtests_by_tool(tool_id).await.map_err(|e| e.to_string())?;
-
-    let mut full_subtests = Vec::new();
-
-    for subtest in subtests {
-        let questions = db.get_questions_by_subtest(subtest.id).await.map_err(|e| e.to_string())?;
-        full_subtests.push(FullSubtest {
-            subtest,
-            questions,
-        });
-    }
-
-    Ok(FullToolStructure {
-        tool,
-        subtests: full_subtests,
-    })
+    db.get_full_tool_structure(tool_id).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -61,9 +42,42 @@ pub async fn create_question(
     options: serde_json::Value,
     sequence: i64
 ) -> Result<i64, String> {
+    validate_question_options(&q_type, &options)?;
     db.create_question(subtest_id, &text, &q_type, options, sequence).await.map_err(|e| e.to_string())
 }
 
+fn validate_question_options(q_type: &str, options: &serde_json::Value) -> Result<QuestionType, String> {
+    let question_type = QuestionType::parse(q_type).ok_or_else(|| format!("Unknown question type '{}'", q_type))?;
+    question_types::validate_options(question_type, Some(options))?;
+    Ok(question_type)
+}
+
+/// Import questions from a CSV/XLSX file into a subtest.
+/// Rows are only written when every row is valid; otherwise the report lists the errors.
+#[tauri::command]
+pub async fn import_questions(
+    db: State<'_, Database>,
+    subtest_id: i64,
+    path: String,
+    dry_run: Option<bool>
+) -> Result<QuestionImportReport, String> {
+    let rows = question_import::read_rows(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+    let (valid, errors) = question_import::validate_rows(&rows);
+
+    let mut report = QuestionImportReport {
+        total_rows: rows.len(),
+        valid_rows: valid.len(),
+        errors,
+        created_ids: Vec::new(),
+    };
+
+    if report.errors.is_empty() && !dry_run.unwrap_or(false) {
+        report.created_ids = db.create_questions_bulk(subtest_id, &valid).await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(report)
+}
+
 #[tauri::command]
 pub async fn delete_question(db: State<'_, Database>, id: i64) -> Result<(), String> {
     db.delete_question(id).await.map_err(|e| e.to_string())
@@ -77,5 +91,134 @@ pub async fn update_question(
     q_type: String,
     options: serde_json::Value
 ) -> Result<(), String> {
+    validate_question_options(&q_type, &options)?;
     db.update_question(id, &text, &q_type, options).await.map_err(|e| e.to_string())
 }
+
+// --- Versioned Packages ---
+
+#[tauri::command]
+pub async fn publish_tool_version(db: State<'_, Database>, tool_id: i64) -> Result<PackageInfo, String> {
+    db.publish_tool_package(tool_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tool_versions(db: State<'_, Database>, tool_id: i64) -> Result<Vec<PackageInfo>, String> {
+    db.get_tool_packages(tool_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_event_tool_version(
+    db: State<'_, Database>,
+    event_id: i64,
+    tool_id: i64,
+    package_id: i64
+) -> Result<(), String> {
+    db.ensure_event_editable(event_id).await.map_err(|e| e.to_string())?;
+    let package = db.get_package_info(package_id).await.map_err(|e| e.to_string())?;
+    if package.tool_id != tool_id {
+        return Err("Package does not belong to this tool".to_string());
+    }
+
+    // Candidates must all see the same version of a test
+    let sessions = db.count_event_sessions(event_id).await.map_err(|e| e.to_string())?;
+    if sessions > 0 {
+        return Err("Cannot change the version once sessions have started".to_string());
+    }
+
+    db.set_event_tool_package(event_id, tool_id, package_id).await.map_err(|e| e.to_string())
+}
+
+/// Tool content as delivered to candidates: the event's frozen snapshot without answer keys.
+///
+/// Language: `locale` if given, else the candidate's choice, else the event's, else the tool's own.
+#[tauri::command]
+pub async fn get_event_tool_content(
+    db: State<'_, Database>,
+    event_id: i64,
+    tool_id: i64,
+    user_id: Option<i64>,
+    locale: Option<String>
+) -> Result<LocalizedToolContent, String> {
+    let mut snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.map_err(|e| e.to_string())?;
+    // Extended time is delivered as longer limits, so the test UI enforces it like any other limit
+    let accommodations = match user_id {
+        Some(user_id) => db.get_participant_accommodations(event_id, user_id).await.map_err(|e| e.to_string())?,
+        None => Default::default(),
+    };
+    accommodations.apply_to_structure(&mut snapshot.structure);
+    let (participant_locale, event_locale) = db.get_locale_preferences(event_id, user_id).await.map_err(|e| e.to_string())?;
+
+    let chain = localization::fallback_chain(
+        &[locale.as_deref(), participant_locale.as_deref(), event_locale.as_deref()],
+        &snapshot.structure.tool.content_locale,
+    );
+    let mut content = snapshot.into_localized_candidate_content(&chain);
+    content.large_font = accommodations.large_font;
+    Ok(content)
+}
+
+#[tauri::command]
+pub async fn score_event_tool(
+    db: State<'_, Database>,
+    event_id: i64,
+    tool_id: i64,
+    answers: Vec<SubmittedAnswer>
+) -> Result<ScoreSummary, String> {
+    let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.map_err(|e| e.to_string())?;
+    Ok(scoring::score_snapshot(&snapshot, &answers))
+}
+
+/// Validate a structured answer from the test UI and return the string to store for it
+#[tauri::command]
+pub async fn serialize_answer(
+    db: State<'_, Database>,
+    event_id: i64,
+    tool_id: i64,
+    question_id: i64,
+    answer: serde_json::Value
+) -> Result<String, String> {
+    let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.map_err(|e| e.to_string())?;
+    let question = snapshot.structure.subtests.iter()
+        .flat_map(|s| s.questions.iter())
+        .find(|q| q.id == question_id)
+        .ok_or("Question is not part of this event's package".to_string())?;
+
+    let question_type = QuestionType::parse(&question.question_type)
+        .ok_or_else(|| format!("Unknown question type '{}'", question.question_type))?;
+    question_types::serialize_answer(question_type, question.options.as_ref(), &answer)
+}
+
+// --- Bundles ---
+
+#[tauri::command]
+pub async fn export_tool_bundle(
+    app_handle: AppHandle,
+    db: State<'_, Database>,
+    tool_id: i64,
+    path: String
+) -> Result<String, String> {
+    let bundle_data = db.build_tool_bundle(tool_id).await.map_err(|e| e.to_string())?;
+    bundle::write_bundle(&PathBuf::from(&path), bundle_data, &media_dir(&app_handle)?).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn import_tool_bundle(
+    app_handle: AppHandle,
+    db: State<'_, Database>,
+    path: String,
+    strategy: ConflictStrategy
+) -> Result<ImportReport, String> {
+    let (bundle_data, media_files) = bundle::read_bundle(&PathBuf::from(&path))
+        .map_err(|e| e.to_string())?;
+
+    // Media only reaches the store once the content is in
+    let report = db.import_tool_bundle(&bundle_data, strategy).await.map_err(|e| e.to_string())?;
+    let stored_media = bundle::store_media(&media_dir(&app_handle)?, &media_files)
+        .map_err(|e| e.to_string())?;
+    for file in &stored_media {
+        db.register_media_asset(file).await.map_err(|e| e.to_string())?;
+    }
+    Ok(report)
+}
@@ -0,0 +1,197 @@
+// Participant Access Tokens
+// Single-use tokens bound to one participant and one event, printed on invitations
+// (usually as a QR code) instead of a shared event code. Only a hash is stored.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::Database;
+use super::event_lifecycle::{from_stored, to_stored};
+use super::models::User;
+use super::withdrawal::has_left;
+
+#[derive(Debug)]
+pub enum AccessTokenError {
+    /// Unknown or revoked; deliberately doesn't say which
+    Invalid,
+    Expired { expired_at: String },
+    AlreadyUsed { used_at: String },
+    NotEnrolled,
+    EventArchived,
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for AccessTokenError {
+    fn from(err: sqlx::Error) -> Self {
+        AccessTokenError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for AccessTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessTokenError::Invalid => write!(f, "Invalid access token"),
+            AccessTokenError::Expired { expired_at } => write!(f, "This access token expired on {}", expired_at),
+            AccessTokenError::AlreadyUsed { used_at } => write!(f, "This access token was already used on {}", used_at),
+            AccessTokenError::NotEnrolled => write!(f, "Participant is not enrolled in this event"),
+            AccessTokenError::EventArchived => write!(f, "This event has been archived"),
+            AccessTokenError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+/// A token as listed for admins; the token itself can't be recovered
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AccessToken {
+    pub id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub event_id: i64,
+    pub expires_at: String,
+    pub used_at: Option<String>,
+    pub created_at: String,
+}
+
+/// Who a redeemed token logged in, and into which event
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenLogin {
+    pub user: User,
+    pub full_name: Option<String>,
+    pub event_id: i64,
+    pub event_name: String,
+    pub event_code: Option<String>,
+}
+
+impl Database {
+    /// Issue a token for an enrolled participant. Any unused token they already had for
+    /// the event stops working, so only the latest printout is valid.
+    pub async fn issue_access_token(
+        &self,
+        event_id: i64,
+        user_id: i64,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<i64, AccessTokenError> {
+        let mut tx = self.pool.begin().await?;
+
+        let status: String = sqlx::query("SELECT status FROM event_participants WHERE event_id = ? AND user_id = ?")
+            .bind(event_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AccessTokenError::NotEnrolled)?
+            .get("status");
+        if has_left(&status) {
+            return Err(AccessTokenError::NotEnrolled);
+        }
+
+        sqlx::query("DELETE FROM access_tokens WHERE event_id = ? AND user_id = ? AND used_at IS NULL")
+            .bind(event_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let id = sqlx::query("INSERT INTO access_tokens (token_hash, user_id, event_id, expires_at) VALUES (?, ?, ?, ?)")
+            .bind(token_hash)
+            .bind(user_id)
+            .bind(event_id)
+            .bind(to_stored(expires_at))
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    pub async fn get_event_access_tokens(&self, event_id: i64) -> Result<Vec<AccessToken>, Error> {
+        sqlx::query_as::<_, AccessToken>(
+            r#"
+            SELECT t.id, t.user_id, u.username, pp.full_name, t.event_id, t.expires_at, t.used_at, t.created_at
+            FROM access_tokens t
+            JOIN users u ON u.id = t.user_id
+            LEFT JOIN participant_profiles pp ON pp.user_id = t.user_id
+            WHERE t.event_id = ?
+            ORDER BY u.username, t.created_at DESC
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Withdraw an unused token; used ones stay as a record of the login
+    pub async fn revoke_access_token(&self, token_id: i64) -> Result<(), Error> {
+        let result = sqlx::query("DELETE FROM access_tokens WHERE id = ? AND used_at IS NULL")
+            .bind(token_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// Log in with a token, using it up
+    pub async fn redeem_access_token(&self, token_hash: &str, now: DateTime<Utc>) -> Result<TokenLogin, AccessTokenError> {
+        let token = sqlx::query(
+            r#"
+            SELECT t.id, t.user_id, t.event_id, t.expires_at, t.used_at, e.event_name, e.event_code, e.status,
+                   ep.status as participant_status
+            FROM access_tokens t
+            JOIN events e ON e.id = t.event_id
+            LEFT JOIN event_participants ep ON ep.event_id = t.event_id AND ep.user_id = t.user_id
+            WHERE t.token_hash = ?
+            "#
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AccessTokenError::Invalid)?;
+
+        if let Some(used_at) = token.get::<Option<String>, _>("used_at") {
+            return Err(AccessTokenError::AlreadyUsed { used_at });
+        }
+        let expires_at: String = token.get("expires_at");
+        if from_stored(&expires_at).map(|t| now >= t).unwrap_or(true) {
+            return Err(AccessTokenError::Expired { expired_at: expires_at });
+        }
+        if token.get::<String, _>("status") == "archived" {
+            return Err(AccessTokenError::EventArchived);
+        }
+        match token.get::<Option<String>, _>("participant_status") {
+            Some(status) if !has_left(&status) => {}
+            _ => return Err(AccessTokenError::NotEnrolled),
+        }
+
+        // Guarded on used_at so two devices racing with the same token can't both get in
+        let claimed = sqlx::query("UPDATE access_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL")
+            .bind(to_stored(now))
+            .bind(token.get::<i64, _>("id"))
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        if claimed == 0 {
+            return Err(AccessTokenError::AlreadyUsed { used_at: to_stored(now) });
+        }
+
+        let user_id: i64 = token.get("user_id");
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        let full_name = self.get_participant_profile(user_id).await?.map(|p| p.full_name);
+
+        Ok(TokenLogin {
+            user,
+            full_name,
+            event_id: token.get("event_id"),
+            event_name: token.get("event_name"),
+            event_code: token.get("event_code"),
+        })
+    }
+}
@@ -0,0 +1,183 @@
+// Testing Accommodations
+// Extended time, extra breaks and large print per event participant
+
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use super::models::{FullToolStructure, Session};
+
+/// Longest extension allowed; beyond triple time the norms stop meaning anything
+const MAX_TIME_MULTIPLIER: f64 = 3.0;
+
+/// Pause reason marking one of a candidate's extra breaks
+pub const EXTRA_BREAK_REASON: &str = "Extra break (accommodation)";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Accommodations {
+    /// Applied to every subtest time limit; 1.5 means time and a half
+    pub time_multiplier: f64,
+    pub extra_breaks: i64,
+    /// Length of each extra break
+    pub break_minutes: i64,
+    pub large_font: bool,
+    pub notes: Option<String>,
+}
+
+impl Default for Accommodations {
+    fn default() -> Self {
+        Self {
+            time_multiplier: 1.0,
+            extra_breaks: 0,
+            break_minutes: 0,
+            large_font: false,
+            notes: None,
+        }
+    }
+}
+
+impl Accommodations {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1.0..=MAX_TIME_MULTIPLIER).contains(&self.time_multiplier) {
+            return Err(format!("Time multiplier must be between 1 and {}", MAX_TIME_MULTIPLIER));
+        }
+        if self.extra_breaks < 0 || self.break_minutes < 0 {
+            return Err("Breaks can't be negative".to_string());
+        }
+        if self.extra_breaks > 0 && self.break_minutes == 0 {
+            return Err("Extra breaks need a length in minutes".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether the test was given under standard conditions. Large print alone
+    /// doesn't change the administration, but extra time and breaks do.
+    pub fn is_standard(&self) -> bool {
+        self.time_multiplier == 1.0 && self.extra_breaks == 0
+    }
+
+    pub fn break_seconds(&self) -> i64 {
+        self.break_minutes * 60
+    }
+
+    /// Extended limit, rounded up to the whole second. Untimed subtests stay untimed.
+    pub fn apply_time_limit(&self, limit_seconds: Option<i64>) -> Option<i64> {
+        limit_seconds.map(|limit| (limit as f64 * self.time_multiplier).ceil() as i64)
+    }
+
+    pub fn apply_to_structure(&self, structure: &mut FullToolStructure) {
+        for full in structure.subtests.iter_mut() {
+            full.subtest.time_limit_seconds = self.apply_time_limit(full.subtest.time_limit_seconds);
+        }
+    }
+}
+
+impl Database {
+    /// Accommodations of a participant; standard conditions when there are none (or no enrollment)
+    pub async fn get_participant_accommodations(&self, event_id: i64, user_id: i64) -> Result<Accommodations, Error> {
+        let accommodations = sqlx::query_as::<_, Accommodations>(
+            r#"
+            SELECT time_multiplier, extra_breaks, break_minutes, large_font, accommodation_notes AS notes
+            FROM event_participants
+            WHERE event_id = ? AND user_id = ?
+            "#
+        )
+        .bind(event_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(accommodations.unwrap_or_default())
+    }
+
+    pub async fn set_participant_accommodations(&self, event_id: i64, user_id: i64, accommodations: &Accommodations) -> Result<(), Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE event_participants
+            SET time_multiplier = ?, extra_breaks = ?, break_minutes = ?, large_font = ?, accommodation_notes = ?
+            WHERE event_id = ? AND user_id = ?
+            "#
+        )
+        .bind(accommodations.time_multiplier)
+        .bind(accommodations.extra_breaks)
+        .bind(accommodations.break_minutes)
+        .bind(accommodations.large_font)
+        .bind(&accommodations.notes)
+        .bind(event_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// Accommodations in effect for a session, looked up through its event enrollment
+    pub async fn get_session_accommodations(&self, session_id: i64) -> Result<Accommodations, Error> {
+        let row = sqlx::query("SELECT event_id, user_id FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        match row.get::<Option<i64>, _>("user_id") {
+            Some(user_id) => self.get_participant_accommodations(row.get("event_id"), user_id).await,
+            None => Ok(Accommodations::default()),
+        }
+    }
+
+    /// Extra breaks the candidate still has, counted over their current attempt in the event.
+    /// Breaks are session pauses with `EXTRA_BREAK_REASON`, so their time is never charged.
+    pub async fn get_extra_breaks_left(&self, session_id: i64) -> Result<i64, Error> {
+        let accommodations = self.get_session_accommodations(session_id).await?;
+        if accommodations.extra_breaks == 0 {
+            return Ok(0);
+        }
+
+        let taken: i64 = sqlx::query(
+            r#"
+            SELECT COUNT(*) as count
+            FROM session_transitions st
+            JOIN sessions s ON s.id = st.session_id
+            JOIN sessions cur ON cur.id = ?1
+            WHERE st.to_status = 'paused' AND st.reason = ?2
+              AND s.event_id = cur.event_id AND s.superseded_at IS NULL
+              AND (s.id = cur.id OR s.user_id = cur.user_id)
+            "#
+        )
+        .bind(session_id)
+        .bind(EXTRA_BREAK_REASON)
+        .fetch_one(&self.pool)
+        .await?
+        .get("count");
+
+        Ok((accommodations.extra_breaks - taken).max(0))
+    }
+
+    /// Count the part of a pause beyond the allowed break length as testing time again
+    pub async fn charge_break_overrun(&self, session_id: i64, overrun_seconds: i64) -> Result<Session, Error> {
+        sqlx::query("UPDATE sessions SET paused_seconds = MAX(paused_seconds - ?, 0) WHERE id = ?")
+            .bind(overrun_seconds.max(0))
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        self.get_session_by_id(session_id).await
+    }
+
+    /// Note the administration conditions in the session's report under `scores.administration`
+    pub async fn record_report_administration(&self, session_id: i64) -> Result<(), Error> {
+        let accommodations = self.get_session_accommodations(session_id).await?;
+        let administration = serde_json::json!({
+            "standard": accommodations.is_standard(),
+            "accommodations": accommodations,
+        });
+
+        sqlx::query("UPDATE reports SET scores = json_set(COALESCE(scores, '{}'), '$.administration', json(?)) WHERE session_id = ?")
+            .bind(administration.to_string())
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -120,6 +120,7 @@ impl Database {
 
     // ===== Test Results with Sync Tracking =====
 
+    /// Archived events take no more results; this fails with `RowNotFound` for them
     pub async fn save_synced_test_result(
         &self,
         client_session_id: &str,
@@ -130,7 +131,8 @@ impl Database {
         let result = sqlx::query(
             "INSERT INTO test_results 
              (user_id, event_id, answers, client_session_id, sync_source, received_at) 
-             VALUES (?, ?, ?, ?, 'client_sync', datetime('now'))"
+             SELECT ?1, ?2, ?3, ?4, 'client_sync', datetime('now')
+             WHERE NOT EXISTS (SELECT 1 FROM events WHERE id = ?2 AND status = 'archived')"
         )
         .bind(user_id)
         .bind(event_id)
@@ -139,6 +141,9 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
         Ok(result.last_insert_rowid())
     }
 }
@@ -0,0 +1,230 @@
+// Event Blueprint
+// Stored step order of an event and each session's progress through it
+
+use sqlx::{Error, Row};
+use std::collections::HashSet;
+use std::fmt;
+
+use super::Database;
+use crate::blueprint::{self, BlueprintStep, NextStep, StepType};
+
+#[derive(Debug)]
+pub enum BlueprintError {
+    SessionNotFound(i64),
+    /// The candidate tried to finish a step other than the current one
+    OutOfOrder { expected: Option<i64>, got: i64 },
+    RequiredStep(i64),
+    /// A tool step was finished before the tool has a result
+    NoToolResult(i64),
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for BlueprintError {
+    fn from(err: sqlx::Error) -> Self {
+        BlueprintError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for BlueprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlueprintError::SessionNotFound(id) => write!(f, "Session {} not found", id),
+            BlueprintError::OutOfOrder { expected: Some(expected), got } => {
+                write!(f, "Step {} is not the current step (expected step {})", got, expected)
+            }
+            BlueprintError::OutOfOrder { expected: None, got } => {
+                write!(f, "Step {} can't be finished, the battery is already complete", got)
+            }
+            BlueprintError::RequiredStep(id) => write!(f, "Step {} is required and can't be skipped", id),
+            BlueprintError::NoToolResult(tool_id) => write!(f, "Tool {} has no result yet", tool_id),
+            BlueprintError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for BlueprintStep {
+    fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, Error> {
+        let step_type: String = row.try_get("step_type")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            step_type: StepType::parse(&step_type)
+                .ok_or_else(|| Error::Protocol(format!("Unknown blueprint step type '{}'", step_type)))?,
+            tool_id: row.try_get("tool_id")?,
+            subtest_id: row.try_get("subtest_id")?,
+            title: row.try_get("title")?,
+            content: row.try_get("content")?,
+            duration_seconds: row.try_get("duration_seconds")?,
+            required: row.try_get("required")?,
+        })
+    }
+}
+
+impl Database {
+    /// Replace an event's blueprint. Callers validate the steps first.
+    pub async fn set_event_blueprint(&self, event_id: i64, steps: &[BlueprintStep]) -> Result<Vec<BlueprintStep>, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM event_blueprint_steps WHERE event_id = ?")
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (order, step) in steps.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO event_blueprint_steps
+                    (event_id, step_order, step_type, tool_id, subtest_id, title, content, duration_seconds, required)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(event_id)
+            .bind(order as i64)
+            .bind(step.step_type.as_str())
+            .bind(step.tool_id)
+            .bind(step.subtest_id)
+            .bind(&step.title)
+            .bind(&step.content)
+            .bind(step.duration_seconds)
+            .bind(step.required)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        self.get_stored_blueprint(event_id).await
+    }
+
+    /// Steps saved for an event, empty if it never got a blueprint
+    pub async fn get_stored_blueprint(&self, event_id: i64) -> Result<Vec<BlueprintStep>, Error> {
+        sqlx::query_as::<_, BlueprintStep>("SELECT * FROM event_blueprint_steps WHERE event_id = ? ORDER BY step_order")
+            .bind(event_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// The event's blueprint, or its tools in package order when none was defined
+    pub async fn get_event_blueprint(&self, event_id: i64) -> Result<Vec<BlueprintStep>, Error> {
+        let stored = self.get_stored_blueprint(event_id).await?;
+        if !stored.is_empty() {
+            return Ok(stored);
+        }
+
+        let tool_ids: Vec<i64> = self.get_event_packages(event_id).await?
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect();
+        Ok(blueprint::default_blueprint(&tool_ids))
+    }
+
+    /// Like `get_event_blueprint`, but a default blueprint is saved first so sessions can record progress against it.
+    /// Only called when progress is recorded; reads never save anything.
+    pub async fn ensure_event_blueprint(&self, event_id: i64) -> Result<Vec<BlueprintStep>, Error> {
+        let steps = self.get_event_blueprint(event_id).await?;
+        if steps.iter().all(|s| s.id > 0) {
+            return Ok(steps);
+        }
+        self.set_event_blueprint(event_id, &steps).await
+    }
+
+    pub async fn get_finished_step_ids(&self, session_id: i64) -> Result<HashSet<i64>, Error> {
+        let rows = sqlx::query("SELECT step_id FROM session_step_progress WHERE session_id = ?")
+            .bind(session_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(|r| r.get("step_id")).collect())
+    }
+
+    pub async fn finish_blueprint_step(&self, session_id: i64, step_id: i64, skipped: bool) -> Result<(), Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO session_step_progress (session_id, step_id, status) VALUES (?, ?, ?)"
+        )
+        .bind(session_id)
+        .bind(step_id)
+        .bind(if skipped { "skipped" } else { "completed" })
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether the session's participant has a completed session or a report for the tool in this event
+    async fn has_tool_result(&self, session_id: i64, tool_id: i64) -> Result<bool, Error> {
+        sqlx::query(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM sessions s
+                JOIN sessions cur ON cur.id = ?1
+                WHERE s.event_id = cur.event_id AND s.superseded_at IS NULL
+                  AND (s.id = cur.id OR s.user_id = cur.user_id)
+                  AND CAST(COALESCE(
+                      json_extract(s.metadata, '$.tool_id'),
+                      json_extract(s.metadata, '$.toolId'),
+                      (SELECT t.id FROM tools t WHERE t.name = json_extract(s.metadata, '$.testName'))
+                  ) AS INTEGER) = ?2
+                  AND (s.status = 'completed' OR EXISTS (SELECT 1 FROM reports r WHERE r.session_id = s.id))
+            ) as finished
+            "#
+        )
+        .bind(session_id)
+        .bind(tool_id)
+        .fetch_one(&self.pool)
+        .await
+        .map(|row| row.get("finished"))
+    }
+
+    async fn get_session_event_id(&self, session_id: i64) -> Result<i64, BlueprintError> {
+        Ok(sqlx::query("SELECT event_id FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(BlueprintError::SessionNotFound(session_id))?
+            .get("event_id"))
+    }
+
+    /// Where a session is in its event's blueprint
+    pub async fn get_session_next_step(&self, session_id: i64) -> Result<NextStep, BlueprintError> {
+        let event_id = self.get_session_event_id(session_id).await?;
+        let steps = self.get_event_blueprint(event_id).await?;
+        let done = self.get_finished_step_ids(session_id).await?;
+        let mut next = blueprint::next_step(&steps, &done);
+
+        next.extra_breaks_left = self.get_extra_breaks_left(session_id).await?;
+        if next.extra_breaks_left > 0 {
+            next.extra_break_seconds = self.get_session_accommodations(session_id).await?.break_seconds();
+        }
+        Ok(next)
+    }
+
+    /// Finish the current step and move on. Steps run strictly in order; only optional ones may be skipped,
+    /// and a tool step is only done once the tool has a result.
+    pub async fn complete_session_step(&self, session_id: i64, step_id: i64, skipped: bool) -> Result<NextStep, BlueprintError> {
+        let event_id = self.get_session_event_id(session_id).await?;
+        let steps = self.get_event_blueprint(event_id).await?;
+        let current = blueprint::next_step(&steps, &self.get_finished_step_ids(session_id).await?);
+        let step = match current.step {
+            Some(step) if step.id == step_id => step,
+            other => return Err(BlueprintError::OutOfOrder { expected: other.map(|s| s.id), got: step_id }),
+        };
+        if skipped && step.required {
+            return Err(BlueprintError::RequiredStep(step_id));
+        }
+        if !skipped && step.step_type == StepType::Tool {
+            if let Some(tool_id) = step.tool_id {
+                if !self.has_tool_result(session_id, tool_id).await? {
+                    return Err(BlueprintError::NoToolResult(tool_id));
+                }
+            }
+        }
+
+        // A default blueprint's steps have no ids until the first progress saves it
+        let step_id = match step.id {
+            0 => self.ensure_event_blueprint(event_id).await?[current.position].id,
+            id => id,
+        };
+        self.finish_blueprint_step(session_id, step_id, skipped).await?;
+        if step.step_type == StepType::Tool {
+            self.refresh_session_progress(session_id).await?;
+        }
+        self.get_session_next_step(session_id).await
+    }
+}
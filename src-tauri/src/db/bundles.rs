@@ -0,0 +1,350 @@
+// Tool Bundles
+// Self-contained tool content (subtests, questions, answer keys) for sharing between installations
+
+use sqlx::{Error, Row, SqliteConnection};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use crate::media;
+use crate::question_types::{self, QuestionType};
+
+/// Bump when the bundle layout changes; older readers refuse newer bundles
+pub const BUNDLE_SCHEMA_VERSION: i64 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolBundle {
+    pub schema_version: i64,
+    pub exported_at: String,
+    pub tool: BundleTool,
+    pub subtests: Vec<BundleSubtest>,
+}
+
+impl ToolBundle {
+    /// Every question must have a known type and options that fit its schema.
+    /// Checked before any media or content from the bundle is written.
+    pub fn validate(&self) -> Result<(), String> {
+        for subtest in &self.subtests {
+            for (idx, question) in subtest.questions.iter().enumerate() {
+                let question_type = QuestionType::parse(&question.question_type).ok_or_else(|| format!(
+                    "{} question {}: unknown question type '{}'", subtest.subtest_name, idx + 1, question.question_type
+                ))?;
+                question_types::validate_options(question_type, question.options.as_ref())
+                    .map_err(|e| format!("{} question {}: {}", subtest.subtest_name, idx + 1, e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleTool {
+    pub name: String,
+    pub tool_type: String,
+    pub category: String,
+    pub config: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleSubtest {
+    pub subtest_name: String,
+    pub time_limit_seconds: Option<i64>,
+    pub instructions: serde_json::Value,
+    pub sequence_order: i64,
+    pub questions: Vec<BundleQuestion>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleQuestion {
+    pub question_text: String,
+    pub question_type: String,
+    pub options: Option<serde_json::Value>,
+    pub media_url: Option<String>,
+    /// Path of the packed media file inside the bundle, if any
+    pub media_file: Option<String>,
+    /// Original file name of the media, kept so the importing side knows its type
+    #[serde(default)]
+    pub media_name: Option<String>,
+    pub sequence_order: i64,
+    pub answer_key: Option<BundleAnswerKey>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleAnswerKey {
+    pub correct_answer: String,
+    pub scoring_rule: Option<serde_json::Value>,
+}
+
+/// What to do when a tool with the same name already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Import as a new tool under a free name
+    Rename,
+    /// Keep the existing tool and add subtests/questions it doesn't have yet
+    Merge,
+    /// Replace the existing tool's content. Published packages keep their snapshots.
+    Replace,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub tool_id: i64,
+    pub tool_name: String,
+    pub strategy: Option<ConflictStrategy>,
+    pub subtests_created: i64,
+    pub questions_created: i64,
+    pub questions_skipped: i64,
+}
+
+impl Database {
+    /// Collect a tool's content for export. Media files are packed by the caller.
+    pub async fn build_tool_bundle(&self, tool_id: i64) -> Result<ToolBundle, Error> {
+        let structure = self.get_full_tool_structure(tool_id).await?;
+        let answer_keys = self.get_answer_keys_by_tool(tool_id).await?;
+
+        let mut subtests: Vec<BundleSubtest> = structure.subtests.into_iter().map(|full| BundleSubtest {
+            subtest_name: full.subtest.subtest_name,
+            time_limit_seconds: full.subtest.time_limit_seconds,
+            instructions: full.subtest.instructions,
+            sequence_order: full.subtest.sequence_order,
+            questions: full.questions.into_iter().map(|q| BundleQuestion {
+                answer_key: answer_keys.iter()
+                    .find(|k| k.question_id == q.id)
+                    .map(|k| BundleAnswerKey {
+                        correct_answer: k.correct_answer.clone(),
+                        scoring_rule: k.scoring_rule.clone(),
+                    }),
+                question_text: q.question_text,
+                question_type: q.question_type,
+                options: q.options,
+                media_url: q.media_url,
+                media_file: None,
+                media_name: None,
+                sequence_order: q.sequence_order,
+            }).collect(),
+        }).collect();
+
+        for subtest in subtests.iter_mut() {
+            for question in subtest.questions.iter_mut() {
+                let hash = match question.media_url.as_deref().and_then(media::hash_from_url) {
+                    Some(hash) => hash.to_string(),
+                    None => continue,
+                };
+                question.media_name = self.get_media_asset(&hash).await?.map(|a| a.original_name);
+            }
+        }
+
+        Ok(ToolBundle {
+            schema_version: BUNDLE_SCHEMA_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            tool: BundleTool {
+                name: structure.tool.name,
+                tool_type: structure.tool.tool_type,
+                category: structure.tool.category,
+                config: structure.tool.config,
+            },
+            subtests,
+        })
+    }
+
+    /// Import a bundle in a single transaction.
+    ///
+    /// `strategy` is only consulted when a tool with the same name exists.
+    pub async fn import_tool_bundle(
+        &self,
+        bundle: &ToolBundle,
+        strategy: ConflictStrategy,
+    ) -> Result<ImportReport, Error> {
+        bundle.validate().map_err(|e| Error::Protocol(format!("Invalid bundle: {}", e)))?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let existing: Option<i64> = sqlx::query("SELECT id FROM tools WHERE name = ?")
+            .bind(&bundle.tool.name)
+            .fetch_optional(&mut *tx)
+            .await?
+            .map(|row| row.get("id"));
+
+        let mut report = ImportReport {
+            tool_id: 0,
+            tool_name: bundle.tool.name.clone(),
+            strategy: existing.map(|_| strategy),
+            subtests_created: 0,
+            questions_created: 0,
+            questions_skipped: 0,
+        };
+
+        let (tool_id, merge) = match (existing, strategy) {
+            (None, _) => (insert_tool(&mut tx, &bundle.tool, &bundle.tool.name).await?, false),
+            (Some(_), ConflictStrategy::Rename) => {
+                let name = free_tool_name(&mut tx, &bundle.tool.name).await?;
+                report.tool_name = name.clone();
+                (insert_tool(&mut tx, &bundle.tool, &name).await?, false)
+            }
+            (Some(id), ConflictStrategy::Merge) => (id, true),
+            (Some(id), ConflictStrategy::Replace) => {
+                sqlx::query(
+                    "UPDATE tools SET tool_type = ?, category = ?, config = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
+                )
+                .bind(&bundle.tool.tool_type)
+                .bind(&bundle.tool.category)
+                .bind(&bundle.tool.config)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+                // Questions and answer keys go with their subtests via ON DELETE CASCADE
+                sqlx::query("DELETE FROM tool_subtests WHERE tool_id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                (id, false)
+            }
+        };
+        report.tool_id = tool_id;
+
+        for subtest in &bundle.subtests {
+            let existing_subtest: Option<i64> = if merge {
+                sqlx::query("SELECT id FROM tool_subtests WHERE tool_id = ? AND subtest_name = ?")
+                    .bind(tool_id)
+                    .bind(&subtest.subtest_name)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .map(|row| row.get("id"))
+            } else {
+                None
+            };
+
+            let subtest_id = match existing_subtest {
+                Some(id) => id,
+                None => {
+                    // Merged subtests go after the existing ones
+                    let sequence = if merge {
+                        next_sequence(&mut tx, "SELECT COALESCE(MAX(sequence_order), 0) + 1 FROM tool_subtests WHERE tool_id = ?", tool_id).await?
+                    } else {
+                        subtest.sequence_order
+                    };
+
+                    report.subtests_created += 1;
+                    sqlx::query(
+                        r#"
+                        INSERT INTO tool_subtests (tool_id, subtest_name, sequence_order, time_limit_seconds, instructions, question_count)
+                        VALUES (?, ?, ?, ?, ?, 0)
+                        "#
+                    )
+                    .bind(tool_id)
+                    .bind(&subtest.subtest_name)
+                    .bind(sequence)
+                    .bind(subtest.time_limit_seconds)
+                    .bind(&subtest.instructions)
+                    .execute(&mut *tx)
+                    .await?
+                    .last_insert_rowid()
+                }
+            };
+
+            for question in &subtest.questions {
+                if existing_subtest.is_some() {
+                    let duplicate = sqlx::query("SELECT id FROM questions WHERE subtest_id = ? AND question_text = ?")
+                        .bind(subtest_id)
+                        .bind(&question.question_text)
+                        .fetch_optional(&mut *tx)
+                        .await?
+                        .is_some();
+                    if duplicate {
+                        report.questions_skipped += 1;
+                        continue;
+                    }
+                }
+
+                let sequence = if existing_subtest.is_some() {
+                    next_sequence(&mut tx, "SELECT COALESCE(MAX(sequence_order), 0) + 1 FROM questions WHERE subtest_id = ?", subtest_id).await?
+                } else {
+                    question.sequence_order
+                };
+
+                let question_id = sqlx::query(
+                    r#"
+                    INSERT INTO questions (subtest_id, question_text, question_type, options, media_url, sequence_order)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    "#
+                )
+                .bind(subtest_id)
+                .bind(&question.question_text)
+                .bind(&question.question_type)
+                .bind(&question.options)
+                .bind(&question.media_url)
+                .bind(sequence)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+
+                if let Some(key) = &question.answer_key {
+                    sqlx::query(
+                        "INSERT INTO answer_keys (tool_id, question_id, correct_answer, scoring_rule) VALUES (?, ?, ?, ?)"
+                    )
+                    .bind(tool_id)
+                    .bind(question_id)
+                    .bind(&key.correct_answer)
+                    .bind(&key.scoring_rule)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                report.questions_created += 1;
+            }
+
+            sqlx::query("UPDATE tool_subtests SET question_count = (SELECT COUNT(*) FROM questions WHERE subtest_id = ?) WHERE id = ?")
+                .bind(subtest_id)
+                .bind(subtest_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(report)
+    }
+}
+
+async fn insert_tool(conn: &mut SqliteConnection, tool: &BundleTool, name: &str) -> Result<i64, Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO tools (name, tool_type, category, config)
+        VALUES (?, ?, ?, ?)
+        "#
+    )
+    .bind(name)
+    .bind(&tool.tool_type)
+    .bind(&tool.category)
+    .bind(&tool.config)
+    .execute(&mut *conn)
+    .await?
+    .last_insert_rowid();
+    Ok(id)
+}
+
+/// First of "Name (2)", "Name (3)", ... that isn't taken
+async fn free_tool_name(conn: &mut SqliteConnection, name: &str) -> Result<String, Error> {
+    let mut n = 2;
+    loop {
+        let candidate = format!("{} ({})", name, n);
+        let taken = sqlx::query("SELECT id FROM tools WHERE name = ?")
+            .bind(&candidate)
+            .fetch_optional(&mut *conn)
+            .await?
+            .is_some();
+        if !taken {
+            return Ok(candidate);
+        }
+        n += 1;
+    }
+}
+
+async fn next_sequence(conn: &mut SqliteConnection, sql: &str, parent_id: i64) -> Result<i64, Error> {
+    let next: i64 = sqlx::query(sql)
+        .bind(parent_id)
+        .fetch_one(&mut *conn)
+        .await?
+        .get(0);
+    Ok(next)
+}
@@ -30,6 +30,36 @@ pub struct LocalAnswer {
     pub answered_at: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionProgress {
+    pub session_id: String,
+    pub tool_id: i64,
+    pub subtest_id: i64,
+    pub question_index: i64,
+    pub time_limit_seconds: Option<i64>,
+    pub elapsed_seconds: i64,
+    pub updated_at: String,
+}
+
+/// Everything needed to put an interrupted test back on screen
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResumeState {
+    pub session: LocalSession,
+    pub answers: Vec<LocalAnswer>,
+    pub progress: Option<SessionProgress>,
+    pub remaining_seconds: Option<i64>,
+}
+
+/// Question media downloaded ahead of the test
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedMedia {
+    pub content_hash: String,
+    pub file_path: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub downloaded_at: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SyncQueueItem {
     pub id: i64,
@@ -105,6 +135,91 @@ impl CandidateDatabase {
         Ok(())
     }
 
+    pub async fn get_in_progress_session(&self, user_id: i64) -> Result<Option<LocalSession>, Error> {
+        let session = sqlx::query_as::<_, LocalSession>(
+            "SELECT * FROM local_sessions 
+             WHERE user_id = ? AND status = 'in_progress' 
+             ORDER BY started_at DESC, id DESC 
+             LIMIT 1"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    // ===== Session Progress =====
+
+    /// Checkpoint the current position; called on every subtest change and periodically while timed
+    pub async fn save_session_progress(
+        &self,
+        session_id: &str,
+        tool_id: i64,
+        subtest_id: i64,
+        question_index: i64,
+        time_limit_seconds: Option<i64>,
+        elapsed_seconds: i64,
+    ) -> Result<(), Error> {
+        sqlx::query(
+            "INSERT INTO local_session_progress 
+             (session_id, tool_id, subtest_id, question_index, time_limit_seconds, elapsed_seconds) 
+             VALUES (?, ?, ?, ?, ?, ?) 
+             ON CONFLICT(session_id) DO UPDATE SET 
+                tool_id = excluded.tool_id,
+                subtest_id = excluded.subtest_id,
+                question_index = excluded.question_index,
+                time_limit_seconds = excluded.time_limit_seconds,
+                elapsed_seconds = excluded.elapsed_seconds,
+                updated_at = datetime('now')"
+        )
+        .bind(session_id)
+        .bind(tool_id)
+        .bind(subtest_id)
+        .bind(question_index)
+        .bind(time_limit_seconds)
+        .bind(elapsed_seconds)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_session_progress(&self, session_id: &str) -> Result<Option<SessionProgress>, Error> {
+        let progress = sqlx::query_as::<_, SessionProgress>(
+            "SELECT * FROM local_session_progress WHERE session_id = ?"
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(progress)
+    }
+
+    /// Rebuild the in-progress session for a user after a crash.
+    ///
+    /// Time between the last checkpoint and the crash is not charged to the
+    /// candidate, so the remaining time is based on the saved elapsed seconds.
+    pub async fn resume_session(&self, user_id: i64) -> Result<Option<ResumeState>, Error> {
+        let session = match self.get_in_progress_session(user_id).await? {
+            Some(session) => session,
+            None => return Ok(None),
+        };
+
+        let answers = self.get_latest_answers(&session.session_id).await?;
+        let progress = self.get_session_progress(&session.session_id).await?;
+        let remaining_seconds = progress.as_ref().and_then(|p| {
+            p.time_limit_seconds.map(|limit| (limit - p.elapsed_seconds).max(0))
+        });
+
+        Ok(Some(ResumeState {
+            session,
+            answers,
+            progress,
+            remaining_seconds,
+        }))
+    }
+
     // ===== Local Answers =====
 
     pub async fn save_local_answer(
@@ -137,6 +252,22 @@ impl CandidateDatabase {
         Ok(answers)
     }
 
+    /// Latest answer per question; changed answers are appended, not overwritten
+    pub async fn get_latest_answers(&self, session_id: &str) -> Result<Vec<LocalAnswer>, Error> {
+        let answers = sqlx::query_as::<_, LocalAnswer>(
+            "SELECT * FROM local_answers 
+             WHERE id IN (
+                SELECT MAX(id) FROM local_answers WHERE session_id = ? GROUP BY question_id
+             ) 
+             ORDER BY answered_at, id"
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(answers)
+    }
+
     // ===== Sync Queue =====
 
     pub async fn add_to_sync_queue(
@@ -243,6 +374,30 @@ impl CandidateDatabase {
         Ok(())
     }
 
+    // ===== Media Cache =====
+
+    pub async fn cache_media(&self, content_hash: &str, file_path: &str, mime_type: &str, size_bytes: i64) -> Result<(), Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO local_media_cache (content_hash, file_path, mime_type, size_bytes) 
+             VALUES (?, ?, ?, ?)"
+        )
+        .bind(content_hash)
+        .bind(file_path)
+        .bind(mime_type)
+        .bind(size_bytes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_cached_media(&self, content_hash: &str) -> Result<Option<CachedMedia>, Error> {
+        sqlx::query_as::<_, CachedMedia>("SELECT * FROM local_media_cache WHERE content_hash = ?")
+            .bind(content_hash)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
     // ===== Recording Management =====
 
     pub async fn save_recording_metadata(
@@ -324,6 +479,32 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for LocalAnswer {
     }
 }
 
+impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for SessionProgress {
+    fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            session_id: row.try_get("session_id")?,
+            tool_id: row.try_get("tool_id")?,
+            subtest_id: row.try_get("subtest_id")?,
+            question_index: row.try_get("question_index")?,
+            time_limit_seconds: row.try_get("time_limit_seconds")?,
+            elapsed_seconds: row.try_get("elapsed_seconds")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for CachedMedia {
+    fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            content_hash: row.try_get("content_hash")?,
+            file_path: row.try_get("file_path")?,
+            mime_type: row.try_get("mime_type")?,
+            size_bytes: row.try_get("size_bytes")?,
+            downloaded_at: row.try_get("downloaded_at")?,
+        })
+    }
+}
+
 impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for SyncQueueItem {
     fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
         Ok(Self {
@@ -339,3 +520,7 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for SyncQueueItem {
         })
     }
 }
+
+#[cfg(test)]
+#[path = "candidate_tests.rs"]
+mod candidate_tests;
@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod candidate_db_tests {
-    use super::*;
+    use crate::db::candidate::*;
     use sqlx::sqlite::SqlitePoolOptions;
 
     async fn setup_test_db() -> CandidateDatabase {
@@ -62,6 +62,17 @@ mod candidate_db_tests {
                 uploaded_at TEXT,
                 FOREIGN KEY (session_id) REFERENCES local_sessions(session_id) ON DELETE CASCADE
             );
+
+            CREATE TABLE IF NOT EXISTS local_session_progress (
+                session_id TEXT PRIMARY KEY,
+                tool_id INTEGER NOT NULL,
+                subtest_id INTEGER NOT NULL,
+                question_index INTEGER NOT NULL DEFAULT 0,
+                time_limit_seconds INTEGER,
+                elapsed_seconds INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (session_id) REFERENCES local_sessions(session_id) ON DELETE CASCADE
+            );
             "#
         )
         .execute(&pool)
@@ -197,6 +208,63 @@ mod candidate_db_tests {
         assert_eq!(answers[2].question_id, 3);
     }
 
+    // ===== Session Resume Tests =====
+
+    #[tokio::test]
+    async fn test_resume_session_restores_progress() {
+        let db = setup_test_db().await;
+
+        db.create_local_session("test-session-17", 1, 42, None)
+            .await
+            .unwrap();
+
+        db.save_local_answer("test-session-17", 1, Some("A"))
+            .await
+            .unwrap();
+        db.save_local_answer("test-session-17", 2, Some("B"))
+            .await
+            .unwrap();
+        // Candidate changed their mind on question 1
+        db.save_local_answer("test-session-17", 1, Some("C"))
+            .await
+            .unwrap();
+
+        db.save_session_progress("test-session-17", 3, 7, 2, Some(600), 250)
+            .await
+            .unwrap();
+        db.save_session_progress("test-session-17", 3, 8, 0, Some(300), 45)
+            .await
+            .unwrap();
+
+        let state = db
+            .resume_session(42)
+            .await
+            .expect("Failed to resume")
+            .expect("No session to resume");
+
+        assert_eq!(state.session.session_id, "test-session-17");
+        assert_eq!(state.answers.len(), 2);
+        let q1 = state.answers.iter().find(|a| a.question_id == 1).unwrap();
+        assert_eq!(q1.answer, Some("C".to_string()));
+
+        let progress = state.progress.unwrap();
+        assert_eq!(progress.subtest_id, 8);
+        assert_eq!(state.remaining_seconds, Some(255));
+    }
+
+    #[tokio::test]
+    async fn test_resume_session_ignores_completed() {
+        let db = setup_test_db().await;
+
+        db.create_local_session("test-session-18", 1, 43, None)
+            .await
+            .unwrap();
+        db.complete_session("test-session-18").await.unwrap();
+
+        let state = db.resume_session(43).await.unwrap();
+        assert!(state.is_none());
+    }
+
     // ===== Sync Queue Tests =====
 
     #[tokio::test]
@@ -0,0 +1,96 @@
+// Drawing Answers
+// Stroke data for drawing questions, one drawing per session and question
+
+use sqlx::{Error, Row};
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use crate::drawing::{Drawing, Stroke};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DrawingAnswer {
+    pub id: i64,
+    pub session_id: i64,
+    pub question_id: i64,
+    pub drawing: Drawing,
+    pub stroke_count: i64,
+    pub duration_ms: i64,
+    pub updated_at: NaiveDateTime,
+}
+
+impl Database {
+    /// Store (or replace) the drawing for a question. Callers validate the drawing first.
+    pub async fn save_drawing_answer(&self, session_id: i64, question_id: i64, drawing: &Drawing) -> Result<i64, Error> {
+        let strokes = serde_json::to_value(&drawing.strokes)
+            .map_err(|e| Error::Protocol(format!("Failed to serialize strokes: {}", e)))?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO drawing_answers (session_id, question_id, canvas_width, canvas_height, strokes, stroke_count, duration_ms)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(session_id, question_id) DO UPDATE SET
+                canvas_width = excluded.canvas_width,
+                canvas_height = excluded.canvas_height,
+                strokes = excluded.strokes,
+                stroke_count = excluded.stroke_count,
+                duration_ms = excluded.duration_ms,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING id
+            "#
+        )
+        .bind(session_id)
+        .bind(question_id)
+        .bind(drawing.width as i64)
+        .bind(drawing.height as i64)
+        .bind(strokes)
+        .bind(drawing.strokes.len() as i64)
+        .bind(drawing.duration_ms())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("id"))
+    }
+
+    pub async fn get_drawing_answer(&self, session_id: i64, question_id: i64) -> Result<Option<DrawingAnswer>, Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, session_id, question_id, canvas_width, canvas_height, strokes, stroke_count, duration_ms, updated_at
+            FROM drawing_answers
+            WHERE session_id = ? AND question_id = ?
+            "#
+        )
+        .bind(session_id)
+        .bind(question_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            let strokes: Vec<Stroke> = serde_json::from_value(row.get("strokes"))
+                .map_err(|e| Error::Protocol(format!("Corrupt drawing strokes: {}", e)))?;
+            Ok(DrawingAnswer {
+                id: row.get("id"),
+                session_id: row.get("session_id"),
+                question_id: row.get("question_id"),
+                drawing: Drawing {
+                    width: row.get::<i64, _>("canvas_width") as u32,
+                    height: row.get::<i64, _>("canvas_height") as u32,
+                    strokes,
+                },
+                stroke_count: row.get("stroke_count"),
+                duration_ms: row.get("duration_ms"),
+                updated_at: row.get("updated_at"),
+            })
+        })
+        .transpose()
+    }
+
+    /// Questions of a session that have a drawing, for report generation
+    pub async fn get_session_drawing_question_ids(&self, session_id: i64) -> Result<Vec<i64>, Error> {
+        let rows = sqlx::query("SELECT question_id FROM drawing_answers WHERE session_id = ? ORDER BY question_id")
+            .bind(session_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(|r| r.get("question_id")).collect())
+    }
+}
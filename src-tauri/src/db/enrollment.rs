@@ -0,0 +1,331 @@
+// Event Enrollment
+// Capacity and deadline checks for joining an event, with a first-come waitlist
+
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::{Error, Row, SqliteConnection};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::Database;
+
+#[derive(Debug)]
+pub enum EnrollmentError {
+    EventNotFound,
+    EventArchived,
+    AlreadyEnrolled,
+    AlreadyWaitlisted { position: i64 },
+    DeadlinePassed { deadline: String },
+    /// Full and the caller asked not to be waitlisted
+    EventFull { capacity: i64 },
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for EnrollmentError {
+    fn from(err: sqlx::Error) -> Self {
+        EnrollmentError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for EnrollmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnrollmentError::EventNotFound => write!(f, "Event not found"),
+            EnrollmentError::EventArchived => write!(f, "This event has been archived"),
+            EnrollmentError::AlreadyEnrolled => write!(f, "Already enrolled in this event"),
+            EnrollmentError::AlreadyWaitlisted { position } => {
+                write!(f, "Already on the waitlist for this event (position {})", position)
+            }
+            EnrollmentError::DeadlinePassed { deadline } => {
+                write!(f, "Enrollment for this event closed on {}", deadline)
+            }
+            EnrollmentError::EventFull { capacity } => {
+                write!(f, "This event is full ({} participants)", capacity)
+            }
+            EnrollmentError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl EnrollmentError {
+    /// Short machine-readable form for the enrollment screens
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EnrollmentError::EventNotFound => "event_not_found",
+            EnrollmentError::EventArchived => "event_archived",
+            EnrollmentError::AlreadyEnrolled => "already_enrolled",
+            EnrollmentError::AlreadyWaitlisted { .. } => "already_waitlisted",
+            EnrollmentError::DeadlinePassed { .. } => "deadline_passed",
+            EnrollmentError::EventFull { .. } => "event_full",
+            EnrollmentError::DatabaseError(_) => "database_error",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum EnrollmentOutcome {
+    Enrolled { participant_id: i64 },
+    /// 1 is next in line
+    Waitlisted { position: i64 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WaitlistEntry {
+    pub user_id: i64,
+    pub username: String,
+    pub position: i64,
+    pub created_at: String,
+}
+
+/// Deadlines are stored as typed by the admin: a bare date runs to the end of that day
+pub fn parse_deadline(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&chrono::Local).naive_local());
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(dt);
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(23, 59, 59))
+}
+
+pub fn validate_enrollment_settings(max_participants: Option<i64>, enrollment_deadline: Option<&str>) -> Result<(), String> {
+    if max_participants.map(|m| m < 1).unwrap_or(false) {
+        return Err("Capacity must be at least 1 participant".to_string());
+    }
+    if let Some(deadline) = enrollment_deadline.filter(|d| !d.trim().is_empty()) {
+        if parse_deadline(deadline).is_none() {
+            return Err(format!("'{}' is not a valid deadline", deadline));
+        }
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Enroll a candidate, respecting the event's deadline and capacity. When the event
+    /// is full the candidate joins the waitlist, unless `waitlist` is false.
+    pub async fn enroll_participant(
+        &self,
+        event_id: i64,
+        user_id: i64,
+        notes: Option<String>,
+        waitlist: bool,
+    ) -> Result<EnrollmentOutcome, EnrollmentError> {
+        let mut tx = self.pool.begin().await?;
+
+        let event = sqlx::query("SELECT status, max_participants, enrollment_deadline FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(EnrollmentError::EventNotFound)?;
+        if event.get::<String, _>("status") == "archived" {
+            return Err(EnrollmentError::EventArchived);
+        }
+        let capacity: Option<i64> = event.get("max_participants");
+        let deadline: Option<String> = event.get("enrollment_deadline");
+
+        let enrolled: i64 = sqlx::query("SELECT COUNT(*) as count FROM event_participants WHERE event_id = ? AND user_id = ?")
+            .bind(event_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .get("count");
+        if enrolled > 0 {
+            return Err(EnrollmentError::AlreadyEnrolled);
+        }
+        if let Some(position) = waitlist_position(&mut tx, event_id, user_id).await? {
+            return Err(EnrollmentError::AlreadyWaitlisted { position });
+        }
+
+        if let Some(deadline) = deadline {
+            let passed = parse_deadline(&deadline)
+                .map(|d| chrono::Local::now().naive_local() > d)
+                .unwrap_or(false);
+            if passed {
+                return Err(EnrollmentError::DeadlinePassed { deadline });
+            }
+        }
+
+        // Anyone already waiting goes first if places have opened up
+        promote_from_waitlist(&mut tx, event_id).await?;
+
+        if let Some(capacity) = capacity {
+            if count_active_participants(&mut tx, event_id).await? >= capacity {
+                if !waitlist {
+                    return Err(EnrollmentError::EventFull { capacity });
+                }
+                sqlx::query("INSERT INTO event_waitlist (event_id, user_id, notes) VALUES (?, ?, ?)")
+                    .bind(event_id)
+                    .bind(user_id)
+                    .bind(notes)
+                    .execute(&mut *tx)
+                    .await?;
+                let position = waitlist_position(&mut tx, event_id, user_id).await?.unwrap_or(1);
+                tx.commit().await?;
+                return Ok(EnrollmentOutcome::Waitlisted { position });
+            }
+        }
+
+        let participant_id = sqlx::query("INSERT INTO event_participants (event_id, user_id, notes) VALUES (?, ?, ?)")
+            .bind(event_id)
+            .bind(user_id)
+            .bind(notes)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+
+        tx.commit().await?;
+        Ok(EnrollmentOutcome::Enrolled { participant_id })
+    }
+
+    /// Free places left, `None` when the event has no capacity limit
+    pub async fn remaining_places(&self, event_id: i64) -> Result<Option<i64>, Error> {
+        let capacity: Option<i64> = sqlx::query("SELECT max_participants FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get("max_participants");
+
+        match capacity {
+            Some(capacity) => {
+                let mut conn = self.pool.acquire().await?;
+                let taken = count_active_participants(&mut conn, event_id).await?;
+                Ok(Some((capacity - taken).max(0)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_event_waitlist(&self, event_id: i64) -> Result<Vec<WaitlistEntry>, Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT w.user_id, u.username, w.created_at
+            FROM event_waitlist w
+            JOIN users u ON u.id = w.user_id
+            WHERE w.event_id = ?
+            ORDER BY w.id
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().enumerate().map(|(i, row)| WaitlistEntry {
+            user_id: row.get("user_id"),
+            username: row.get("username"),
+            position: i as i64 + 1,
+            created_at: row.get("created_at"),
+        }).collect())
+    }
+
+    pub async fn leave_waitlist(&self, event_id: i64, user_id: i64) -> Result<(), Error> {
+        let result = sqlx::query("DELETE FROM event_waitlist WHERE event_id = ? AND user_id = ?")
+            .bind(event_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// Change capacity and deadline. Raising the capacity promotes waiting candidates
+    /// straight away; returns the user ids that were promoted.
+    pub async fn set_event_enrollment_settings(
+        &self,
+        event_id: i64,
+        max_participants: Option<i64>,
+        enrollment_deadline: Option<String>,
+    ) -> Result<Vec<i64>, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query("UPDATE events SET max_participants = ?, enrollment_deadline = ? WHERE id = ?")
+            .bind(max_participants)
+            .bind(enrollment_deadline.filter(|d| !d.trim().is_empty()))
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+
+        let promoted = promote_from_waitlist(&mut tx, event_id).await?;
+        tx.commit().await?;
+        Ok(promoted)
+    }
+}
+
+/// Withdrawn candidates and no-shows no longer hold a place
+async fn count_active_participants(conn: &mut SqliteConnection, event_id: i64) -> Result<i64, Error> {
+    Ok(sqlx::query("SELECT COUNT(*) as count FROM event_participants WHERE event_id = ? AND status NOT IN ('withdrawn', 'no_show')")
+        .bind(event_id)
+        .fetch_one(&mut *conn)
+        .await?
+        .get("count"))
+}
+
+async fn waitlist_position(conn: &mut SqliteConnection, event_id: i64, user_id: i64) -> Result<Option<i64>, Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT (SELECT COUNT(*) FROM event_waitlist ahead WHERE ahead.event_id = w.event_id AND ahead.id <= w.id) as position
+        FROM event_waitlist w
+        WHERE w.event_id = ? AND w.user_id = ?
+        "#
+    )
+    .bind(event_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.map(|r| r.get("position")))
+}
+
+/// Move candidates off the waitlist, oldest first, while the event has free places.
+/// Called wherever a place can open up: withdrawal, removal and capacity changes.
+pub(crate) async fn promote_from_waitlist(conn: &mut SqliteConnection, event_id: i64) -> Result<Vec<i64>, Error> {
+    let capacity: Option<i64> = match sqlx::query("SELECT max_participants FROM events WHERE id = ?")
+        .bind(event_id)
+        .fetch_optional(&mut *conn)
+        .await?
+    {
+        Some(row) => row.get("max_participants"),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut promoted = Vec::new();
+    loop {
+        if let Some(capacity) = capacity {
+            if count_active_participants(conn, event_id).await? >= capacity {
+                break;
+            }
+        }
+
+        let next = sqlx::query("SELECT id, user_id, notes FROM event_waitlist WHERE event_id = ? ORDER BY id LIMIT 1")
+            .bind(event_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        let next = match next {
+            Some(row) => row,
+            None => break,
+        };
+        let user_id: i64 = next.get("user_id");
+
+        sqlx::query("INSERT INTO event_participants (event_id, user_id, notes) VALUES (?, ?, ?)")
+            .bind(event_id)
+            .bind(user_id)
+            .bind(next.get::<Option<String>, _>("notes"))
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("DELETE FROM event_waitlist WHERE id = ?")
+            .bind(next.get::<i64, _>("id"))
+            .execute(&mut *conn)
+            .await?;
+        promoted.push(user_id);
+    }
+
+    Ok(promoted)
+}
@@ -0,0 +1,149 @@
+// Event Cloning
+// Start a new run of an existing event: same packages, order and settings, optionally the same candidates.
+// Sessions, results and waitlists always stay with the original.
+
+use chrono::{Duration, NaiveDate};
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use super::enrollment::parse_deadline;
+use super::event_lifecycle::{from_stored, to_stored};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloneEventOptions {
+    /// Event names are unique; defaults to "<original> (copy)"
+    pub name: Option<String>,
+    #[serde(default)]
+    pub copy_participants: bool,
+    /// Move the window and enrollment deadline forward, e.g. 28 for next month's run
+    pub shift_days: Option<i64>,
+}
+
+/// Shift a deadline, keeping the way it was written (date only or date and time)
+pub fn shift_deadline(deadline: &str, days: i64) -> String {
+    if let Ok(date) = NaiveDate::parse_from_str(deadline.trim(), "%Y-%m-%d") {
+        return (date + Duration::days(days)).format("%Y-%m-%d").to_string();
+    }
+    match parse_deadline(deadline) {
+        Some(dt) => (dt + Duration::days(days)).format("%Y-%m-%dT%H:%M:%S").to_string(),
+        None => deadline.to_string(),
+    }
+}
+
+impl Database {
+    /// Copy an event into a new draft with a freshly generated code. Returns the new event id.
+    pub async fn clone_event(&self, event_id: i64, options: &CloneEventOptions) -> Result<i64, Error> {
+        let source = sqlx::query(
+            "SELECT event_name, description, max_participants, enrollment_deadline, starts_at, ends_at, timezone, locale,
+                    max_attempts, retake_cooldown_hours, report_attempt, ranking_tie_breaks
+             FROM events WHERE id = ?"
+        )
+        .bind(event_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let days = options.shift_days.unwrap_or(0);
+        let shift_time = |column: &str| source.get::<Option<String>, _>(column).map(|value| {
+            from_stored(&value).map(|t| to_stored(t + Duration::days(days))).unwrap_or(value)
+        });
+        let name = options.name.clone()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| format!("{} (copy)", source.get::<String, _>("event_name")));
+        let deadline = source.get::<Option<String>, _>("enrollment_deadline")
+            .map(|d| if days != 0 { shift_deadline(&d, days) } else { d });
+
+        // Generated outside the transaction, which would otherwise hold the only connection
+        let code = self.generate_event_code().await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let new_id: i64 = sqlx::query(
+            r#"
+            INSERT INTO events (event_name, description, status, created_at, event_code, max_participants,
+                                enrollment_deadline, starts_at, ends_at, timezone, locale,
+                                max_attempts, retake_cooldown_hours, report_attempt, ranking_tie_breaks)
+            VALUES (?, ?, 'draft', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#
+        )
+        .bind(&name)
+        .bind(source.get::<Option<String>, _>("description"))
+        .bind(chrono::Local::now().naive_local())
+        .bind(&code)
+        .bind(source.get::<Option<i64>, _>("max_participants"))
+        .bind(deadline)
+        .bind(shift_time("starts_at"))
+        .bind(shift_time("ends_at"))
+        .bind(source.get::<Option<String>, _>("timezone"))
+        .bind(source.get::<Option<String>, _>("locale"))
+        .bind(source.get::<Option<i64>, _>("max_attempts"))
+        .bind(source.get::<Option<i64>, _>("retake_cooldown_hours"))
+        .bind(source.get::<String, _>("report_attempt"))
+        .bind(source.get::<String, _>("ranking_tie_breaks"))
+        .fetch_one(&mut *tx)
+        .await?
+        .get(0);
+
+        // Same frozen versions, same order
+        sqlx::query(
+            "INSERT INTO event_packages (event_id, package_id, sequence_order)
+             SELECT ?, package_id, sequence_order FROM event_packages WHERE event_id = ?"
+        )
+        .bind(new_id)
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO event_blueprint_steps (event_id, step_order, step_type, tool_id, subtest_id, title, content, duration_seconds, required)
+            SELECT ?, step_order, step_type, tool_id, subtest_id, title, content, duration_seconds, required
+            FROM event_blueprint_steps WHERE event_id = ?
+            "#
+        )
+        .bind(new_id)
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Ranking setup refers to tools, which the copied packages keep
+        sqlx::query(
+            "INSERT INTO event_composite_weights (event_id, tool_id, weight)
+             SELECT ?, tool_id, weight FROM event_composite_weights WHERE event_id = ? ORDER BY id"
+        )
+        .bind(new_id)
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO event_score_cutoffs (event_id, tool_id, scale, min_score)
+             SELECT ?, tool_id, scale, min_score FROM event_score_cutoffs WHERE event_id = ? ORDER BY id"
+        )
+        .bind(new_id)
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Candidates start over, but keep their language and accommodations
+        if options.copy_participants {
+            sqlx::query(
+                r#"
+                INSERT INTO event_participants (event_id, user_id, notes, locale, time_multiplier, extra_breaks,
+                                                break_minutes, large_font, accommodation_notes)
+                SELECT ?, user_id, notes, locale, time_multiplier, extra_breaks, break_minutes, large_font, accommodation_notes
+                FROM event_participants
+                WHERE event_id = ? AND status NOT IN ('withdrawn', 'no_show')
+                ORDER BY id
+                "#
+            )
+            .bind(new_id)
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(new_id)
+    }
+}
@@ -0,0 +1,309 @@
+// Event Codes
+// The shared 6-character code candidates enroll with: an optional validity window, a count
+// of enrollments made with it, revocation, and a history of codes that were rotated out.
+
+use chrono::{DateTime, FixedOffset, Utc};
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::Database;
+use super::event_lifecycle::{from_stored, local_timezone, parse_schedule_time, parse_timezone, to_stored};
+use super::models::Event;
+
+#[derive(Debug)]
+pub enum EventCodeError {
+    NotFound,
+    NotYetValid { valid_from: String },
+    Expired { expired_at: String },
+    Revoked { revoked_at: String },
+    /// An old code that has since been rotated
+    Replaced,
+    InvalidWindow(String),
+    /// Rotating and revoking is for admins and operators
+    NotAllowed,
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for EventCodeError {
+    fn from(err: sqlx::Error) -> Self {
+        EventCodeError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for EventCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventCodeError::NotFound => write!(f, "Invalid event code"),
+            EventCodeError::NotYetValid { valid_from } => write!(f, "This event code is valid from {}", valid_from),
+            EventCodeError::Expired { expired_at } => write!(f, "This event code expired on {}", expired_at),
+            EventCodeError::Revoked { revoked_at } => write!(f, "This event code was revoked on {}", revoked_at),
+            EventCodeError::Replaced => write!(f, "This event code has been replaced; ask the organiser for the new one"),
+            EventCodeError::InvalidWindow(msg) => write!(f, "Invalid code validity: {}", msg),
+            EventCodeError::NotAllowed => write!(f, "Only admins and operators can change event codes"),
+            EventCodeError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl EventCodeError {
+    /// Short machine-readable form for API clients
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EventCodeError::NotFound => "not_found",
+            EventCodeError::NotYetValid { .. } => "not_yet_valid",
+            EventCodeError::Expired { .. } => "expired",
+            EventCodeError::Revoked { .. } => "revoked",
+            EventCodeError::Replaced => "replaced",
+            EventCodeError::InvalidWindow(_) => "invalid_window",
+            EventCodeError::NotAllowed => "not_allowed",
+            EventCodeError::DatabaseError(_) => "database_error",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventCodeStatus {
+    pub event_id: i64,
+    pub event_code: Option<String>,
+    pub valid_from: Option<String>,
+    pub expires_at: Option<String>,
+    pub use_count: i64,
+    pub revoked_at: Option<String>,
+    /// Whether candidates can enroll with the code right now
+    pub usable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EventCodeHistoryEntry {
+    pub id: i64,
+    pub event_code: String,
+    /// rotated or revoked
+    pub action: String,
+    pub use_count: i64,
+    pub changed_by: Option<i64>,
+    pub changed_by_username: Option<String>,
+    pub changed_at: String,
+}
+
+/// Times are shown in the event's own timezone
+fn display_time(stored: &str, timezone: FixedOffset) -> String {
+    from_stored(stored)
+        .map(|t| format!("{} ({})", t.with_timezone(&timezone).format("%Y-%m-%d %H:%M"), timezone))
+        .unwrap_or_else(|| stored.to_string())
+}
+
+fn event_timezone(event: &Event) -> FixedOffset {
+    event.timezone.as_deref().and_then(parse_timezone).unwrap_or_else(local_timezone)
+}
+
+/// Whether an event's code may be used at `now`
+pub fn check_event_code(event: &Event, now: DateTime<Utc>) -> Result<(), EventCodeError> {
+    let timezone = event_timezone(event);
+    if let Some(revoked_at) = &event.code_revoked_at {
+        return Err(EventCodeError::Revoked { revoked_at: display_time(revoked_at, timezone) });
+    }
+    if let Some(valid_from) = &event.code_valid_from {
+        if from_stored(valid_from).map(|t| now < t).unwrap_or(false) {
+            return Err(EventCodeError::NotYetValid { valid_from: display_time(valid_from, timezone) });
+        }
+    }
+    if let Some(expires_at) = &event.code_expires_at {
+        if from_stored(expires_at).map(|t| now >= t).unwrap_or(false) {
+            return Err(EventCodeError::Expired { expired_at: display_time(expires_at, timezone) });
+        }
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Look up an event by its code, rejecting codes that are outside their window,
+    /// revoked, or rotated out
+    pub async fn get_event_by_code(&self, code: &str) -> Result<Event, EventCodeError> {
+        let code = code.trim().to_uppercase();
+        let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE event_code = ?")
+            .bind(&code)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match event {
+            Some(event) => {
+                check_event_code(&event, Utc::now())?;
+                Ok(event)
+            }
+            None => {
+                let retired: i64 = sqlx::query("SELECT COUNT(*) as count FROM event_code_history WHERE event_code = ?")
+                    .bind(&code)
+                    .fetch_one(&self.pool)
+                    .await?
+                    .get("count");
+                Err(if retired > 0 { EventCodeError::Replaced } else { EventCodeError::NotFound })
+            }
+        }
+    }
+
+    pub async fn get_event_code_status(&self, event_id: i64) -> Result<EventCodeStatus, Error> {
+        let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(EventCodeStatus {
+            event_id,
+            usable: event.event_code.is_some() && check_event_code(&event, Utc::now()).is_ok(),
+            event_code: event.event_code,
+            valid_from: event.code_valid_from,
+            expires_at: event.code_expires_at,
+            use_count: event.code_use_count,
+            revoked_at: event.code_revoked_at,
+        })
+    }
+
+    /// Count an enrollment made with the event's code
+    pub async fn record_event_code_use(&self, event_id: i64) -> Result<(), Error> {
+        sqlx::query("UPDATE events SET code_use_count = code_use_count + 1 WHERE id = ?")
+            .bind(event_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Limit when the current code works; times are read in the event's timezone
+    pub async fn set_event_code_validity(
+        &self,
+        event_id: i64,
+        valid_from: Option<&str>,
+        expires_at: Option<&str>,
+    ) -> Result<EventCodeStatus, EventCodeError> {
+        let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(EventCodeError::NotFound)?;
+        let timezone = event_timezone(&event);
+
+        let parse = |value: Option<&str>| -> Result<Option<DateTime<Utc>>, EventCodeError> {
+            match value.filter(|v| !v.trim().is_empty()) {
+                Some(v) => parse_schedule_time(v, timezone)
+                    .map(Some)
+                    .ok_or_else(|| EventCodeError::InvalidWindow(format!("'{}' is not a valid time", v))),
+                None => Ok(None),
+            }
+        };
+        let (from, until) = (parse(valid_from)?, parse(expires_at)?);
+        if let (Some(from), Some(until)) = (from, until) {
+            if until <= from {
+                return Err(EventCodeError::InvalidWindow("the expiry must be after the start".to_string()));
+            }
+        }
+
+        sqlx::query("UPDATE events SET code_valid_from = ?, code_expires_at = ? WHERE id = ?")
+            .bind(from.map(to_stored))
+            .bind(until.map(to_stored))
+            .bind(event_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(self.get_event_code_status(event_id).await?)
+    }
+
+    /// Replace the event's code with a fresh one. The old code goes into the history and
+    /// stops working; the validity window is kept, the usage count starts again.
+    pub async fn rotate_event_code(&self, event_id: i64, rotated_by: i64) -> Result<String, EventCodeError> {
+        self.ensure_code_manager(rotated_by).await?;
+        let new_code = self.generate_event_code().await?;
+        let now = to_stored(Utc::now());
+
+        let mut tx = self.pool.begin().await?;
+        let current = sqlx::query("SELECT event_code, code_use_count, code_revoked_at FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(EventCodeError::NotFound)?;
+
+        // A revoked code is already in the history
+        let old_code: Option<String> = current.get("event_code");
+        if let (Some(old_code), None) = (old_code, current.get::<Option<String>, _>("code_revoked_at")) {
+            sqlx::query(
+                "INSERT INTO event_code_history (event_id, event_code, action, use_count, changed_by, changed_at) VALUES (?, ?, 'rotated', ?, ?, ?)"
+            )
+            .bind(event_id)
+            .bind(old_code)
+            .bind(current.get::<i64, _>("code_use_count"))
+            .bind(rotated_by)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("UPDATE events SET event_code = ?, code_use_count = 0, code_revoked_at = NULL WHERE id = ?")
+            .bind(&new_code)
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(new_code)
+    }
+
+    /// Stop the current code from working without issuing a new one
+    pub async fn revoke_event_code(&self, event_id: i64, revoked_by: i64) -> Result<EventCodeStatus, EventCodeError> {
+        self.ensure_code_manager(revoked_by).await?;
+        let now = to_stored(Utc::now());
+
+        let mut tx = self.pool.begin().await?;
+        let current = sqlx::query("SELECT event_code, code_use_count FROM events WHERE id = ? AND code_revoked_at IS NULL")
+            .bind(event_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if let Some(current) = current {
+            if let Some(code) = current.get::<Option<String>, _>("event_code") {
+                sqlx::query(
+                    "INSERT INTO event_code_history (event_id, event_code, action, use_count, changed_by, changed_at) VALUES (?, ?, 'revoked', ?, ?, ?)"
+                )
+                .bind(event_id)
+                .bind(code)
+                .bind(current.get::<i64, _>("code_use_count"))
+                .bind(revoked_by)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+
+                sqlx::query("UPDATE events SET code_revoked_at = ? WHERE id = ?")
+                    .bind(&now)
+                    .bind(event_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+
+        self.get_event_code_status(event_id).await.map_err(|e| match e {
+            Error::RowNotFound => EventCodeError::NotFound,
+            e => EventCodeError::DatabaseError(e),
+        })
+    }
+
+    /// Past codes, newest first
+    pub async fn get_event_code_history(&self, event_id: i64) -> Result<Vec<EventCodeHistoryEntry>, Error> {
+        sqlx::query_as::<_, EventCodeHistoryEntry>(
+            r#"
+            SELECT h.id, h.event_code, h.action, h.use_count, h.changed_by, u.username as changed_by_username, h.changed_at
+            FROM event_code_history h
+            LEFT JOIN users u ON u.id = h.changed_by
+            WHERE h.event_id = ?
+            ORDER BY h.changed_at DESC, h.id DESC
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn ensure_code_manager(&self, user_id: i64) -> Result<(), EventCodeError> {
+        if !self.is_rater(user_id).await? {
+            return Err(EventCodeError::NotAllowed);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+// Event Lifecycle
+// draft -> active -> archived, with a scheduled testing window that opens events and closes
+// them to new sessions. A closed event stays active, so its window can still be extended.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::Database;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventStatus {
+    Draft,
+    Active,
+    Archived,
+}
+
+impl EventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventStatus::Draft => "draft",
+            EventStatus::Active => "active",
+            EventStatus::Archived => "archived",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "draft" => Some(EventStatus::Draft),
+            "active" => Some(EventStatus::Active),
+            "archived" => Some(EventStatus::Archived),
+            _ => None,
+        }
+    }
+
+    /// Archiving is final; an active event can go back to draft to be reworked
+    pub fn can_transition_to(&self, next: EventStatus) -> bool {
+        use EventStatus::*;
+        matches!(
+            (self, next),
+            (Draft, Active) | (Draft, Archived) | (Active, Draft) | (Active, Archived)
+        )
+    }
+}
+
+impl fmt::Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub enum EventLifecycleError {
+    NotFound(i64),
+    UnknownStatus(String),
+    InvalidTransition { from: EventStatus, to: EventStatus },
+    /// Archived events are read-only
+    Archived,
+    /// Drafts take no sessions until activated, by hand or by their window opening
+    NotActive,
+    InvalidSchedule(String),
+    /// Times are in the event's own timezone
+    NotOpenYet { opens_at: String },
+    WindowClosed { closed_at: String },
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for EventLifecycleError {
+    fn from(err: sqlx::Error) -> Self {
+        EventLifecycleError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for EventLifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventLifecycleError::NotFound(id) => write!(f, "Event {} not found", id),
+            EventLifecycleError::UnknownStatus(status) => write!(f, "Unknown event status '{}'", status),
+            EventLifecycleError::InvalidTransition { from, to } => {
+                write!(f, "Cannot change event from '{}' to '{}'", from, to)
+            }
+            EventLifecycleError::Archived => write!(f, "This event has been archived and is read-only"),
+            EventLifecycleError::NotActive => write!(f, "This event has not been activated yet"),
+            EventLifecycleError::InvalidSchedule(reason) => write!(f, "Invalid schedule: {}", reason),
+            EventLifecycleError::NotOpenYet { opens_at } => write!(f, "This event opens at {}", opens_at),
+            EventLifecycleError::WindowClosed { closed_at } => write!(f, "This event closed at {}", closed_at),
+            EventLifecycleError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleWindow {
+    Unscheduled,
+    Upcoming,
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventSchedule {
+    pub event_id: i64,
+    pub status: String,
+    /// UTC offset the event was scheduled in, e.g. "+07:00"
+    pub timezone: Option<String>,
+    /// UTC
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    /// The same times in the event's timezone, for display
+    pub starts_at_local: Option<String>,
+    pub ends_at_local: Option<String>,
+    pub window: ScheduleWindow,
+}
+
+/// UTC offsets ("+07:00", "+0700", "-03"), "UTC" and the Indonesian zone names
+pub fn parse_timezone(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let hours = |h: i32| FixedOffset::east_opt(h * 3600);
+    match value.to_uppercase().as_str() {
+        "UTC" | "Z" | "GMT" => return hours(0),
+        "WIB" => return hours(7),
+        "WITA" => return hours(8),
+        "WIT" => return hours(9),
+        _ => {}
+    }
+
+    let sign = match value.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = value[1..].chars().filter(|c| *c != ':').collect();
+    let (h, m) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (digits[..2].parse::<i32>().ok()?, digits[2..].parse::<i32>().ok()?),
+        _ => return None,
+    };
+    if h > 14 || m > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (h * 3600 + m * 60))
+}
+
+/// The machine's current offset, used when an event doesn't name one
+pub fn local_timezone() -> FixedOffset {
+    chrono::Local::now().offset().fix()
+}
+
+/// A wall-clock time in `timezone`; times with their own offset (RFC 3339) keep it
+pub fn parse_schedule_time(value: &str, timezone: FixedOffset) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"].iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|naive| timezone.from_local_datetime(&naive).single())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+pub fn to_stored(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+pub(crate) fn from_stored(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&Utc))
+}
+
+fn local_display(time: DateTime<Utc>, timezone: FixedOffset) -> String {
+    format!("{} ({})", time.with_timezone(&timezone).format("%Y-%m-%d %H:%M"), timezone)
+}
+
+pub fn window_at(starts_at: Option<DateTime<Utc>>, ends_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> ScheduleWindow {
+    match (starts_at, ends_at) {
+        (None, None) => ScheduleWindow::Unscheduled,
+        (Some(start), _) if now < start => ScheduleWindow::Upcoming,
+        (_, Some(end)) if now >= end => ScheduleWindow::Closed,
+        _ => ScheduleWindow::Open,
+    }
+}
+
+/// `create_event` takes a plain event date: a bare date schedules that whole day
+pub fn window_for_event_date(event_date: &str, timezone: FixedOffset) -> Option<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+    if let Ok(day) = NaiveDate::parse_from_str(event_date.trim(), "%Y-%m-%d") {
+        let start = timezone.from_local_datetime(&day.and_hms_opt(0, 0, 0)?).single()?;
+        let end = timezone.from_local_datetime(&day.succ_opt()?.and_hms_opt(0, 0, 0)?).single()?;
+        return Some((start.with_timezone(&Utc), Some(end.with_timezone(&Utc))));
+    }
+    parse_schedule_time(event_date, timezone).map(|start| (start, None))
+}
+
+struct ScheduleRow {
+    status: String,
+    timezone_name: Option<String>,
+    timezone: FixedOffset,
+    starts_at: Option<DateTime<Utc>>,
+    ends_at: Option<DateTime<Utc>>,
+}
+
+impl Database {
+    async fn get_schedule_row(&self, event_id: i64) -> Result<ScheduleRow, EventLifecycleError> {
+        let row = sqlx::query("SELECT status, starts_at, ends_at, timezone FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(EventLifecycleError::NotFound(event_id))?;
+
+        let stored = |column: &str| row.get::<Option<String>, _>(column).as_deref().and_then(from_stored);
+        let timezone_name: Option<String> = row.get("timezone");
+        Ok(ScheduleRow {
+            status: row.get("status"),
+            timezone: timezone_name.as_deref().and_then(parse_timezone).unwrap_or_else(local_timezone),
+            timezone_name,
+            starts_at: stored("starts_at"),
+            ends_at: stored("ends_at"),
+        })
+    }
+
+    pub async fn get_event_schedule(&self, event_id: i64) -> Result<EventSchedule, EventLifecycleError> {
+        let row = self.get_schedule_row(event_id).await?;
+
+        Ok(EventSchedule {
+            event_id,
+            window: window_at(row.starts_at, row.ends_at, Utc::now()),
+            status: row.status,
+            timezone: row.timezone_name,
+            starts_at: row.starts_at.map(to_stored),
+            ends_at: row.ends_at.map(to_stored),
+            starts_at_local: row.starts_at.map(|t| local_display(t, row.timezone)),
+            ends_at_local: row.ends_at.map(|t| local_display(t, row.timezone)),
+        })
+    }
+
+    /// Set or clear the testing window. Times without an offset are read in `timezone`
+    /// (the machine's timezone when omitted).
+    pub async fn set_event_schedule(
+        &self,
+        event_id: i64,
+        starts_at: Option<&str>,
+        ends_at: Option<&str>,
+        timezone: Option<&str>,
+    ) -> Result<EventSchedule, EventLifecycleError> {
+        self.ensure_event_editable(event_id).await?;
+
+        let offset = match timezone.filter(|t| !t.trim().is_empty()) {
+            Some(tz) => parse_timezone(tz)
+                .ok_or_else(|| EventLifecycleError::InvalidSchedule(format!("unknown timezone '{}'", tz)))?,
+            None => local_timezone(),
+        };
+        let parse = |value: Option<&str>| -> Result<Option<DateTime<Utc>>, EventLifecycleError> {
+            match value.filter(|v| !v.trim().is_empty()) {
+                Some(v) => parse_schedule_time(v, offset)
+                    .map(Some)
+                    .ok_or_else(|| EventLifecycleError::InvalidSchedule(format!("'{}' is not a valid time", v))),
+                None => Ok(None),
+            }
+        };
+        let (start, end) = (parse(starts_at)?, parse(ends_at)?);
+        if let (Some(start), Some(end)) = (start, end) {
+            if end <= start {
+                return Err(EventLifecycleError::InvalidSchedule("the end must be after the start".to_string()));
+            }
+        }
+
+        sqlx::query("UPDATE events SET starts_at = ?, ends_at = ?, timezone = ? WHERE id = ?")
+            .bind(start.map(to_stored))
+            .bind(end.map(to_stored))
+            .bind(offset.to_string())
+            .bind(event_id)
+            .execute(&self.pool)
+            .await?;
+
+        self.apply_event_schedules(Utc::now()).await?;
+        self.get_event_schedule(event_id).await
+    }
+
+    pub async fn set_event_status(&self, event_id: i64, to: EventStatus) -> Result<EventSchedule, EventLifecycleError> {
+        let row = self.get_schedule_row(event_id).await?;
+        let from = EventStatus::parse(&row.status).ok_or_else(|| EventLifecycleError::UnknownStatus(row.status.clone()))?;
+        if from == EventStatus::Archived {
+            return Err(EventLifecycleError::Archived);
+        }
+        if !from.can_transition_to(to) {
+            return Err(EventLifecycleError::InvalidTransition { from, to });
+        }
+        // It would only be closed again straight away
+        if to == EventStatus::Active && window_at(row.starts_at, row.ends_at, Utc::now()) == ScheduleWindow::Closed {
+            return Err(EventLifecycleError::InvalidSchedule("the event's window has already ended".to_string()));
+        }
+
+        sqlx::query("UPDATE events SET status = ? WHERE id = ?")
+            .bind(to.as_str())
+            .bind(event_id)
+            .execute(&self.pool)
+            .await?;
+
+        self.get_event_schedule(event_id).await
+    }
+
+    /// Activate scheduled drafts whose window has opened. Events whose window has ended
+    /// stay active but take no new sessions (see `check_session_window`), so the window can
+    /// still be extended; archiving is left to the admin. Returns the events that changed.
+    pub async fn apply_event_schedules(&self, now: DateTime<Utc>) -> Result<Vec<(i64, EventStatus)>, Error> {
+        let now = to_stored(now);
+
+        let activated: Vec<i64> = sqlx::query(
+            r#"
+            UPDATE events SET status = 'active'
+            WHERE status = 'draft' AND starts_at IS NOT NULL AND starts_at <= ?
+              AND (ends_at IS NULL OR ends_at > ?)
+            RETURNING id
+            "#
+        )
+        .bind(&now)
+        .bind(&now)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| row.get("id"))
+        .collect();
+
+        Ok(activated.into_iter().map(|id| (id, EventStatus::Active)).collect())
+    }
+
+    /// Whether the event exists and has been archived. Missing events aren't archived.
+    pub async fn is_event_archived(&self, event_id: i64) -> Result<bool, Error> {
+        let count: i64 = sqlx::query("SELECT COUNT(*) as count FROM events WHERE id = ? AND status = 'archived'")
+            .bind(event_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+        Ok(count > 0)
+    }
+
+    /// Refuse changes to archived events
+    pub async fn ensure_event_editable(&self, event_id: i64) -> Result<(), EventLifecycleError> {
+        let row = self.get_schedule_row(event_id).await?;
+        if row.status == EventStatus::Archived.as_str() {
+            return Err(EventLifecycleError::Archived);
+        }
+        Ok(())
+    }
+
+    /// Whether a candidate may start a session now: the event must be active and, if it
+    /// has a schedule, inside its window.
+    pub async fn check_session_window(&self, event_id: i64) -> Result<(), EventLifecycleError> {
+        let now = Utc::now();
+        self.apply_event_schedules(now).await?;
+
+        let row = self.get_schedule_row(event_id).await?;
+        if row.status == EventStatus::Archived.as_str() {
+            return Err(EventLifecycleError::Archived);
+        }
+        match window_at(row.starts_at, row.ends_at, now) {
+            ScheduleWindow::Upcoming => Err(EventLifecycleError::NotOpenYet {
+                opens_at: row.starts_at.map(|t| local_display(t, row.timezone)).unwrap_or_default(),
+            }),
+            ScheduleWindow::Closed => Err(EventLifecycleError::WindowClosed {
+                closed_at: row.ends_at.map(|t| local_display(t, row.timezone)).unwrap_or_default(),
+            }),
+            // Drafts with an open window were activated above
+            _ if row.status != EventStatus::Active.as_str() => Err(EventLifecycleError::NotActive),
+            ScheduleWindow::Open | ScheduleWindow::Unscheduled => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+// Localized Content
+// Per-locale translations of questions and subtest instructions, and the language choice per event/candidate
+
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use crate::localization::normalize_locale;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct QuestionTranslation {
+    pub question_id: i64,
+    pub locale: String,
+    pub question_text: String,
+    pub options: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SubtestTranslation {
+    pub subtest_id: i64,
+    pub locale: String,
+    pub instructions: serde_json::Value,
+}
+
+/// All translations of one tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolTranslations {
+    pub questions: Vec<QuestionTranslation>,
+    pub subtests: Vec<SubtestTranslation>,
+}
+
+impl Database {
+    pub async fn set_tool_content_locale(&self, tool_id: i64, locale: &str) -> Result<(), Error> {
+        sqlx::query("UPDATE tools SET content_locale = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(normalize_locale(locale))
+            .bind(tool_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn upsert_question_translation(
+        &self,
+        question_id: i64,
+        locale: &str,
+        question_text: &str,
+        options: Option<serde_json::Value>,
+    ) -> Result<(), Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO question_translations (question_id, locale, question_text, options)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(question_id, locale) DO UPDATE SET
+                question_text = excluded.question_text,
+                options = excluded.options,
+                updated_at = CURRENT_TIMESTAMP
+            "#
+        )
+        .bind(question_id)
+        .bind(normalize_locale(locale))
+        .bind(question_text)
+        .bind(options)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_question_translation(&self, question_id: i64, locale: &str) -> Result<(), Error> {
+        sqlx::query("DELETE FROM question_translations WHERE question_id = ? AND locale = ?")
+            .bind(question_id)
+            .bind(normalize_locale(locale))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn upsert_subtest_translation(
+        &self,
+        subtest_id: i64,
+        locale: &str,
+        instructions: serde_json::Value,
+    ) -> Result<(), Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO subtest_translations (subtest_id, locale, instructions)
+            VALUES (?, ?, ?)
+            ON CONFLICT(subtest_id, locale) DO UPDATE SET
+                instructions = excluded.instructions,
+                updated_at = CURRENT_TIMESTAMP
+            "#
+        )
+        .bind(subtest_id)
+        .bind(normalize_locale(locale))
+        .bind(instructions)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_subtest_translation(&self, subtest_id: i64, locale: &str) -> Result<(), Error> {
+        sqlx::query("DELETE FROM subtest_translations WHERE subtest_id = ? AND locale = ?")
+            .bind(subtest_id)
+            .bind(normalize_locale(locale))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_tool_translations(&self, tool_id: i64) -> Result<ToolTranslations, Error> {
+        let questions = sqlx::query_as::<_, QuestionTranslation>(
+            r#"
+            SELECT qt.question_id, qt.locale, qt.question_text, qt.options
+            FROM question_translations qt
+            JOIN questions q ON q.id = qt.question_id
+            JOIN tool_subtests ts ON ts.id = q.subtest_id
+            WHERE ts.tool_id = ?
+            ORDER BY qt.question_id, qt.locale
+            "#
+        )
+        .bind(tool_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let subtests = sqlx::query_as::<_, SubtestTranslation>(
+            r#"
+            SELECT st.subtest_id, st.locale, st.instructions
+            FROM subtest_translations st
+            JOIN tool_subtests ts ON ts.id = st.subtest_id
+            WHERE ts.tool_id = ?
+            ORDER BY st.subtest_id, st.locale
+            "#
+        )
+        .bind(tool_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ToolTranslations { questions, subtests })
+    }
+
+    /// `None` clears the event's language, leaving each tool in its own language
+    pub async fn set_event_locale(&self, event_id: i64, locale: Option<&str>) -> Result<(), Error> {
+        sqlx::query("UPDATE events SET locale = ? WHERE id = ?")
+            .bind(locale.map(normalize_locale))
+            .bind(event_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_participant_locale(&self, event_id: i64, user_id: i64, locale: Option<&str>) -> Result<(), Error> {
+        let result = sqlx::query("UPDATE event_participants SET locale = ? WHERE event_id = ? AND user_id = ?")
+            .bind(locale.map(normalize_locale))
+            .bind(event_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// Language preferences for a candidate in an event, most specific first: (candidate, event)
+    pub async fn get_locale_preferences(&self, event_id: i64, user_id: Option<i64>) -> Result<(Option<String>, Option<String>), Error> {
+        let event_locale: Option<String> = sqlx::query("SELECT locale FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get("locale");
+
+        let participant_locale = match user_id {
+            Some(user_id) => sqlx::query("SELECT locale FROM event_participants WHERE event_id = ? AND user_id = ?")
+                .bind(event_id)
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .and_then(|row| row.get::<Option<String>, _>("locale")),
+            None => None,
+        };
+
+        Ok((participant_locale, event_locale))
+    }
+}
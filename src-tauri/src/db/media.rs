@@ -0,0 +1,83 @@
+// Media Assets
+// Metadata for files in the content-addressed media store
+
+use sqlx::Error;
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+
+use super::Database;
+use crate::media::{self, StoredMedia};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MediaAsset {
+    pub id: i64,
+    pub content_hash: String,
+    pub original_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub storage_path: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl MediaAsset {
+    pub fn url(&self) -> String {
+        media::media_url(&self.content_hash)
+    }
+}
+
+impl Database {
+    /// Record a stored file. Uploading the same content twice returns the existing asset.
+    pub async fn register_media_asset(&self, stored: &StoredMedia) -> Result<MediaAsset, Error> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO media_assets (content_hash, original_name, mime_type, size_bytes, storage_path) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&stored.content_hash)
+        .bind(&stored.original_name)
+        .bind(&stored.mime_type)
+        .bind(stored.size_bytes)
+        .bind(stored.storage_path.to_string_lossy().to_string())
+        .execute(&self.pool)
+        .await?;
+
+        self.get_media_asset(&stored.content_hash).await?
+            .ok_or(Error::RowNotFound)
+    }
+
+    pub async fn get_media_asset(&self, content_hash: &str) -> Result<Option<MediaAsset>, Error> {
+        sqlx::query_as::<_, MediaAsset>("SELECT * FROM media_assets WHERE content_hash = ?")
+            .bind(content_hash)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Every stored asset referenced by the packages an event delivers
+    pub async fn get_event_media_assets(&self, event_id: i64) -> Result<Vec<MediaAsset>, Error> {
+        let tool_ids = sqlx::query_as::<_, (i64,)>(
+            "SELECT DISTINCT p.tool_id FROM event_packages ep JOIN packages p ON p.id = ep.package_id WHERE ep.event_id = ?"
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut hashes = BTreeSet::new();
+        for (tool_id,) in tool_ids {
+            let snapshot = self.get_event_tool_snapshot(event_id, tool_id).await?;
+            for subtest in &snapshot.structure.subtests {
+                for question in &subtest.questions {
+                    if let Some(hash) = question.media_url.as_deref().and_then(media::hash_from_url) {
+                        hashes.insert(hash.to_string());
+                    }
+                }
+            }
+        }
+
+        let mut assets = Vec::new();
+        for hash in hashes {
+            if let Some(asset) = self.get_media_asset(&hash).await? {
+                assets.push(asset);
+            }
+        }
+        Ok(assets)
+    }
+}
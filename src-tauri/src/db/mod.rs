@@ -2,6 +2,26 @@ pub mod models;
 pub mod seed;
 pub mod candidate;
 pub mod admin_sync;
+pub mod session_lifecycle;
+pub mod packages;
+pub mod bundles;
+pub mod media;
+pub mod localization;
+pub mod drawings;
+pub mod rating;
+pub mod accommodations;
+pub mod blueprint;
+pub mod enrollment;
+pub mod event_lifecycle;
+pub mod event_clone;
+pub mod participants;
+pub mod access_tokens;
+pub mod event_codes;
+pub mod progress;
+pub mod retakes;
+pub mod withdrawal;
+pub mod statistics;
+pub mod ranking;
 
 use sqlx::{SqlitePool, Error, Row};
 use self::models::*;
@@ -18,6 +38,10 @@ impl Database {
         Self { pool }
     }
 
+    pub(crate) fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
     // --- Tools ---
     pub async fn create_tool(&self, name: &str, tool_type: &str, category: &str, description: &str) -> Result<i64, Error> {
         let id = sqlx::query(
@@ -70,6 +94,13 @@ impl Database {
             .await
     }
 
+    pub async fn get_question_by_id(&self, id: i64) -> Result<Question, Error> {
+        sqlx::query_as::<_, Question>("SELECT * FROM questions WHERE id = ?")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
     pub async fn create_subtest(&self, tool_id: i64, name: &str, sequence: i64, time_limit: Option<i64>) -> Result<i64, Error> {
          let id = sqlx::query(
             r#"
@@ -113,6 +144,45 @@ impl Database {
         Ok(id)
     }
 
+    /// Append questions to a subtest in one transaction; nothing is written if any insert fails
+    pub async fn create_questions_bulk(
+        &self,
+        subtest_id: i64,
+        questions: &[crate::question_import::NewQuestion],
+    ) -> Result<Vec<i64>, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut sequence: i64 = sqlx::query("SELECT COALESCE(MAX(sequence_order), 0) FROM questions WHERE subtest_id = ?")
+            .bind(subtest_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .get(0);
+
+        let mut ids = Vec::with_capacity(questions.len());
+        for question in questions {
+            sequence += 1;
+            let id = sqlx::query(
+                r#"
+                INSERT INTO questions (subtest_id, question_text, question_type, options, media_url, sequence_order)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(subtest_id)
+            .bind(&question.text)
+            .bind(&question.question_type)
+            .bind(&question.options)
+            .bind(&question.media_url)
+            .bind(sequence)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+            ids.push(id);
+        }
+
+        tx.commit().await?;
+        Ok(ids)
+    }
+
     pub async fn delete_question(&self, id: i64) -> Result<(), Error> {
         sqlx::query("DELETE FROM questions WHERE id = ?")
             .bind(id)
@@ -143,21 +213,34 @@ impl Database {
         &self, 
         event_id: i64, 
         participant_id: &str,
+        user_id: Option<i64>,
         metadata: Option<serde_json::Value>
     ) -> Result<i64, Error> {
+        use self::session_lifecycle::{SessionStatus, record_transition, sync_participant_status};
+
+        let mut tx = self.pool.begin().await?;
+
         let id = sqlx::query(
             r#"
-            INSERT INTO sessions (event_id, participant_id, metadata, status, started_at)
-            VALUES (?, ?, ?, 'active', CURRENT_TIMESTAMP)
+            INSERT INTO sessions (event_id, user_id, participant_id, metadata, status, started_at, status_changed_at, attempt_number)
+            VALUES (?1, ?2, ?3, ?4, 'active', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP,
+                    COALESCE((SELECT attempt_number FROM event_participants WHERE event_id = ?1 AND user_id = ?2), 1))
             RETURNING id
             "#
         )
         .bind(event_id)
+        .bind(user_id)
         .bind(participant_id)
         .bind(metadata)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?
         .get(0);
+
+        // Sessions are started immediately, so log the implicit pending -> active step
+        record_transition(&mut tx, id, SessionStatus::Pending, SessionStatus::Active, None).await?;
+        sync_participant_status(&mut tx, id, SessionStatus::Active).await?;
+
+        tx.commit().await?;
         
         Ok(id)
     }
@@ -211,16 +294,24 @@ impl Database {
     }
 
     pub async fn create_event(&self, name: &str, description: Option<String>, event_date: Option<String>) -> Result<i64, Error> {
+        use self::event_lifecycle::{local_timezone, to_stored, window_for_event_date};
+
+        // The event date becomes the testing window, in this machine's timezone
+        let timezone = local_timezone();
+        let window = event_date.as_deref().and_then(|date| window_for_event_date(date, timezone));
+
         let id = sqlx::query(
             r#"
-            INSERT INTO events (event_name, description, status, created_at, enrollment_deadline)
-            VALUES (?, ?, 'draft', ?, ?)
+            INSERT INTO events (event_name, description, status, created_at, starts_at, ends_at, timezone)
+            VALUES (?, ?, 'draft', ?, ?, ?, ?)
             "#
         )
         .bind(name)
         .bind(description)
         .bind(chrono::Local::now().naive_local())
-        .bind(event_date)
+        .bind(window.map(|(start, _)| to_stored(start)))
+        .bind(window.and_then(|(_, end)| end).map(to_stored))
+        .bind(window.map(|_| timezone.to_string()))
         .execute(&self.pool)
         .await?
         .last_insert_rowid();
@@ -240,67 +331,63 @@ impl Database {
     }
 
     pub async fn add_tools_to_event(&self, event_id: i64, tool_ids: Vec<i64>) -> Result<(), Error> {
-        println!("DEBUG: add_tools_to_event START. Event: {}, Tools: {:?}", event_id, tool_ids);
-        for (index, tool_id) in tool_ids.iter().enumerate() {
-            // First check if the tool exists
-            let tool_exists = sqlx::query("SELECT id FROM tools WHERE id = ?")
-                .bind(tool_id)
-                .fetch_optional(&self.pool)
-                .await?
-                .is_some();
-            
-            if !tool_exists {
-                println!("DEBUG: Tool {} does not exist, skipping", tool_id);
-                continue; // Skip non-existent tools
+        // Freeze each tool as it is now. Later edits never reach this event
+        // unless a new version is set explicitly.
+        let mut snapshots: Vec<packages::PackageSnapshot> = Vec::new();
+        for tool_id in tool_ids {
+            if snapshots.iter().any(|s| s.structure.tool.id == tool_id) {
+                continue;
+            }
+            match self.current_tool_snapshot(tool_id).await {
+                Ok(snapshot) => snapshots.push(snapshot),
+                // Skip tools that don't exist
+                Err(Error::RowNotFound) => continue,
+                Err(e) => return Err(e),
             }
+        }
 
-            println!("DEBUG: Tool {} exists. Checking package...", tool_id);
-
-            // Check if package already exists for this tool
-            let existing_package = sqlx::query("SELECT id FROM packages WHERE tool_id = ? AND version = '1.0'")
-                .bind(tool_id)
-                .fetch_optional(&self.pool)
-                .await?;
-
-            let package_id = if let Some(row) = existing_package {
-                let id: i64 = sqlx::Row::get(&row, "id");
-                println!("DEBUG: Using existing package {} for tool {}", id, tool_id);
-                id
-            } else {
-                println!("DEBUG: Creating NEW package for tool {}", tool_id);
-                // Create new package
-                let id = sqlx::query(
-                    r#"
-                    INSERT INTO packages (tool_id, package_name, version, content_data)
-                    VALUES (?, ?, '1.0', '{}')
-                    "#
-                )
-                .bind(tool_id)
-                .bind(format!("Package for tool {}", tool_id))
-                .execute(&self.pool)
-                .await?
-                .last_insert_rowid();
-                println!("DEBUG: Created new package {} for tool {}", id, tool_id);
-                id
-            };
-
-            // Link package to event
-            println!("DEBUG: Linking package {} to event {}", package_id, event_id);
-            // Use INSERT OR IGNORE to prevent duplicate linking errors if retried
+        // Publish and link in one go, appending after the tools the event already has
+        let mut tx = self.pool.begin().await?;
+        let mut next_order: i64 = sqlx::query(
+            "SELECT COALESCE(MAX(sequence_order), -1) + 1 as next_order FROM event_packages WHERE event_id = ?"
+        )
+        .bind(event_id)
+        .fetch_one(&mut *tx)
+        .await?
+        .get("next_order");
+
+        for snapshot in snapshots {
+            // Skip tools that are already part of the event
+            let attached = sqlx::query(
+                r#"
+                SELECT 1 FROM event_packages ep JOIN packages p ON p.id = ep.package_id
+                WHERE ep.event_id = ? AND p.tool_id = ?
+                "#
+            )
+            .bind(event_id)
+            .bind(snapshot.structure.tool.id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .is_some();
+            if attached {
+                continue;
+            }
+
+            let package_id = packages::insert_tool_package(&mut tx, snapshot).await?;
             sqlx::query(
                 r#"
-                INSERT OR IGNORE INTO event_packages (event_id, package_id, sequence_order)
+                INSERT INTO event_packages (event_id, package_id, sequence_order)
                 VALUES (?, ?, ?)
                 "#
             )
             .bind(event_id)
             .bind(package_id)
-            .bind(index as i64)
-            .execute(&self.pool)
+            .bind(next_order)
+            .execute(&mut *tx)
             .await?;
-            println!("DEBUG: Linked package {} to event {}", package_id, event_id);
+            next_order += 1;
         }
-        println!("DEBUG: add_tools_to_event DONE");
+        tx.commit().await?;
         Ok(())
     }
 
@@ -313,7 +400,7 @@ impl Database {
             JOIN packages p ON p.tool_id = t.id
             JOIN event_packages ep ON ep.package_id = p.id
             WHERE ep.event_id = ?
-            ORDER BY t.id
+            ORDER BY ep.sequence_order, t.id
             "#
         )
         .bind(event_id)
@@ -347,8 +434,9 @@ impl Database {
 
     // --- Reports / Results ---
     pub async fn get_all_test_results(&self) -> Result<Vec<TestResultDTO>, Error> {
-        let sql = r#"
-            SELECT 
+        let sql = format!(r#"
+            WITH {}
+            SELECT
                 r.id as id,
                 CAST(COALESCE(s.user_id, 0) AS INTEGER) as candidate_id,
                 COALESCE(u.username, s.participant_id) as candidate_name,
@@ -359,6 +447,7 @@ impl Database {
                 CAST(COALESCE(json_extract(r.scores, '$.total_score'), 0) AS INTEGER) as score,
                 CAST(COALESCE(json_extract(r.scores, '$.raw_score'), 0) AS INTEGER) as raw_score,
                 CAST(json_extract(r.scores, '$.percentile') AS INTEGER) as percentile,
+                CAST(COALESCE(json_extract(r.scores, '$.administration.standard'), 1) AS INTEGER) as standard_administration,
                 COALESCE(
                     json_extract(r.interpretations, '$.ai_review'),
                     json_extract(r.interpretations, '$.response')
@@ -366,22 +455,40 @@ impl Database {
                 s.status as status,
                 CAST(s.id AS TEXT) as session_id,
                 json_extract(s.metadata, '$.recordingId') as recording_id,
-                r.generated_at as completed_at
+                r.generated_at as completed_at,
+                pp.full_name as full_name,
+                pp.nik as nik,
+                pp.date_of_birth as date_of_birth,
+                pp.gender as gender,
+                pp.education as education,
+                pp.position_applied as position_applied,
+                CAST(COALESCE(
+                    json_extract(r.scores, '$.participant.age'),
+                    CAST(strftime('%Y', r.generated_at) AS INTEGER) - CAST(strftime('%Y', pp.date_of_birth) AS INTEGER)
+                        - (strftime('%m-%d', r.generated_at) < strftime('%m-%d', pp.date_of_birth))
+                ) AS INTEGER) as age,
+                s.attempt_number as attempt_number,
+                COALESCE(rs.selected, 1) as selected,
+                ep.status as participant_status
             FROM reports r
             JOIN sessions s ON r.session_id = s.id
             JOIN events e ON s.event_id = e.id
             LEFT JOIN users u ON s.user_id = u.id
+            LEFT JOIN participant_profiles pp ON pp.user_id = s.user_id
+            LEFT JOIN report_selection rs ON rs.report_id = r.id
+            LEFT JOIN event_participants ep ON ep.event_id = s.event_id AND ep.user_id = s.user_id
             ORDER BY r.generated_at DESC
-        "#;
+        "#, retakes::REPORT_SELECTION_CTE);
 
-        sqlx::query_as::<_, TestResultDTO>(sql)
+        sqlx::query_as::<_, TestResultDTO>(&sql)
             .fetch_all(&self.pool)
             .await
     }
 
     pub async fn get_test_result_by_id(&self, report_id: i64) -> Result<TestResultDTO, Error> {
-        let sql = r#"
-            SELECT 
+        let sql = format!(r#"
+            WITH {}
+            SELECT
                 r.id as id,
                 CAST(COALESCE(s.user_id, 0) AS INTEGER) as candidate_id,
                 COALESCE(u.username, s.participant_id) as candidate_name,
@@ -392,6 +499,7 @@ impl Database {
                 CAST(COALESCE(json_extract(r.scores, '$.total_score'), 0) AS INTEGER) as score,
                 CAST(COALESCE(json_extract(r.scores, '$.raw_score'), 0) AS INTEGER) as raw_score,
                 CAST(json_extract(r.scores, '$.percentile') AS INTEGER) as percentile,
+                CAST(COALESCE(json_extract(r.scores, '$.administration.standard'), 1) AS INTEGER) as standard_administration,
                 COALESCE(
                     json_extract(r.interpretations, '$.ai_review'),
                     json_extract(r.interpretations, '$.response')
@@ -399,15 +507,32 @@ impl Database {
                 s.status as status,
                 CAST(s.id AS TEXT) as session_id,
                 json_extract(s.metadata, '$.recordingId') as recording_id,
-                r.generated_at as completed_at
+                r.generated_at as completed_at,
+                pp.full_name as full_name,
+                pp.nik as nik,
+                pp.date_of_birth as date_of_birth,
+                pp.gender as gender,
+                pp.education as education,
+                pp.position_applied as position_applied,
+                CAST(COALESCE(
+                    json_extract(r.scores, '$.participant.age'),
+                    CAST(strftime('%Y', r.generated_at) AS INTEGER) - CAST(strftime('%Y', pp.date_of_birth) AS INTEGER)
+                        - (strftime('%m-%d', r.generated_at) < strftime('%m-%d', pp.date_of_birth))
+                ) AS INTEGER) as age,
+                s.attempt_number as attempt_number,
+                COALESCE(rs.selected, 1) as selected,
+                ep.status as participant_status
             FROM reports r
             JOIN sessions s ON r.session_id = s.id
             JOIN events e ON s.event_id = e.id
             LEFT JOIN users u ON s.user_id = u.id
+            LEFT JOIN participant_profiles pp ON pp.user_id = s.user_id
+            LEFT JOIN report_selection rs ON rs.report_id = r.id
+            LEFT JOIN event_participants ep ON ep.event_id = s.event_id AND ep.user_id = s.user_id
             WHERE r.id = ?
-        "#;
+        "#, retakes::REPORT_SELECTION_CTE);
 
-        sqlx::query_as::<_, TestResultDTO>(sql)
+        sqlx::query_as::<_, TestResultDTO>(&sql)
             .bind(report_id)
             .fetch_one(&self.pool)
             .await
@@ -576,8 +701,10 @@ impl Database {
                 })
                 .collect();
             
-            // Check if code already exists
-            let exists = sqlx::query("SELECT COUNT(*) as count FROM events WHERE event_code = ?")
+            // Check if code already exists, or did once; retired codes must keep pointing nowhere
+            let exists = sqlx::query(
+                "SELECT (SELECT COUNT(*) FROM events WHERE event_code = ?1) + (SELECT COUNT(*) FROM event_code_history WHERE event_code = ?1) as count"
+            )
                 .bind(&code)
                 .fetch_one(&self.pool)
                 .await?
@@ -589,14 +716,6 @@ impl Database {
         }
     }
     
-    /// Get event by code
-    pub async fn get_event_by_code(&self, code: &str) -> Result<Event, Error> {
-        sqlx::query_as::<_, Event>("SELECT * FROM events WHERE event_code = ?")
-            .bind(code)
-            .fetch_one(&self.pool)
-            .await
-    }
-    
     /// Update event code
     pub async fn update_event_code(&self, event_id: i64, code: &str) -> Result<(), Error> {
         sqlx::query("UPDATE events SET event_code = ? WHERE id = ?")
@@ -636,31 +755,41 @@ impl Database {
         event_id: i64,
         user_id: i64
     ) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query("DELETE FROM event_participants WHERE event_id = ? AND user_id = ?")
             .bind(event_id)
             .bind(user_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+        // The freed place goes to the next candidate on the waitlist
+        enrollment::promote_from_waitlist(&mut tx, event_id).await?;
+        tx.commit().await?;
         Ok(())
     }
     
     /// Get event participants
     pub async fn get_event_participants(&self, event_id: i64) -> Result<Vec<ParticipantInfo>, Error> {
-        sqlx::query_as::<_, ParticipantInfo>(
+        sqlx::query_as::<_, ParticipantInfo>(&format!(
             r#"
+            WITH {}
             SELECT 
                 u.id as user_id,
                 u.username,
                 u.email,
                 ep.status,
                 ep.enrolled_at,
-                ep.completed_at
+                ep.completed_at,
+                ep.notes,
+                COALESCE(tp.tools_completed, 0) as tools_completed,
+                (SELECT COUNT(*) FROM event_tools) as tools_total
             FROM event_participants ep
             JOIN users u ON ep.user_id = u.id
-            WHERE ep.event_id = ?
+            LEFT JOIN tool_progress tp ON tp.user_id = ep.user_id
+            WHERE ep.event_id = ?1
             ORDER BY ep.enrolled_at DESC
-            "#
-        )
+            "#,
+            progress::PROGRESS_CTE
+        ))
         .bind(event_id)
         .fetch_all(&self.pool)
         .await
@@ -677,6 +806,8 @@ impl Database {
                 e.status,
                 e.event_code,
                 e.enrollment_deadline,
+                e.starts_at,
+                e.ends_at,
                 e.created_at,
                 ep.status as participant_status
             FROM events e
@@ -721,8 +852,14 @@ impl Database {
                 e.status,
                 e.max_participants,
                 e.enrollment_deadline,
+                e.starts_at,
+                e.ends_at,
+                e.timezone,
                 e.created_at,
-                COUNT(ep.id) as participant_count
+                COUNT(ep.id) as participant_count,
+                (SELECT COUNT(*) FROM event_waitlist w WHERE w.event_id = e.id) as waitlist_count,
+                COALESCE(SUM(ep.status = 'withdrawn'), 0) as withdrawn_count,
+                COALESCE(SUM(ep.status = 'no_show'), 0) as no_show_count
             FROM events e
             LEFT JOIN event_participants ep ON e.id = ep.event_id
             WHERE e.id = ?
@@ -741,8 +878,14 @@ impl Database {
             status: row.get("status"),
             max_participants: row.get("max_participants"),
             enrollment_deadline: row.get("enrollment_deadline"),
+            starts_at: row.get("starts_at"),
+            ends_at: row.get("ends_at"),
+            timezone: row.get("timezone"),
             created_at: row.get::<chrono::NaiveDateTime, _>("created_at").to_string(),
             participant_count: row.get::<i64, _>("participant_count") as i32,
+            waitlist_count: row.get::<i64, _>("waitlist_count") as i32,
+            withdrawn_count: row.get::<i64, _>("withdrawn_count") as i32,
+            no_show_count: row.get::<i64, _>("no_show_count") as i32,
         })
     }
 
@@ -785,6 +928,9 @@ impl Database {
         .execute(&self.pool)
         .await?
             .last_insert_rowid();
+        self.record_report_administration(session_id).await?;
+        self.record_report_participant(session_id).await?;
+        self.refresh_session_progress(session_id).await?;
         Ok(id)
     }
 
@@ -806,37 +952,4 @@ impl Database {
         query.execute(&self.pool).await?;
         Ok(())
     }
-
-    pub async fn reset_participant_session(&self, event_id: i64, user_id: i64) -> Result<(), Error> {
-        // 1. Find the session ID for this user/event combo
-        let session_query = sqlx::query(
-            "SELECT id FROM sessions WHERE event_id = ? AND user_id = ?" // Assuming user_id is linked
-        )
-        .bind(event_id)
-        .bind(user_id)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        // 2. Delete session (Cascade should handle reports, recordings etc if configured, else manual)
-        // Check cascade support in SQLite schema. If not standard, we might need manual delete.
-        // Assuming cascade for now based on previous 'delete_event' comment.
-        if let Some(row) = session_query {
-             let session_id: i64 = row.get("id");
-             sqlx::query("DELETE FROM sessions WHERE id = ?")
-                .bind(session_id)
-                .execute(&self.pool)
-                .await?;
-        }
-
-        // 3. Reset participant status to 'enrolled', clear completed_at
-        sqlx::query(
-            "UPDATE event_participants SET status = 'enrolled', completed_at = NULL WHERE event_id = ? AND user_id = ?"
-        )
-        .bind(event_id)
-        .bind(user_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
-    }
 }
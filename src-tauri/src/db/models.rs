@@ -14,6 +14,13 @@ pub struct Tool {
     pub config: Value, // JSON
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Language the base content is written in; snapshots from before localization default to Indonesian
+    #[serde(default = "default_content_locale")]
+    pub content_locale: String,
+}
+
+fn default_content_locale() -> String {
+    crate::localization::DEFAULT_LOCALE.to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -38,6 +45,36 @@ pub struct Question {
     pub sequence_order: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FullToolStructure {
+    pub tool: Tool,
+    pub subtests: Vec<FullSubtest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FullSubtest {
+    pub subtest: ToolSubtest,
+    pub questions: Vec<Question>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AnswerKey {
+    pub id: i64,
+    pub tool_id: i64,
+    pub question_id: i64,
+    pub correct_answer: String,
+    pub scoring_rule: Option<Value>, // JSON
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PackageInfo {
+    pub id: i64,
+    pub tool_id: i64,
+    pub package_name: String,
+    pub version: String,
+    pub created_at: NaiveDateTime,
+}
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Event {
     pub id: i64,
@@ -47,6 +84,19 @@ pub struct Event {
     pub event_code: Option<String>,
     pub max_participants: Option<i64>,
     pub enrollment_deadline: Option<String>,
+    /// Testing window in UTC, see `event_lifecycle`
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub timezone: Option<String>,
+    /// Code validity and revocation, see `event_codes`
+    #[sqlx(default)]
+    pub code_valid_from: Option<String>,
+    #[sqlx(default)]
+    pub code_expires_at: Option<String>,
+    #[sqlx(default)]
+    pub code_use_count: i64,
+    #[sqlx(default)]
+    pub code_revoked_at: Option<String>,
     pub created_at: NaiveDateTime,
     #[sqlx(default)]
     pub participant_count: i64,
@@ -73,6 +123,15 @@ pub struct Session {
     pub started_at: Option<NaiveDateTime>,
     pub completed_at: Option<NaiveDateTime>,
     pub metadata: Option<Value>, // JSON
+    pub paused_at: Option<NaiveDateTime>,
+    pub paused_seconds: i64,
+    pub status_reason: Option<String>,
+    pub status_changed_at: Option<NaiveDateTime>,
+    #[sqlx(default)]
+    pub attempt_number: i64,
+    /// Set once a retake replaced this session's attempt
+    #[sqlx(default)]
+    pub superseded_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -118,11 +177,30 @@ pub struct TestResultDTO {
     pub score: i64,       // Derived from report JSON or just raw score for now
     pub raw_score: i64,
     pub percentile: Option<i64>,
+    /// False when accommodations were in effect; norm comparisons should say so
+    pub standard_administration: bool,
     pub interpretation: Option<String>,
     pub status: String,   // from session status or logic
     pub completed_at: Option<NaiveDateTime>,
     pub session_id: String,
     pub recording_id: Option<String>,
+    // Participant profile, when one has been filled in
+    pub full_name: Option<String>,
+    pub nik: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub gender: Option<String>,
+    pub education: Option<String>,
+    pub position_applied: Option<String>,
+    /// Age when the report was generated
+    pub age: Option<i64>,
+    #[sqlx(default)]
+    pub attempt_number: i64,
+    /// Whether this is the attempt the event's retake policy counts
+    #[sqlx(default)]
+    pub selected: bool,
+    /// Enrollment status, so exports show withdrawals and no-shows
+    #[sqlx(default)]
+    pub participant_status: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -182,8 +260,14 @@ pub struct EventDetails {
     pub status: String,
     pub max_participants: Option<i32>,
     pub enrollment_deadline: Option<String>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub timezone: Option<String>,
     pub created_at: String,
     pub participant_count: i32,
+    pub waitlist_count: i32,
+    pub withdrawn_count: i32,
+    pub no_show_count: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -194,6 +278,11 @@ pub struct ParticipantInfo {
     pub status: String,
     pub enrolled_at: String,
     pub completed_at: Option<String>,
+    /// Enrollment notes, including withdrawal and no-show reasons
+    pub notes: Option<String>,
+    /// "3 of 5 tools done"
+    pub tools_completed: i64,
+    pub tools_total: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -204,6 +293,8 @@ pub struct CandidateEvent {
     pub status: String, // Event status
     pub event_code: Option<String>,
     pub enrollment_deadline: Option<String>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
     pub created_at: NaiveDateTime,
     pub participant_status: String, // User's status (enrolled, completed, etc)
 }
@@ -0,0 +1,228 @@
+// Package Snapshots
+// Frozen, versioned copies of a tool's content that events deliver and score from
+
+use sqlx::{Error, Row, SqliteConnection};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use super::localization::ToolTranslations;
+use super::models::{AnswerKey, FullSubtest, FullToolStructure, PackageInfo, Question};
+use crate::localization::{self, LocalizedToolContent};
+
+/// Bump when the layout of `PackageSnapshot` changes
+pub const PACKAGE_SCHEMA_VERSION: i64 = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackageSnapshot {
+    pub schema_version: i64,
+    pub version: i64,
+    pub structure: FullToolStructure,
+    pub answer_keys: Vec<AnswerKey>,
+    /// Added in schema version 2; older snapshots have no translations
+    #[serde(default)]
+    pub translations: ToolTranslations,
+}
+
+impl PackageSnapshot {
+    /// Strip everything a candidate must not see before delivery
+    pub fn into_candidate_structure(self) -> FullToolStructure {
+        let mut structure = self.structure;
+        for subtest in structure.subtests.iter_mut() {
+            for question in subtest.questions.iter_mut() {
+                if let Some(serde_json::Value::Object(options)) = question.options.as_mut() {
+                    options.remove("correct");
+                }
+            }
+        }
+        structure
+    }
+
+    /// Candidate content in the first available locale of `chain`
+    pub fn into_localized_candidate_content(mut self, chain: &[String]) -> LocalizedToolContent {
+        let translations = std::mem::take(&mut self.translations);
+        localization::localize_structure(self.into_candidate_structure(), &translations, chain)
+    }
+}
+
+impl Database {
+    pub async fn get_full_tool_structure(&self, tool_id: i64) -> Result<FullToolStructure, Error> {
+        let tool = self.get_tool_by_id(tool_id).await?;
+        let subtests = self.get_subtests_by_tool(tool_id).await?;
+
+        let mut full_subtests = Vec::new();
+
+        for subtest in subtests {
+            let questions = self.get_questions_by_subtest(subtest.id).await?;
+            full_subtests.push(FullSubtest {
+                subtest,
+                questions,
+            });
+        }
+
+        Ok(FullToolStructure {
+            tool,
+            subtests: full_subtests,
+        })
+    }
+
+    pub async fn get_answer_keys_by_tool(&self, tool_id: i64) -> Result<Vec<AnswerKey>, Error> {
+        sqlx::query_as::<_, AnswerKey>("SELECT * FROM answer_keys WHERE tool_id = ? ORDER BY question_id")
+            .bind(tool_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Freeze the current content of a tool as a new package version
+    pub async fn publish_tool_package(&self, tool_id: i64) -> Result<PackageInfo, Error> {
+        let snapshot = self.current_tool_snapshot(tool_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let id = insert_tool_package(&mut tx, snapshot).await?;
+        tx.commit().await?;
+
+        self.get_package_info(id).await
+    }
+
+    /// The tool's content as it is now, not yet numbered or stored
+    pub(crate) async fn current_tool_snapshot(&self, tool_id: i64) -> Result<PackageSnapshot, Error> {
+        Ok(PackageSnapshot {
+            schema_version: PACKAGE_SCHEMA_VERSION,
+            version: 0,
+            structure: self.get_full_tool_structure(tool_id).await?,
+            answer_keys: self.get_answer_keys_by_tool(tool_id).await?,
+            translations: self.get_tool_translations(tool_id).await?,
+        })
+    }
+
+    pub async fn get_package_info(&self, package_id: i64) -> Result<PackageInfo, Error> {
+        sqlx::query_as::<_, PackageInfo>(
+            "SELECT id, tool_id, package_name, version, created_at FROM packages WHERE id = ?"
+        )
+        .bind(package_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Published versions of a tool, newest first. Legacy empty packages are left out.
+    pub async fn get_tool_packages(&self, tool_id: i64) -> Result<Vec<PackageInfo>, Error> {
+        sqlx::query_as::<_, PackageInfo>(
+            r#"
+            SELECT id, tool_id, package_name, version, created_at
+            FROM packages
+            WHERE tool_id = ? AND json_extract(content_data, '$.schema_version') IS NOT NULL
+            ORDER BY id DESC
+            "#
+        )
+        .bind(tool_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Snapshot a tool is delivered from within an event.
+    ///
+    /// Events created before snapshots existed point at an empty package;
+    /// those fall back to the live tool content.
+    pub async fn get_event_tool_snapshot(&self, event_id: i64, tool_id: i64) -> Result<PackageSnapshot, Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT p.content_data
+            FROM packages p
+            JOIN event_packages ep ON ep.package_id = p.id
+            WHERE ep.event_id = ? AND p.tool_id = ?
+            "#
+        )
+        .bind(event_id)
+        .bind(tool_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let content: serde_json::Value = row.get("content_data");
+        if content.get("schema_version").is_some() {
+            return serde_json::from_value(content)
+                .map_err(|e| Error::Protocol(format!("Corrupt package content: {}", e)));
+        }
+
+        self.current_tool_snapshot(tool_id).await
+    }
+
+    /// A question as the event delivers it, looked up across all of the event's packages.
+    /// `None` when the question is not part of the event.
+    pub async fn get_event_question(&self, event_id: i64, question_id: i64) -> Result<Option<Question>, Error> {
+        let tool_ids = sqlx::query_as::<_, (i64,)>(
+            "SELECT DISTINCT p.tool_id FROM event_packages ep JOIN packages p ON p.id = ep.package_id WHERE ep.event_id = ?"
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (tool_id,) in tool_ids {
+            let snapshot = self.get_event_tool_snapshot(event_id, tool_id).await?;
+            let question = snapshot.structure.subtests.into_iter()
+                .flat_map(|s| s.questions)
+                .find(|q| q.id == question_id);
+            if question.is_some() {
+                return Ok(question);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Point an event at a different version of one of its tools
+    pub async fn set_event_tool_package(&self, event_id: i64, tool_id: i64, package_id: i64) -> Result<(), Error> {
+        sqlx::query(
+            r#"
+            UPDATE event_packages
+            SET package_id = ?
+            WHERE event_id = ? AND package_id IN (SELECT id FROM packages WHERE tool_id = ?)
+            "#
+        )
+        .bind(package_id)
+        .bind(event_id)
+        .bind(tool_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn count_event_sessions(&self, event_id: i64) -> Result<i64, Error> {
+        let count = sqlx::query("SELECT COUNT(*) as count FROM sessions WHERE event_id = ?")
+            .bind(event_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get::<i64, _>("count");
+        Ok(count)
+    }
+}
+
+/// Store a snapshot as the tool's next package version and return the package id.
+/// Numbered and inserted on the same connection, so two publishes can't take the same version
+/// as long as the caller holds a transaction.
+pub(crate) async fn insert_tool_package(conn: &mut SqliteConnection, mut snapshot: PackageSnapshot) -> Result<i64, Error> {
+    let tool_id = snapshot.structure.tool.id;
+    let version: i64 = sqlx::query(
+        "SELECT COALESCE(MAX(CAST(version AS INTEGER)), 0) + 1 as next_version FROM packages WHERE tool_id = ?"
+    )
+    .bind(tool_id)
+    .fetch_one(&mut *conn)
+    .await?
+    .get("next_version");
+
+    snapshot.version = version;
+    let package_name = format!("{} v{}", snapshot.structure.tool.name, version);
+    let content = serde_json::to_value(&snapshot)
+        .map_err(|e| Error::Protocol(format!("Failed to serialize package: {}", e)))?;
+
+    Ok(sqlx::query(
+        r#"
+        INSERT INTO packages (tool_id, package_name, version, content_data)
+        VALUES (?, ?, ?, ?)
+        "#
+    )
+    .bind(tool_id)
+    .bind(&package_name)
+    .bind(version.to_string())
+    .bind(content)
+    .execute(&mut *conn)
+    .await?
+    .last_insert_rowid())
+}
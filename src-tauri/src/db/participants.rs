@@ -0,0 +1,275 @@
+// Participant Profiles
+// Identity details of candidates, and creating a batch of enrolled candidates from an import
+
+use chrono::{Datelike, NaiveDate};
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use crate::candidate_import::{self, username_base, NewCandidate};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ParticipantProfile {
+    #[serde(default)]
+    pub user_id: i64,
+    pub full_name: String,
+    /// National ID (NIK)
+    pub nik: Option<String>,
+    /// YYYY-MM-DD
+    pub date_of_birth: Option<String>,
+    /// male or female
+    pub gender: Option<String>,
+    /// One of `candidate_import::EDUCATION_LEVELS`
+    pub education: Option<String>,
+    pub position_applied: Option<String>,
+}
+
+impl ParticipantProfile {
+    /// Validate, and bring each field into its stored form (NIK digits only, ISO date, canonical codes)
+    pub fn normalize(&mut self) -> Result<(), String> {
+        self.full_name = self.full_name.trim().to_string();
+        if self.full_name.is_empty() {
+            return Err("Full name is required".to_string());
+        }
+
+        let blank_to_none = |value: &mut Option<String>| {
+            if value.as_deref().map(|v| v.trim().is_empty()).unwrap_or(false) {
+                *value = None;
+            }
+        };
+        for field in [&mut self.nik, &mut self.date_of_birth, &mut self.gender, &mut self.education, &mut self.position_applied] {
+            blank_to_none(field);
+        }
+
+        if let Some(nik) = &mut self.nik {
+            *nik = nik.replace([' ', '.'], "");
+            candidate_import::validate_nik(nik)?;
+        }
+        if let Some(date) = &mut self.date_of_birth {
+            *date = candidate_import::parse_birth_date(date)?.format("%Y-%m-%d").to_string();
+        }
+        if let Some(gender) = &mut self.gender {
+            *gender = candidate_import::normalize_gender(gender)?;
+        }
+        if let Some(education) = &mut self.education {
+            *education = candidate_import::normalize_education(education)?;
+        }
+        Ok(())
+    }
+
+    /// Whole years on `date`, for picking an age norm group
+    pub fn age_on(&self, date: NaiveDate) -> Option<i64> {
+        let born = NaiveDate::parse_from_str(self.date_of_birth.as_deref()?, "%Y-%m-%d").ok()?;
+        let mut age = (date.year() - born.year()) as i64;
+        if (date.month(), date.day()) < (born.month(), born.day()) {
+            age -= 1;
+        }
+        (age >= 0).then_some(age)
+    }
+}
+
+/// An imported candidate with their credential already hashed
+#[derive(Debug, Clone)]
+pub struct PreparedCandidate {
+    pub candidate: NewCandidate,
+    pub password_hash: Option<String>,
+    pub token_hash: Option<String>,
+}
+
+impl Database {
+    pub async fn nik_registered(&self, nik: &str) -> Result<bool, Error> {
+        let count: i64 = sqlx::query("SELECT COUNT(*) as count FROM participant_profiles WHERE nik = ?")
+            .bind(nik)
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+        Ok(count > 0)
+    }
+
+    pub async fn email_registered(&self, email: &str) -> Result<bool, Error> {
+        let count: i64 = sqlx::query("SELECT COUNT(*) as count FROM users WHERE LOWER(email) = LOWER(?)")
+            .bind(email)
+            .fetch_one(&self.pool)
+            .await?
+            .get("count");
+        Ok(count > 0)
+    }
+
+    pub async fn get_participant_profile(&self, user_id: i64) -> Result<Option<ParticipantProfile>, Error> {
+        sqlx::query_as::<_, ParticipantProfile>(
+            "SELECT user_id, full_name, nik, date_of_birth, gender, education, position_applied FROM participant_profiles WHERE user_id = ?"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Create or replace a profile; expects `ParticipantProfile::normalize` to have run
+    pub async fn save_participant_profile(&self, user_id: i64, profile: &ParticipantProfile) -> Result<ParticipantProfile, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO participant_profiles (user_id, full_name, nik, date_of_birth, gender, education, position_applied)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                full_name = excluded.full_name,
+                nik = excluded.nik,
+                date_of_birth = excluded.date_of_birth,
+                gender = excluded.gender,
+                education = excluded.education,
+                position_applied = excluded.position_applied,
+                updated_at = CURRENT_TIMESTAMP
+            "#
+        )
+        .bind(user_id)
+        .bind(&profile.full_name)
+        .bind(&profile.nik)
+        .bind(&profile.date_of_birth)
+        .bind(&profile.gender)
+        .bind(&profile.education)
+        .bind(&profile.position_applied)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_participant_profile(user_id).await?.ok_or(Error::RowNotFound)
+    }
+
+    pub async fn delete_participant_profile(&self, user_id: i64) -> Result<(), Error> {
+        let result = sqlx::query("DELETE FROM participant_profiles WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// Snapshot the demographics into the session's report, so norm lookups use the
+    /// candidate's age and details at the time of testing
+    pub async fn record_report_participant(&self, session_id: i64) -> Result<(), Error> {
+        let user_id: Option<i64> = sqlx::query("SELECT user_id FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get("user_id");
+
+        let profile = match user_id {
+            Some(user_id) => self.get_participant_profile(user_id).await?,
+            None => None,
+        };
+        let profile = match profile {
+            Some(profile) => profile,
+            None => return Ok(()),
+        };
+
+        let participant = serde_json::json!({
+            "age": profile.age_on(chrono::Local::now().date_naive()),
+            "gender": profile.gender,
+            "education": profile.education,
+            "position_applied": profile.position_applied,
+        });
+
+        sqlx::query("UPDATE reports SET scores = json_set(COALESCE(scores, '{}'), '$.participant', json(?)) WHERE session_id = ?")
+            .bind(participant.to_string())
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_participant_name(&self, user_id: i64, full_name: &str) -> Result<(), Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO participant_profiles (user_id, full_name) VALUES (?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET full_name = excluded.full_name, updated_at = CURRENT_TIMESTAMP
+            "#
+        )
+        .bind(user_id)
+        .bind(full_name)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Create, profile and enroll every candidate in one transaction, so a failure
+    /// leaves no half-imported batch behind. Returns (user id, username) per candidate.
+    pub async fn import_candidates(
+        &self,
+        event_id: i64,
+        candidates: &[PreparedCandidate],
+        token_expires_at: Option<&str>,
+    ) -> Result<Vec<(i64, String)>, Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut created = Vec::with_capacity(candidates.len());
+
+        for prepared in candidates {
+            let candidate = &prepared.candidate;
+
+            // First free name of base, base2, base3, ...
+            let base = username_base(&candidate.full_name);
+            let mut username = base.clone();
+            let mut suffix = 1;
+            loop {
+                let taken: i64 = sqlx::query("SELECT COUNT(*) as count FROM users WHERE username = ?")
+                    .bind(&username)
+                    .fetch_one(&mut *tx)
+                    .await?
+                    .get("count");
+                if taken == 0 {
+                    break;
+                }
+                suffix += 1;
+                username = format!("{}{}", base, suffix);
+            }
+
+            let user_id = sqlx::query(
+                "INSERT INTO users (username, email, password_hash, role, created_at) VALUES (?, ?, ?, 'participant', ?)"
+            )
+            .bind(&username)
+            .bind(&candidate.email)
+            .bind(&prepared.password_hash)
+            .bind(chrono::Local::now().naive_local())
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+
+            sqlx::query(
+                r#"
+                INSERT INTO participant_profiles (user_id, full_name, nik, date_of_birth, education, gender, position_applied)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(user_id)
+            .bind(&candidate.full_name)
+            .bind(&candidate.nik)
+            .bind(&candidate.date_of_birth)
+            .bind(&candidate.education)
+            .bind(&candidate.gender)
+            .bind(&candidate.position_applied)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("INSERT INTO event_participants (event_id, user_id) VALUES (?, ?)")
+                .bind(event_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+
+            if let (Some(token_hash), Some(expires_at)) = (&prepared.token_hash, token_expires_at) {
+                sqlx::query("INSERT INTO access_tokens (token_hash, user_id, event_id, expires_at) VALUES (?, ?, ?, ?)")
+                    .bind(token_hash)
+                    .bind(user_id)
+                    .bind(event_id)
+                    .bind(expires_at)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            created.push((user_id, username));
+        }
+
+        tx.commit().await?;
+        Ok(created)
+    }
+}
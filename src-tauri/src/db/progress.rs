@@ -0,0 +1,186 @@
+// Participant Progress
+// How many of an event's tools each participant has finished, and the enrollment
+// status that follows from it. Kept up to date from session, step and report changes.
+
+use sqlx::{Error, Row, SqliteConnection};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use super::withdrawal::has_left;
+
+/// Tools of event ?1 (`event_tools`) and the (user_id, tool_id) pairs finished in it
+/// (`finished_tools`). A tool counts as finished when its blueprint step was completed, or
+/// a session for it (tool id in the metadata, or its name for older clients) completed or
+/// got a report. Only the current attempt counts.
+pub(crate) const PROGRESS_CTE: &str = r#"
+    event_tools AS (
+        SELECT DISTINCT p.tool_id
+        FROM event_packages ep
+        JOIN packages p ON p.id = ep.package_id
+        WHERE ep.event_id = ?1
+    ),
+    finished_tools AS (
+        SELECT s.user_id, bs.tool_id
+        FROM session_step_progress ssp
+        JOIN event_blueprint_steps bs ON bs.id = ssp.step_id
+        JOIN sessions s ON s.id = ssp.session_id
+        WHERE s.event_id = ?1 AND s.user_id IS NOT NULL AND s.superseded_at IS NULL
+          AND bs.step_type = 'tool' AND ssp.status = 'completed'
+        UNION
+        SELECT s.user_id, CAST(COALESCE(
+            json_extract(s.metadata, '$.tool_id'),
+            json_extract(s.metadata, '$.toolId'),
+            (SELECT t.id FROM tools t WHERE t.name = json_extract(s.metadata, '$.testName'))
+        ) AS INTEGER)
+        FROM sessions s
+        WHERE s.event_id = ?1 AND s.user_id IS NOT NULL AND s.superseded_at IS NULL
+          AND (s.status = 'completed' OR EXISTS (SELECT 1 FROM reports r WHERE r.session_id = s.id))
+    ),
+    tool_progress AS (
+        SELECT user_id, COUNT(*) as tools_completed
+        FROM finished_tools
+        WHERE tool_id IN (SELECT tool_id FROM event_tools)
+        GROUP BY user_id
+    )
+"#;
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ToolProgress {
+    pub tool_id: i64,
+    pub tool_name: String,
+    pub completed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParticipantProgress {
+    pub event_id: i64,
+    pub user_id: i64,
+    pub status: String,
+    pub tools_completed: i64,
+    pub tools_total: i64,
+    /// In the event's tool order
+    pub tools: Vec<ToolProgress>,
+}
+
+/// (tools finished, tools in the event) for one participant
+async fn tool_counts(conn: &mut SqliteConnection, event_id: i64, user_id: i64) -> Result<(i64, i64), Error> {
+    let row = sqlx::query(&format!(
+        "WITH {} SELECT
+            (SELECT COUNT(*) FROM event_tools) as tools_total,
+            COALESCE((SELECT tools_completed FROM tool_progress WHERE user_id = ?2), 0) as tools_completed",
+        PROGRESS_CTE
+    ))
+    .bind(event_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok((row.get("tools_completed"), row.get("tools_total")))
+}
+
+/// Recompute a participant's status from their sessions and finished tools.
+///
+/// Completed once every tool is done; events without tools fall back to "all sessions
+/// closed and at least one completed". Withdrawn participants and no-shows are left alone.
+pub(crate) async fn refresh_participant_progress(conn: &mut SqliteConnection, event_id: i64, user_id: i64) -> Result<(), Error> {
+    let current: Option<String> = sqlx::query("SELECT status FROM event_participants WHERE event_id = ? AND user_id = ?")
+        .bind(event_id)
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.get("status"));
+    match current.as_deref() {
+        None => return Ok(()),
+        Some(status) if has_left(status) => return Ok(()),
+        _ => {}
+    }
+
+    let (done, total) = tool_counts(conn, event_id, user_id).await?;
+    let sessions = sqlx::query(
+        "SELECT COUNT(*) as started,
+                COALESCE(SUM(status IN ('pending', 'active', 'paused')), 0) as open,
+                COALESCE(SUM(status = 'completed'), 0) as completed
+         FROM sessions WHERE event_id = ? AND user_id = ? AND superseded_at IS NULL"
+    )
+    .bind(event_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    let (started, open, completed): (i64, i64, i64) = (sessions.get("started"), sessions.get("open"), sessions.get("completed"));
+
+    let finished = if total > 0 { done >= total } else { open == 0 && completed > 0 };
+    let status = if finished {
+        "completed"
+    } else if started > 0 || done > 0 {
+        "in_progress"
+    } else {
+        "enrolled"
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE event_participants
+        SET status = ?1,
+            completed_at = CASE WHEN ?1 = 'completed' THEN COALESCE(completed_at, CURRENT_TIMESTAMP) ELSE NULL END
+        WHERE event_id = ?2 AND user_id = ?3
+        "#
+    )
+    .bind(status)
+    .bind(event_id)
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+impl Database {
+    /// Refresh the progress of whoever a session belongs to; anonymous sessions are skipped
+    pub async fn refresh_session_progress(&self, session_id: i64) -> Result<(), Error> {
+        let mut conn = self.pool.acquire().await?;
+        let row = sqlx::query("SELECT event_id, user_id FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_one(&mut *conn)
+            .await?;
+        if let Some(user_id) = row.get::<Option<i64>, _>("user_id") {
+            refresh_participant_progress(&mut conn, row.get("event_id"), user_id).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_participant_progress(&self, event_id: i64, user_id: i64) -> Result<ParticipantProgress, Error> {
+        let status: String = sqlx::query("SELECT status FROM event_participants WHERE event_id = ? AND user_id = ?")
+            .bind(event_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?
+            .get("status");
+
+        let tools = sqlx::query_as::<_, ToolProgress>(&format!(
+            r#"
+            WITH {}
+            SELECT t.id as tool_id, t.name as tool_name,
+                   EXISTS (SELECT 1 FROM finished_tools f WHERE f.user_id = ?2 AND f.tool_id = t.id) as completed
+            FROM tools t
+            JOIN packages p ON p.tool_id = t.id
+            JOIN event_packages ep ON ep.package_id = p.id
+            WHERE ep.event_id = ?1
+            GROUP BY t.id
+            ORDER BY MIN(ep.sequence_order), t.id
+            "#,
+            PROGRESS_CTE
+        ))
+        .bind(event_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ParticipantProgress {
+            event_id,
+            user_id,
+            status,
+            tools_completed: tools.iter().filter(|t| t.completed).count() as i64,
+            tools_total: tools.len() as i64,
+            tools,
+        })
+    }
+}
@@ -0,0 +1,239 @@
+// Event Ranking
+// Stores an event's composite weights, cutoffs and tie-break rules, and gathers the
+// counted reports the shortlist is ranked from
+
+use sqlx::Row;
+use std::collections::HashMap;
+use std::fmt;
+
+use super::Database;
+use super::retakes::REPORT_SELECTION_CTE;
+use crate::ranking::{self, CandidateScores, RankedCandidate, RankingConfig, ScoreCutoff, TieBreak, ToolResult, ToolWeight};
+
+#[derive(Debug)]
+pub enum RankingError {
+    EventNotFound,
+    /// No weights have been set for the event
+    NotConfigured,
+    InvalidConfig(String),
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RankingError {
+    fn from(err: sqlx::Error) -> Self {
+        RankingError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for RankingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RankingError::EventNotFound => write!(f, "Event not found"),
+            RankingError::NotConfigured => write!(f, "This event has no composite weights yet"),
+            RankingError::InvalidConfig(msg) => write!(f, "Invalid ranking: {}", msg),
+            RankingError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl Database {
+    pub async fn get_ranking_config(&self, event_id: i64) -> Result<RankingConfig, RankingError> {
+        let tie_breaks: String = sqlx::query("SELECT ranking_tie_breaks FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RankingError::EventNotFound)?
+            .get("ranking_tie_breaks");
+
+        let weights = sqlx::query(
+            r#"
+            SELECT w.tool_id, t.name as tool_name, w.weight
+            FROM event_composite_weights w
+            JOIN tools t ON t.id = w.tool_id
+            WHERE w.event_id = ?
+            ORDER BY w.weight DESC, w.id
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let cutoffs = sqlx::query(
+            r#"
+            SELECT c.tool_id, t.name as tool_name, c.scale, c.min_score
+            FROM event_score_cutoffs c
+            JOIN tools t ON t.id = c.tool_id
+            WHERE c.event_id = ?
+            ORDER BY c.id
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(RankingConfig {
+            weights: weights.iter().map(|row| ToolWeight {
+                tool_id: row.get("tool_id"),
+                tool_name: row.get("tool_name"),
+                weight: row.get("weight"),
+            }).collect(),
+            cutoffs: cutoffs.iter().map(|row| ScoreCutoff {
+                tool_id: row.get("tool_id"),
+                tool_name: row.get("tool_name"),
+                scale: Some(row.get::<String, _>("scale")).filter(|s| !s.is_empty()),
+                min_score: row.get("min_score"),
+            }).collect(),
+            tie_breaks: serde_json::from_str(&tie_breaks).unwrap_or_else(|_| TieBreak::defaults()),
+        })
+    }
+
+    /// Replace the event's weights, cutoffs and tie-breaks. Only the event's own tools can be used.
+    pub async fn set_ranking_config(&self, event_id: i64, config: &RankingConfig) -> Result<RankingConfig, RankingError> {
+        ranking::validate_config(config).map_err(RankingError::InvalidConfig)?;
+
+        let mut tx = self.pool.begin().await?;
+        let exists: i64 = sqlx::query("SELECT COUNT(*) as count FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .get("count");
+        if exists == 0 {
+            return Err(RankingError::EventNotFound);
+        }
+
+        let event_tools: Vec<i64> = sqlx::query(
+            "SELECT DISTINCT p.tool_id FROM event_packages ep JOIN packages p ON p.id = ep.package_id WHERE ep.event_id = ?"
+        )
+        .bind(event_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(|row| row.get("tool_id"))
+        .collect();
+        let used = config.weights.iter().map(|w| w.tool_id).chain(config.cutoffs.iter().map(|c| c.tool_id));
+        for tool_id in used {
+            if !event_tools.contains(&tool_id) {
+                return Err(RankingError::InvalidConfig(format!("tool {} is not part of this event", tool_id)));
+            }
+        }
+
+        sqlx::query("DELETE FROM event_composite_weights WHERE event_id = ?")
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM event_score_cutoffs WHERE event_id = ?")
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for w in &config.weights {
+            sqlx::query("INSERT INTO event_composite_weights (event_id, tool_id, weight) VALUES (?, ?, ?)")
+                .bind(event_id)
+                .bind(w.tool_id)
+                .bind(w.weight)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for c in &config.cutoffs {
+            sqlx::query("INSERT INTO event_score_cutoffs (event_id, tool_id, scale, min_score) VALUES (?, ?, ?, ?)")
+                .bind(event_id)
+                .bind(c.tool_id)
+                .bind(c.scale.as_deref().map(str::trim).unwrap_or(""))
+                .bind(c.min_score)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let tie_breaks = serde_json::to_string(&config.tie_breaks).map_err(|e| RankingError::InvalidConfig(e.to_string()))?;
+        sqlx::query("UPDATE events SET ranking_tie_breaks = ? WHERE id = ?")
+            .bind(tie_breaks)
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        self.get_ranking_config(event_id).await
+    }
+
+    /// The event's participants ranked on their composite, using the reports the retake
+    /// policy counts. Withdrawn participants and no-shows are left out.
+    pub async fn get_event_ranking(&self, event_id: i64) -> Result<Vec<RankedCandidate>, RankingError> {
+        let config = self.get_ranking_config(event_id).await?;
+        if config.weights.is_empty() {
+            return Err(RankingError::NotConfigured);
+        }
+
+        let participants = sqlx::query(
+            r#"
+            SELECT ep.user_id, u.username, pp.full_name,
+                   (SELECT COUNT(*) FROM surveillance_logs sl
+                    JOIN sessions s ON s.id = sl.session_id
+                    WHERE s.event_id = ep.event_id AND s.user_id = ep.user_id AND sl.severity != 'info') as violations
+            FROM event_participants ep
+            JOIN users u ON u.id = ep.user_id
+            LEFT JOIN participant_profiles pp ON pp.user_id = ep.user_id
+            WHERE ep.event_id = ? AND ep.status NOT IN ('withdrawn', 'no_show')
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Tool ids come from the session metadata, or the tool's name for older clients
+        let reports = sqlx::query(&format!(
+            r#"
+            WITH {}
+            SELECT s.user_id,
+                   CAST(COALESCE(
+                       json_extract(s.metadata, '$.tool_id'),
+                       json_extract(s.metadata, '$.toolId'),
+                       (SELECT t.id FROM tools t WHERE t.name = json_extract(s.metadata, '$.testName'))
+                   ) AS INTEGER) as tool_id,
+                   CAST(json_extract(r.scores, '$.total_score') AS REAL) as score,
+                   r.scores,
+                   r.generated_at as completed_at
+            FROM reports r
+            JOIN sessions s ON s.id = r.session_id
+            JOIN report_selection rs ON rs.report_id = r.id
+            WHERE s.event_id = ?1 AND s.user_id IS NOT NULL AND rs.selected
+            ORDER BY r.generated_at DESC
+            "#,
+            REPORT_SELECTION_CTE
+        ))
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut results: HashMap<i64, Vec<ToolResult>> = HashMap::new();
+        for row in &reports {
+            let tool_id: i64 = match row.get::<Option<i64>, _>("tool_id") {
+                Some(id) => id,
+                None => continue,
+            };
+            let tool_results = results.entry(row.get("user_id")).or_default();
+            // Newest first, so the first report per tool wins
+            if tool_results.iter().any(|r| r.tool_id == tool_id) {
+                continue;
+            }
+            tool_results.push(ToolResult {
+                tool_id,
+                score: row.get("score"),
+                scores: row.get::<Option<serde_json::Value>, _>("scores").unwrap_or_default(),
+                completed_at: row.get::<Option<chrono::NaiveDateTime>, _>("completed_at").map(|t| t.to_string()),
+            });
+        }
+
+        let candidates = participants.iter().map(|row| {
+            let user_id: i64 = row.get("user_id");
+            CandidateScores {
+                user_id,
+                username: row.get("username"),
+                full_name: row.get("full_name"),
+                results: results.remove(&user_id).unwrap_or_default(),
+                violations: row.get("violations"),
+            }
+        }).collect();
+
+        Ok(ranking::rank_candidates(&config, candidates))
+    }
+}
@@ -0,0 +1,419 @@
+// Manual Scoring
+// Rater queue for text and drawing answers, rubric scores, and merging them into session reports
+
+use sqlx::{Error, Row};
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use super::Database;
+use super::packages::PackageSnapshot;
+use crate::question_types::QuestionType;
+use crate::rating::{self, ManualScoreSummary, Rubric};
+use crate::scoring::SubmittedAnswer;
+
+#[derive(Debug)]
+pub enum RatingError {
+    /// A text or drawing question whose rubric is missing or doesn't validate
+    InvalidRubric { question_id: i64, reason: String },
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RatingError {
+    fn from(err: sqlx::Error) -> Self {
+        RatingError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for RatingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RatingError::InvalidRubric { question_id, reason } => write!(f, "Question {} can't be rated: {}", question_id, reason),
+            RatingError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RatingItem {
+    pub id: i64,
+    pub event_id: i64,
+    pub session_id: i64,
+    pub tool_id: i64,
+    pub question_id: i64,
+    pub question_type: String,
+    pub answer: Option<String>,
+    pub rubric: serde_json::Value,
+    pub status: String,
+    pub assigned_to: Option<i64>,
+    pub assigned_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    /// 2 when the item is double-scored for reliability
+    pub required_ratings: i64,
+    pub second_rater: Option<i64>,
+    pub needs_adjudication: bool,
+    pub adjudicated_by: Option<i64>,
+}
+
+impl RatingItem {
+    pub fn rubric(&self) -> Result<Rubric, String> {
+        serde_json::from_value(self.rubric.clone()).map_err(|e| format!("Corrupt rubric: {}", e))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingScore {
+    pub id: i64,
+    pub item_id: i64,
+    pub rater_id: i64,
+    pub criteria: BTreeMap<String, f64>,
+    pub total: f64,
+    pub comment: Option<String>,
+    pub scored_at: NaiveDateTime,
+}
+
+impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for RatingScore {
+    fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, Error> {
+        let criteria: serde_json::Value = row.try_get("criteria")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            item_id: row.try_get("item_id")?,
+            rater_id: row.try_get("rater_id")?,
+            criteria: serde_json::from_value(criteria)
+                .map_err(|e| Error::Protocol(format!("Corrupt rating criteria: {}", e)))?,
+            total: row.try_get("total")?,
+            comment: row.try_get("comment")?,
+            scored_at: row.try_get("scored_at")?,
+        })
+    }
+}
+
+/// Both raters' totals for one double-scored item
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DoubleScoredPair {
+    pub item_id: i64,
+    pub question_id: i64,
+    pub tool_id: i64,
+    pub rubric: serde_json::Value,
+    pub first_total: f64,
+    pub second_total: f64,
+    pub needs_adjudication: bool,
+}
+
+impl Database {
+    /// Queue the text and drawing answers of a session for rating.
+    /// Already queued answers are left alone; returns how many items were added.
+    /// Nothing is queued if an answered question has no usable rubric.
+    pub async fn enqueue_rating_items(
+        &self,
+        event_id: i64,
+        session_id: i64,
+        snapshot: &PackageSnapshot,
+        answers: &[SubmittedAnswer],
+    ) -> Result<i64, RatingError> {
+        let given: HashMap<i64, &str> = answers.iter()
+            .filter_map(|a| a.answer.as_deref().map(|ans| (a.question_id, ans)))
+            .filter(|(_, ans)| !ans.trim().is_empty())
+            .collect();
+        let drawn: Vec<i64> = self.get_session_drawing_question_ids(session_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut added = 0;
+
+        for question in snapshot.structure.subtests.iter().flat_map(|s| s.questions.iter()) {
+            let question_type = match QuestionType::parse(&question.question_type) {
+                Some(t) if rating::needs_manual_scoring(t) => t,
+                _ => continue,
+            };
+
+            let answer = given.get(&question.id).map(|a| a.to_string());
+            let has_drawing = question_type == QuestionType::Drawing && drawn.contains(&question.id);
+            if answer.is_none() && !has_drawing {
+                continue;
+            }
+
+            let rubric = Rubric::from_options(question.options.as_ref())
+                .map_err(|reason| RatingError::InvalidRubric { question_id: question.id, reason })?;
+            let rubric = serde_json::to_value(&rubric)
+                .map_err(|e| Error::Protocol(format!("Failed to serialize rubric: {}", e)))?;
+
+            added += sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO rating_items (event_id, session_id, tool_id, question_id, question_type, answer, rubric)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(event_id)
+            .bind(session_id)
+            .bind(snapshot.structure.tool.id)
+            .bind(question.id)
+            .bind(question_type.as_str())
+            .bind(answer)
+            .bind(rubric)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as i64;
+        }
+
+        tx.commit().await?;
+        Ok(added)
+    }
+
+    pub async fn get_rating_item(&self, item_id: i64) -> Result<RatingItem, Error> {
+        sqlx::query_as::<_, RatingItem>("SELECT * FROM rating_items WHERE id = ?")
+            .bind(item_id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Items of an event, optionally narrowed to a status and/or rater
+    pub async fn get_rating_queue(&self, event_id: i64, status: Option<&str>, rater_id: Option<i64>) -> Result<Vec<RatingItem>, Error> {
+        sqlx::query_as::<_, RatingItem>(
+            r#"
+            SELECT * FROM rating_items
+            WHERE event_id = ?
+              AND (? IS NULL OR status = ?)
+              AND (? IS NULL OR assigned_to = ?)
+            ORDER BY session_id, question_id
+            "#
+        )
+        .bind(event_id)
+        .bind(status)
+        .bind(status)
+        .bind(rater_id)
+        .bind(rater_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_session_rating_items(&self, session_id: i64) -> Result<Vec<RatingItem>, Error> {
+        sqlx::query_as::<_, RatingItem>("SELECT * FROM rating_items WHERE session_id = ? ORDER BY question_id")
+            .bind(session_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Hand items to a rater. Items that are already scored keep their rater.
+    pub async fn assign_rating_items(&self, item_ids: &[i64], rater_id: i64) -> Result<i64, Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut assigned = 0;
+
+        for item_id in item_ids {
+            assigned += sqlx::query(
+                r#"
+                UPDATE rating_items
+                SET assigned_to = ?, assigned_at = CURRENT_TIMESTAMP, status = 'assigned'
+                WHERE id = ? AND status != 'scored'
+                "#
+            )
+            .bind(rater_id)
+            .bind(item_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as i64;
+        }
+
+        tx.commit().await?;
+        Ok(assigned)
+    }
+
+    /// Add a second rater for reliability. Items already scored by the first rater go back to
+    /// `assigned` until the second score is in; adjudicated items and the first rater are skipped.
+    pub async fn assign_second_rater(&self, item_ids: &[i64], rater_id: i64) -> Result<i64, Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut assigned = 0;
+
+        for item_id in item_ids {
+            assigned += sqlx::query(
+                r#"
+                UPDATE rating_items
+                SET second_rater = ?,
+                    required_ratings = 2,
+                    status = CASE WHEN status = 'scored' THEN 'assigned' ELSE status END
+                WHERE id = ? AND adjudicated_by IS NULL AND (assigned_to IS NULL OR assigned_to != ?)
+                "#
+            )
+            .bind(rater_id)
+            .bind(item_id)
+            .bind(rater_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as i64;
+        }
+
+        tx.commit().await?;
+        Ok(assigned)
+    }
+
+    /// Make `rater_id` the first rater of an item nobody has claimed yet. Returns false if
+    /// someone else got there first.
+    pub async fn claim_rating_item(&self, item_id: i64, rater_id: i64) -> Result<bool, Error> {
+        let claimed = sqlx::query(
+            r#"
+            UPDATE rating_items
+            SET assigned_to = ?, assigned_at = CURRENT_TIMESTAMP,
+                status = CASE WHEN status = 'pending' THEN 'assigned' ELSE status END
+            WHERE id = ? AND assigned_to IS NULL AND (second_rater IS NULL OR second_rater != ?)
+            "#
+        )
+        .bind(rater_id)
+        .bind(item_id)
+        .bind(rater_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(claimed > 0)
+    }
+
+    /// Record (or revise) a rater's score. The item counts as scored once it has all its ratings.
+    pub async fn record_rating(
+        &self,
+        item_id: i64,
+        rater_id: i64,
+        criteria: &BTreeMap<String, f64>,
+        total: f64,
+        comment: Option<&str>,
+    ) -> Result<RatingScore, Error> {
+        let criteria = serde_json::to_value(criteria)
+            .map_err(|e| Error::Protocol(format!("Failed to serialize criteria: {}", e)))?;
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO rating_scores (item_id, rater_id, criteria, total, comment)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(item_id, rater_id) DO UPDATE SET
+                criteria = excluded.criteria,
+                total = excluded.total,
+                comment = excluded.comment,
+                scored_at = CURRENT_TIMESTAMP
+            "#
+        )
+        .bind(item_id)
+        .bind(rater_id)
+        .bind(criteria)
+        .bind(total)
+        .bind(comment)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE rating_items
+            SET status = CASE
+                WHEN (SELECT COUNT(*) FROM rating_scores WHERE item_id = rating_items.id) >= required_ratings THEN 'scored'
+                ELSE 'assigned'
+            END
+            WHERE id = ?
+            "#
+        )
+        .bind(item_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        sqlx::query_as::<_, RatingScore>("SELECT * FROM rating_scores WHERE item_id = ? AND rater_id = ?")
+            .bind(item_id)
+            .bind(rater_id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    pub async fn set_needs_adjudication(&self, item_id: i64, needed: bool) -> Result<(), Error> {
+        sqlx::query("UPDATE rating_items SET needs_adjudication = ? WHERE id = ?")
+            .bind(needed)
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The adjudicator's rating (recorded with `record_rating`) becomes the final score
+    pub async fn mark_adjudicated(&self, item_id: i64, adjudicator_id: i64) -> Result<(), Error> {
+        sqlx::query("UPDATE rating_items SET adjudicated_by = ?, status = 'scored' WHERE id = ?")
+            .bind(adjudicator_id)
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_adjudication_queue(&self, event_id: i64) -> Result<Vec<RatingItem>, Error> {
+        sqlx::query_as::<_, RatingItem>(
+            r#"
+            SELECT * FROM rating_items
+            WHERE event_id = ? AND needs_adjudication = 1 AND adjudicated_by IS NULL
+            ORDER BY session_id, question_id
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Items of an event that both raters have scored
+    pub async fn get_double_scored_pairs(&self, event_id: i64) -> Result<Vec<DoubleScoredPair>, Error> {
+        sqlx::query_as::<_, DoubleScoredPair>(
+            r#"
+            SELECT i.id AS item_id, i.question_id, i.tool_id, i.rubric,
+                   a.total AS first_total, b.total AS second_total, i.needs_adjudication
+            FROM rating_items i
+            JOIN rating_scores a ON a.item_id = i.id AND a.rater_id = i.assigned_to
+            JOIN rating_scores b ON b.item_id = i.id AND b.rater_id = i.second_rater
+            WHERE i.event_id = ?
+            ORDER BY i.question_id, i.id
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Only admins and operators score answers
+    pub async fn is_rater(&self, user_id: i64) -> Result<bool, Error> {
+        let role: Option<String> = sqlx::query("SELECT role FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|row| row.get("role"));
+        Ok(matches!(role.as_deref(), Some("admin") | Some("operator")))
+    }
+
+    pub async fn get_item_ratings(&self, item_id: i64) -> Result<Vec<RatingScore>, Error> {
+        sqlx::query_as::<_, RatingScore>("SELECT * FROM rating_scores WHERE item_id = ? ORDER BY scored_at, id")
+            .bind(item_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Write the manual scores into the session's report under `scores.manual`, creating the report if needed
+    pub async fn merge_manual_scores(&self, session_id: i64, summary: &ManualScoreSummary) -> Result<i64, Error> {
+        let manual = serde_json::to_string(summary)
+            .map_err(|e| Error::Protocol(format!("Failed to serialize manual scores: {}", e)))?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO reports (session_id, scores, interpretations, generated_at)
+            VALUES (?, json_object('manual', json(?)), '{}', ?)
+            ON CONFLICT(session_id) DO UPDATE SET
+                scores = json_set(COALESCE(reports.scores, '{}'), '$.manual', json(?))
+            RETURNING id
+            "#
+        )
+        .bind(session_id)
+        .bind(&manual)
+        .bind(chrono::Local::now().naive_local())
+        .bind(&manual)
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.record_report_administration(session_id).await?;
+        self.record_report_participant(session_id).await?;
+        self.refresh_session_progress(session_id).await?;
+        Ok(row.get("id"))
+    }
+}
@@ -0,0 +1,262 @@
+// Retakes
+// A retake starts a new attempt for a participant. Earlier sessions and their reports are
+// kept, marked superseded; the event limits attempts, enforces a cooling-off period and
+// chooses which attempt its reports count.
+
+use chrono::{Duration, NaiveDateTime};
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::Database;
+use super::session_lifecycle::{record_transition, SessionStatus};
+use super::withdrawal::has_left;
+
+/// `report_selection(report_id, selected)`: for each participant, event and tool (by id in
+/// the session metadata, else by name), the one report the event's `report_attempt` policy
+/// counts. Within an attempt the newest report wins.
+pub(crate) const REPORT_SELECTION_CTE: &str = r#"
+    report_selection AS (
+        SELECT r.id as report_id,
+               ROW_NUMBER() OVER (
+                   PARTITION BY s.event_id, COALESCE(CAST(s.user_id AS TEXT), s.participant_id),
+                                COALESCE(json_extract(s.metadata, '$.tool_id'), json_extract(s.metadata, '$.toolId'),
+                                         json_extract(s.metadata, '$.testName'), 'Assessment')
+                   ORDER BY
+                       -- Reports without a total can't be the best one
+                       CASE WHEN e.report_attempt = 'best' THEN json_extract(r.scores, '$.total_score') IS NULL END ASC,
+                       CASE WHEN e.report_attempt = 'best' THEN json_extract(r.scores, '$.total_score') END DESC,
+                       CASE WHEN e.report_attempt = 'first' THEN s.attempt_number END ASC,
+                       s.attempt_number DESC, r.generated_at DESC, r.id DESC
+               ) = 1 as selected
+        FROM reports r
+        JOIN sessions s ON r.session_id = s.id
+        JOIN events e ON s.event_id = e.id
+    )
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportAttempt {
+    /// Highest total score
+    Best,
+    Latest,
+    First,
+}
+
+impl ReportAttempt {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportAttempt::Best => "best",
+            ReportAttempt::Latest => "latest",
+            ReportAttempt::First => "first",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "best" => Some(ReportAttempt::Best),
+            "latest" => Some(ReportAttempt::Latest),
+            "first" => Some(ReportAttempt::First),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RetakeError {
+    NotEnrolled,
+    Withdrawn,
+    AttemptLimit { max_attempts: i64 },
+    CoolingOff { until: String },
+    InvalidPolicy(String),
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RetakeError {
+    fn from(err: sqlx::Error) -> Self {
+        RetakeError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for RetakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetakeError::NotEnrolled => write!(f, "Participant is not enrolled in this event"),
+            RetakeError::Withdrawn => write!(f, "Participant has withdrawn from this event or did not show up"),
+            RetakeError::AttemptLimit { max_attempts } => {
+                write!(f, "This event allows at most {} attempts", max_attempts)
+            }
+            RetakeError::CoolingOff { until } => write!(f, "A retake is possible from {}", until),
+            RetakeError::InvalidPolicy(msg) => write!(f, "Invalid retake policy: {}", msg),
+            RetakeError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetakePolicy {
+    /// `None` for no limit
+    pub max_attempts: Option<i64>,
+    pub cooldown_hours: Option<i64>,
+    pub report_attempt: ReportAttempt,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AttemptSummary {
+    pub attempt_number: i64,
+    pub session_count: i64,
+    pub completed_sessions: i64,
+    pub report_count: i64,
+    pub started_at: Option<NaiveDateTime>,
+    pub ended_at: Option<NaiveDateTime>,
+    pub superseded_at: Option<String>,
+}
+
+pub fn validate_retake_policy(policy: &RetakePolicy) -> Result<(), String> {
+    if policy.max_attempts.map(|m| m < 1).unwrap_or(false) {
+        return Err("at least 1 attempt must be allowed".to_string());
+    }
+    if policy.cooldown_hours.map(|h| h < 0).unwrap_or(false) {
+        return Err("the cooling-off period can't be negative".to_string());
+    }
+    Ok(())
+}
+
+impl Database {
+    pub async fn get_retake_policy(&self, event_id: i64) -> Result<RetakePolicy, Error> {
+        let row = sqlx::query("SELECT max_attempts, retake_cooldown_hours, report_attempt FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(RetakePolicy {
+            max_attempts: row.get("max_attempts"),
+            cooldown_hours: row.get("retake_cooldown_hours"),
+            report_attempt: ReportAttempt::parse(&row.get::<String, _>("report_attempt")).unwrap_or(ReportAttempt::Latest),
+        })
+    }
+
+    pub async fn set_retake_policy(&self, event_id: i64, policy: &RetakePolicy) -> Result<RetakePolicy, RetakeError> {
+        validate_retake_policy(policy).map_err(RetakeError::InvalidPolicy)?;
+
+        sqlx::query("UPDATE events SET max_attempts = ?, retake_cooldown_hours = ?, report_attempt = ? WHERE id = ?")
+            .bind(policy.max_attempts)
+            .bind(policy.cooldown_hours)
+            .bind(policy.report_attempt.as_str())
+            .bind(event_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(self.get_retake_policy(event_id).await?)
+    }
+
+    /// Start a new attempt. The current attempt's sessions are marked superseded (open ones
+    /// are terminated) and the participant goes back to enrolled. Returns the new attempt number.
+    pub async fn start_retake(&self, event_id: i64, user_id: i64) -> Result<i64, RetakeError> {
+        let policy = self.get_retake_policy(event_id).await?;
+        let mut tx = self.pool.begin().await?;
+
+        let participant = sqlx::query("SELECT status, attempt_number FROM event_participants WHERE event_id = ? AND user_id = ?")
+            .bind(event_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RetakeError::NotEnrolled)?;
+        if has_left(&participant.get::<String, _>("status")) {
+            return Err(RetakeError::Withdrawn);
+        }
+        let current: i64 = participant.get("attempt_number");
+
+        if let Some(max_attempts) = policy.max_attempts {
+            if current >= max_attempts {
+                return Err(RetakeError::AttemptLimit { max_attempts });
+            }
+        }
+
+        // The cooling-off period runs from when the last attempt's latest session ended
+        if let Some(hours) = policy.cooldown_hours.filter(|h| *h > 0) {
+            let last_ended: Option<NaiveDateTime> = sqlx::query(
+                "SELECT MAX(COALESCE(completed_at, status_changed_at, started_at)) as ended
+                 FROM sessions WHERE event_id = ? AND user_id = ? AND attempt_number = ?"
+            )
+            .bind(event_id)
+            .bind(user_id)
+            .bind(current)
+            .fetch_one(&mut *tx)
+            .await?
+            .get("ended");
+
+            if let Some(ended) = last_ended {
+                let until = ended + Duration::hours(hours);
+                if chrono::Utc::now().naive_utc() < until {
+                    return Err(RetakeError::CoolingOff { until: until.format("%Y-%m-%d %H:%M UTC").to_string() });
+                }
+            }
+        }
+
+        let open = sqlx::query(
+            "SELECT id, status FROM sessions
+             WHERE event_id = ? AND user_id = ? AND superseded_at IS NULL AND status IN ('pending', 'active', 'paused')"
+        )
+        .bind(event_id)
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for session in open {
+            let session_id: i64 = session.get("id");
+            let from = SessionStatus::parse(&session.get::<String, _>("status")).unwrap_or(SessionStatus::Active);
+            sqlx::query(
+                "UPDATE sessions SET status = 'terminated', status_reason = 'Superseded by a retake',
+                        status_changed_at = CURRENT_TIMESTAMP, completed_at = CURRENT_TIMESTAMP
+                 WHERE id = ?"
+            )
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+            record_transition(&mut tx, session_id, from, SessionStatus::Terminated, Some("Superseded by a retake")).await?;
+        }
+
+        sqlx::query("UPDATE sessions SET superseded_at = CURRENT_TIMESTAMP WHERE event_id = ? AND user_id = ? AND superseded_at IS NULL")
+            .bind(event_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE event_participants SET status = 'enrolled', completed_at = NULL, attempt_number = ? WHERE event_id = ? AND user_id = ?"
+        )
+        .bind(current + 1)
+        .bind(event_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(current + 1)
+    }
+
+    pub async fn get_participant_attempts(&self, event_id: i64, user_id: i64) -> Result<Vec<AttemptSummary>, Error> {
+        sqlx::query_as::<_, AttemptSummary>(
+            r#"
+            SELECT
+                s.attempt_number,
+                COUNT(*) as session_count,
+                COALESCE(SUM(s.status = 'completed'), 0) as completed_sessions,
+                COUNT(r.id) as report_count,
+                MIN(s.started_at) as started_at,
+                MAX(s.completed_at) as ended_at,
+                MAX(s.superseded_at) as superseded_at
+            FROM sessions s
+            LEFT JOIN reports r ON r.session_id = s.id
+            WHERE s.event_id = ? AND s.user_id = ?
+            GROUP BY s.attempt_number
+            ORDER BY s.attempt_number
+            "#
+        )
+        .bind(event_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
@@ -856,7 +856,7 @@ pub async fn seed_dummy_results(db: &Database) -> Result<(), Error> {
             let event_id = if user.id % 2 == 0 { event1 } else { event2 };
             // Participant ID is usually a code (e.g. K-123), but here we use username for simplicity or random string
             let participant_code = format!("{}-{}", user.username.to_uppercase(), 1000 + user.id);
-            let session_id = db.create_session(event_id, &participant_code, None).await?;
+            let session_id = db.create_session(event_id, &participant_code, Some(user.id), None).await?;
             
             // Link session to user (update user_id)
             sqlx::query("UPDATE sessions SET user_id = ?, status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?")
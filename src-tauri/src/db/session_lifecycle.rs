@@ -0,0 +1,243 @@
+// Session Lifecycle
+// State machine for test sessions: pending -> active <-> paused -> completed / terminated
+
+use sqlx::{Error, Row, SqliteConnection};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::Database;
+use super::models::Session;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+    Pending,
+    Active,
+    Paused,
+    Completed,
+    Terminated,
+}
+
+impl SessionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionStatus::Pending => "pending",
+            SessionStatus::Active => "active",
+            SessionStatus::Paused => "paused",
+            SessionStatus::Completed => "completed",
+            SessionStatus::Terminated => "terminated",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(SessionStatus::Pending),
+            "active" => Some(SessionStatus::Active),
+            "paused" => Some(SessionStatus::Paused),
+            "completed" => Some(SessionStatus::Completed),
+            "terminated" => Some(SessionStatus::Terminated),
+            _ => None,
+        }
+    }
+
+    /// Completed and terminated sessions can no longer change
+    pub fn is_final(&self) -> bool {
+        matches!(self, SessionStatus::Completed | SessionStatus::Terminated)
+    }
+
+    pub fn can_transition_to(&self, next: SessionStatus) -> bool {
+        use SessionStatus::*;
+        matches!(
+            (self, next),
+            (Pending, Active)
+                | (Pending, Terminated)
+                | (Active, Paused)
+                | (Active, Completed)
+                | (Active, Terminated)
+                | (Paused, Active)
+                | (Paused, Terminated)
+        )
+    }
+
+    /// Whether entering this state can change the participant's progress. A terminated
+    /// session leaves them enrolled; only `withdraw_participant` withdraws someone.
+    fn updates_progress(&self) -> bool {
+        !matches!(self, SessionStatus::Pending)
+    }
+}
+
+impl fmt::Display for SessionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionStateError {
+    NotFound(i64),
+    UnknownStatus(String),
+    InvalidTransition { from: SessionStatus, to: SessionStatus },
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for SessionStateError {
+    fn from(err: sqlx::Error) -> Self {
+        SessionStateError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for SessionStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionStateError::NotFound(id) => write!(f, "Session {} not found", id),
+            SessionStateError::UnknownStatus(status) => write!(f, "Unknown session status '{}'", status),
+            SessionStateError::InvalidTransition { from, to } => {
+                write!(f, "Cannot change session from '{}' to '{}'", from, to)
+            }
+            SessionStateError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionTransition {
+    pub id: i64,
+    pub session_id: i64,
+    pub from_status: String,
+    pub to_status: String,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+impl Database {
+    pub async fn get_session_by_id(&self, session_id: i64) -> Result<Session, Error> {
+        sqlx::query_as::<_, Session>("SELECT * FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Move a session to a new status, recording the transition and
+    /// keeping the participant's enrollment status in sync.
+    pub async fn transition_session(
+        &self,
+        session_id: i64,
+        to: SessionStatus,
+        reason: Option<&str>,
+    ) -> Result<Session, SessionStateError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query("SELECT status FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(SessionStateError::NotFound(session_id))?;
+
+        let current: String = row.get("status");
+        let from = SessionStatus::parse(&current)
+            .ok_or(SessionStateError::UnknownStatus(current))?;
+
+        if !from.can_transition_to(to) {
+            return Err(SessionStateError::InvalidTransition { from, to });
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE sessions
+            SET status = ?1,
+                status_reason = ?2,
+                status_changed_at = CURRENT_TIMESTAMP,
+                started_at = CASE WHEN ?1 = 'active' THEN COALESCE(started_at, CURRENT_TIMESTAMP) ELSE started_at END,
+                paused_seconds = CASE
+                    WHEN paused_at IS NOT NULL
+                    THEN paused_seconds + CAST((julianday(CURRENT_TIMESTAMP) - julianday(paused_at)) * 86400 AS INTEGER)
+                    ELSE paused_seconds
+                END,
+                paused_at = CASE WHEN ?1 = 'paused' THEN CURRENT_TIMESTAMP ELSE NULL END,
+                completed_at = CASE WHEN ?1 IN ('completed', 'terminated') THEN CURRENT_TIMESTAMP ELSE completed_at END
+            WHERE id = ?3
+            "#
+        )
+        .bind(to.as_str())
+        .bind(reason)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+
+        record_transition(&mut tx, session_id, from, to, reason).await?;
+        sync_participant_status(&mut tx, session_id, to).await?;
+
+        tx.commit().await?;
+
+        Ok(self.get_session_by_id(session_id).await?)
+    }
+
+    pub async fn get_session_transitions(&self, session_id: i64) -> Result<Vec<SessionTransition>, Error> {
+        sqlx::query_as::<_, SessionTransition>(
+            "SELECT * FROM session_transitions WHERE session_id = ? ORDER BY id"
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+pub(crate) async fn record_transition(
+    conn: &mut SqliteConnection,
+    session_id: i64,
+    from: SessionStatus,
+    to: SessionStatus,
+    reason: Option<&str>,
+) -> Result<(), Error> {
+    sqlx::query(
+        "INSERT INTO session_transitions (session_id, from_status, to_status, reason)
+         VALUES (?, ?, ?, ?)"
+    )
+    .bind(session_id)
+    .bind(from.as_str())
+    .bind(to.as_str())
+    .bind(reason)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Mirror a session status change onto `event_participants`.
+///
+/// Progress (enrolled/in_progress/completed) is worked out from the tools finished so
+/// far, see `progress`.
+pub(crate) async fn sync_participant_status(
+    conn: &mut SqliteConnection,
+    session_id: i64,
+    status: SessionStatus,
+) -> Result<(), Error> {
+    if !status.updates_progress() {
+        return Ok(());
+    }
+
+    let row = sqlx::query("SELECT event_id, user_id FROM sessions WHERE id = ?")
+        .bind(session_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    let event_id: i64 = row.get("event_id");
+
+    // Anonymous sessions have no enrollment to update
+    match row.get::<Option<i64>, _>("user_id") {
+        Some(user_id) => super::progress::refresh_participant_progress(conn, event_id, user_id).await,
+        None => Ok(()),
+    }
+}
+
+impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for SessionTransition {
+    fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            session_id: row.try_get("session_id")?,
+            from_status: row.try_get("from_status")?,
+            to_status: row.try_get("to_status")?,
+            reason: row.try_get("reason")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
@@ -0,0 +1,291 @@
+// Event Statistics
+// Completion, timing, score distributions and protocol quality for one event, computed
+// in SQL over its sessions and reports. Scores only use the report each participant's
+// retake policy counts.
+
+use sqlx::{Error, Row};
+use serde::{Serialize, Deserialize};
+
+use super::Database;
+use super::retakes::REPORT_SELECTION_CTE;
+
+/// Bins per score histogram
+pub const HISTOGRAM_BINS: i64 = 10;
+
+/// Counted reports of event ?1 (`event_scores`): one row per report with its tool and total score.
+/// `score` is NULL when the report has no total; distributions skip those rather than count them as 0.
+const EVENT_SCORES_CTE: &str = r#"
+    event_scores AS (
+        SELECT r.id as report_id, r.session_id, t.id as tool_id,
+               COALESCE(t.name, json_extract(s.metadata, '$.testName'), 'Assessment') as tool_name,
+               CAST(json_extract(r.scores, '$.total_score') AS REAL) as score,
+               r.scores
+        FROM reports r
+        JOIN sessions s ON s.id = r.session_id
+        JOIN report_selection rs ON rs.report_id = r.id
+        LEFT JOIN tools t ON t.id = COALESCE(
+            json_extract(s.metadata, '$.tool_id'),
+            json_extract(s.metadata, '$.toolId'),
+            (SELECT id FROM tools WHERE name = json_extract(s.metadata, '$.testName'))
+        )
+        WHERE s.event_id = ?1 AND rs.selected
+    )
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolScoreDistribution {
+    /// `None` for sessions that only recorded a test name
+    pub tool_id: Option<i64>,
+    pub tool_name: String,
+    pub count: i64,
+    pub mean: f64,
+    /// Sample standard deviation, `None` with fewer than two scores
+    pub sd: Option<f64>,
+    pub min: f64,
+    pub max: f64,
+    /// Nearest-rank quartiles
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ViolationTypeCount {
+    pub event_type: String,
+    pub count: i64,
+    pub critical: i64,
+}
+
+/// Surveillance events of warning or critical severity
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ViolationTotals {
+    pub total: i64,
+    pub critical: i64,
+    pub sessions_affected: i64,
+    pub by_type: Vec<ViolationTypeCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventStatistics {
+    pub event_id: i64,
+    pub participants: i64,
+    pub completed_participants: i64,
+    pub withdrawn: i64,
+    pub no_shows: i64,
+    /// Completed share of the participants still taking part
+    pub completion_rate: Option<f64>,
+    pub completed_sessions: i64,
+    /// Active testing time of completed sessions, pauses excluded
+    pub average_duration_seconds: Option<f64>,
+    /// Reports marked invalid, carrying flags, or from a session with a critical surveillance event
+    pub flagged_protocols: i64,
+    /// Reports marked `"valid": false`
+    pub invalid_protocols: i64,
+    pub violations: ViolationTotals,
+    pub tools: Vec<ToolScoreDistribution>,
+}
+
+/// Spread per-bin counts over `HISTOGRAM_BINS` equal-width bins between `min` and `max`.
+/// When every score is the same there is a single bin.
+pub fn histogram_bins(min: f64, max: f64, counts: &[(i64, i64)]) -> Vec<HistogramBin> {
+    if max <= min {
+        let count = counts.iter().map(|(_, c)| c).sum();
+        return vec![HistogramBin { lower: min, upper: max, count }];
+    }
+
+    let width = (max - min) / HISTOGRAM_BINS as f64;
+    (0..HISTOGRAM_BINS)
+        .map(|bin| HistogramBin {
+            lower: min + width * bin as f64,
+            upper: if bin == HISTOGRAM_BINS - 1 { max } else { min + width * (bin + 1) as f64 },
+            count: counts.iter().filter(|(b, _)| *b == bin).map(|(_, c)| c).sum(),
+        })
+        .collect()
+}
+
+impl Database {
+    pub async fn get_event_statistics(&self, event_id: i64) -> Result<EventStatistics, Error> {
+        let totals = sqlx::query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM event_participants WHERE event_id = e.id) as participants,
+                (SELECT COUNT(*) FROM event_participants WHERE event_id = e.id AND status = 'completed') as completed_participants,
+                (SELECT COUNT(*) FROM event_participants WHERE event_id = e.id AND status = 'withdrawn') as withdrawn,
+                (SELECT COUNT(*) FROM event_participants WHERE event_id = e.id AND status = 'no_show') as no_shows,
+                (SELECT COUNT(*) FROM sessions
+                 WHERE event_id = e.id AND status = 'completed' AND superseded_at IS NULL) as completed_sessions,
+                (SELECT AVG((julianday(completed_at) - julianday(started_at)) * 86400 - paused_seconds) FROM sessions
+                 WHERE event_id = e.id AND status = 'completed' AND superseded_at IS NULL
+                   AND started_at IS NOT NULL AND completed_at IS NOT NULL) as average_duration_seconds
+            FROM events e
+            WHERE e.id = ?
+            "#
+        )
+        .bind(event_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let participants: i64 = totals.get("participants");
+        let completed_participants: i64 = totals.get("completed_participants");
+        let withdrawn: i64 = totals.get("withdrawn");
+        let no_shows: i64 = totals.get("no_shows");
+        let taking_part = participants - withdrawn - no_shows;
+
+        let protocols = sqlx::query(&format!(
+            r#"
+            WITH {}, {}
+            SELECT
+                COALESCE(SUM(json_extract(es.scores, '$.valid') = 0), 0) as invalid_protocols,
+                COALESCE(SUM(
+                    json_extract(es.scores, '$.valid') = 0
+                    OR COALESCE(json_array_length(es.scores, '$.flags'), 0) > 0
+                    OR EXISTS (SELECT 1 FROM surveillance_logs sl WHERE sl.session_id = es.session_id AND sl.severity = 'critical')
+                ), 0) as flagged_protocols
+            FROM event_scores es
+            "#,
+            REPORT_SELECTION_CTE, EVENT_SCORES_CTE
+        ))
+        .bind(event_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let by_type = sqlx::query_as::<_, ViolationTypeCount>(
+            r#"
+            SELECT sl.event_type, COUNT(*) as count, COALESCE(SUM(sl.severity = 'critical'), 0) as critical
+            FROM surveillance_logs sl
+            JOIN sessions s ON s.id = sl.session_id
+            WHERE s.event_id = ? AND sl.severity != 'info'
+            GROUP BY sl.event_type
+            ORDER BY count DESC, sl.event_type
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let sessions_affected: i64 = sqlx::query(
+            r#"
+            SELECT COUNT(DISTINCT sl.session_id) as count
+            FROM surveillance_logs sl
+            JOIN sessions s ON s.id = sl.session_id
+            WHERE s.event_id = ? AND sl.severity != 'info'
+            "#
+        )
+        .bind(event_id)
+        .fetch_one(&self.pool)
+        .await?
+        .get("count");
+
+        Ok(EventStatistics {
+            event_id,
+            participants,
+            completed_participants,
+            withdrawn,
+            no_shows,
+            completion_rate: (taking_part > 0).then(|| completed_participants as f64 / taking_part as f64),
+            completed_sessions: totals.get("completed_sessions"),
+            average_duration_seconds: totals.get("average_duration_seconds"),
+            flagged_protocols: protocols.get("flagged_protocols"),
+            invalid_protocols: protocols.get("invalid_protocols"),
+            violations: ViolationTotals {
+                total: by_type.iter().map(|v| v.count).sum(),
+                critical: by_type.iter().map(|v| v.critical).sum(),
+                sessions_affected,
+                by_type,
+            },
+            tools: self.get_event_score_distributions(event_id).await?,
+        })
+    }
+
+    async fn get_event_score_distributions(&self, event_id: i64) -> Result<Vec<ToolScoreDistribution>, Error> {
+        let summaries = sqlx::query(&format!(
+            r#"
+            WITH {}, {},
+            ranked AS (
+                SELECT tool_id, tool_name, score,
+                       ROW_NUMBER() OVER (PARTITION BY tool_id, tool_name ORDER BY score) as rn,
+                       COUNT(*) OVER (PARTITION BY tool_id, tool_name) as n
+                FROM event_scores
+                WHERE score IS NOT NULL
+            )
+            SELECT
+                tool_id,
+                tool_name,
+                COUNT(*) as count,
+                AVG(score) as mean,
+                CASE WHEN COUNT(*) > 1
+                     THEN (SUM(score * score) - COUNT(*) * AVG(score) * AVG(score)) / (COUNT(*) - 1)
+                END as variance,
+                MIN(score) as min,
+                MAX(score) as max,
+                MIN(CASE WHEN rn * 4 >= n THEN score END) as q1,
+                MIN(CASE WHEN rn * 2 >= n THEN score END) as median,
+                MIN(CASE WHEN rn * 4 >= n * 3 THEN score END) as q3
+            FROM ranked
+            GROUP BY tool_id, tool_name
+            ORDER BY tool_name, tool_id
+            "#,
+            REPORT_SELECTION_CTE, EVENT_SCORES_CTE
+        ))
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let bins = sqlx::query(&format!(
+            r#"
+            WITH {}, {},
+            bounds AS (
+                SELECT tool_id, tool_name, MIN(score) as lo, MAX(score) as hi
+                FROM event_scores
+                WHERE score IS NOT NULL
+                GROUP BY tool_id, tool_name
+            )
+            SELECT es.tool_id, es.tool_name,
+                   CASE WHEN b.hi = b.lo THEN 0
+                        ELSE MIN(CAST((es.score - b.lo) * ?2 / (b.hi - b.lo) AS INTEGER), ?2 - 1)
+                   END as bin,
+                   COUNT(*) as count
+            FROM event_scores es
+            JOIN bounds b ON b.tool_id IS es.tool_id AND b.tool_name = es.tool_name
+            WHERE es.score IS NOT NULL
+            GROUP BY es.tool_id, es.tool_name, bin
+            "#,
+            REPORT_SELECTION_CTE, EVENT_SCORES_CTE
+        ))
+        .bind(event_id)
+        .bind(HISTOGRAM_BINS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries.iter().map(|row| {
+            let tool_id: Option<i64> = row.get("tool_id");
+            let tool_name: String = row.get("tool_name");
+            let (min, max): (f64, f64) = (row.get("min"), row.get("max"));
+            let counts: Vec<(i64, i64)> = bins.iter()
+                .filter(|b| b.get::<Option<i64>, _>("tool_id") == tool_id && b.get::<String, _>("tool_name") == tool_name)
+                .map(|b| (b.get("bin"), b.get("count")))
+                .collect();
+
+            ToolScoreDistribution {
+                count: row.get("count"),
+                mean: row.get("mean"),
+                sd: row.get::<Option<f64>, _>("variance").map(|v| v.max(0.0).sqrt()),
+                min,
+                max,
+                q1: row.get("q1"),
+                median: row.get("median"),
+                q3: row.get("q3"),
+                histogram: histogram_bins(min, max, &counts),
+                tool_id,
+                tool_name,
+            }
+        }).collect())
+    }
+}
@@ -0,0 +1,190 @@
+// Withdrawal and No-shows
+// Recording that a candidate dropped out of an event or never turned up. Either way their
+// open sessions end, the reason is kept in the enrollment notes and the place goes back
+// to the waitlist.
+
+use sqlx::Row;
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::Database;
+use super::enrollment::promote_from_waitlist;
+use super::session_lifecycle::{record_transition, SessionStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantExit {
+    Withdrawn,
+    NoShow,
+}
+
+impl ParticipantExit {
+    /// Status stored in `event_participants`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParticipantExit::Withdrawn => "withdrawn",
+            ParticipantExit::NoShow => "no_show",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ParticipantExit::Withdrawn => "Withdrawn",
+            ParticipantExit::NoShow => "No-show",
+        }
+    }
+}
+
+/// Whether a participant status means they no longer take part (and hold no place)
+pub fn has_left(status: &str) -> bool {
+    status == ParticipantExit::Withdrawn.as_str() || status == ParticipantExit::NoShow.as_str()
+}
+
+#[derive(Debug)]
+pub enum WithdrawalError {
+    NotEnrolled,
+    AlreadyLeft { status: String },
+    /// A no-show can't have started a session
+    AlreadyStarted,
+    ReasonRequired,
+    DatabaseError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for WithdrawalError {
+    fn from(err: sqlx::Error) -> Self {
+        WithdrawalError::DatabaseError(err)
+    }
+}
+
+impl fmt::Display for WithdrawalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WithdrawalError::NotEnrolled => write!(f, "Participant is not enrolled in this event"),
+            WithdrawalError::AlreadyLeft { status } => write!(f, "Participant is already marked as {}", status),
+            WithdrawalError::AlreadyStarted => write!(f, "Participant has already started testing and can't be a no-show"),
+            WithdrawalError::ReasonRequired => write!(f, "A reason for the withdrawal is required"),
+            WithdrawalError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExitOutcome {
+    pub status: String,
+    /// Sessions that were still open and got terminated
+    pub terminated_sessions: Vec<i64>,
+    /// Waitlisted candidates who took over the freed place
+    pub promoted: Vec<i64>,
+}
+
+impl Database {
+    /// Record that a participant withdrew, with the reason they gave
+    pub async fn withdraw_participant(&self, event_id: i64, user_id: i64, reason: &str) -> Result<ExitOutcome, WithdrawalError> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(WithdrawalError::ReasonRequired);
+        }
+        self.record_participant_exit(event_id, user_id, ParticipantExit::Withdrawn, Some(reason)).await
+    }
+
+    /// Record that a participant never turned up
+    pub async fn mark_participant_no_show(&self, event_id: i64, user_id: i64, reason: Option<&str>) -> Result<ExitOutcome, WithdrawalError> {
+        let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+        self.record_participant_exit(event_id, user_id, ParticipantExit::NoShow, reason).await
+    }
+
+    async fn record_participant_exit(
+        &self,
+        event_id: i64,
+        user_id: i64,
+        exit: ParticipantExit,
+        reason: Option<&str>,
+    ) -> Result<ExitOutcome, WithdrawalError> {
+        let mut tx = self.pool.begin().await?;
+
+        let status: String = sqlx::query("SELECT status FROM event_participants WHERE event_id = ? AND user_id = ?")
+            .bind(event_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(WithdrawalError::NotEnrolled)?
+            .get("status");
+        if has_left(&status) {
+            return Err(WithdrawalError::AlreadyLeft { status });
+        }
+
+        if exit == ParticipantExit::NoShow {
+            let started: i64 = sqlx::query(
+                "SELECT COUNT(*) as count FROM sessions WHERE event_id = ? AND user_id = ? AND superseded_at IS NULL"
+            )
+            .bind(event_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .get("count");
+            if started > 0 {
+                return Err(WithdrawalError::AlreadyStarted);
+            }
+        }
+
+        let session_reason = match exit {
+            ParticipantExit::Withdrawn => "Participant withdrew",
+            ParticipantExit::NoShow => "Participant marked as no-show",
+        };
+        let open = sqlx::query(
+            "SELECT id, status FROM sessions WHERE event_id = ? AND user_id = ? AND status IN ('pending', 'active', 'paused')"
+        )
+        .bind(event_id)
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut terminated_sessions = Vec::with_capacity(open.len());
+        for session in open {
+            let session_id: i64 = session.get("id");
+            let from = SessionStatus::parse(&session.get::<String, _>("status")).unwrap_or(SessionStatus::Active);
+            sqlx::query(
+                "UPDATE sessions SET status = 'terminated', status_reason = ?,
+                        status_changed_at = CURRENT_TIMESTAMP, completed_at = CURRENT_TIMESTAMP
+                 WHERE id = ?"
+            )
+            .bind(session_reason)
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+            record_transition(&mut tx, session_id, from, SessionStatus::Terminated, Some(session_reason)).await?;
+            terminated_sessions.push(session_id);
+        }
+
+        // Appended so notes made at enrollment are kept
+        let note = match reason {
+            Some(reason) => format!("{}: {}", exit.label(), reason),
+            None => exit.label().to_string(),
+        };
+        sqlx::query(
+            r#"
+            UPDATE event_participants
+            SET status = ?1,
+                notes = CASE WHEN notes IS NULL OR notes = '' THEN ?2 ELSE notes || char(10) || ?2 END
+            WHERE event_id = ?3 AND user_id = ?4
+            "#
+        )
+        .bind(exit.as_str())
+        .bind(note)
+        .bind(event_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // An invitation printed before they left shouldn't still let them in
+        sqlx::query("DELETE FROM access_tokens WHERE event_id = ? AND user_id = ? AND used_at IS NULL")
+            .bind(event_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let promoted = promote_from_waitlist(&mut tx, event_id).await?;
+        tx.commit().await?;
+
+        Ok(ExitOutcome { status: exit.as_str().to_string(), terminated_sessions, promoted })
+    }
+}
@@ -0,0 +1,260 @@
+// Drawing Answers
+// Vector stroke capture for projective drawing questions (house-tree-person, DAP, ...).
+// Strokes keep their timestamps so the drawing can be replayed in the order it was made.
+
+use image::{ImageBuffer, ImageOutputFormat, Rgba};
+use serde::{Serialize, Deserialize};
+use std::io::Cursor;
+
+/// Canvas size limit, in either direction
+const MAX_CANVAS: u32 = 4096;
+const MAX_STROKE_WIDTH: f32 = 200.0;
+const MAX_POINTS_PER_STROKE: usize = 20_000;
+/// Brush stamps per segment; points are on the canvas, so this is never reached by real input
+const MAX_SEGMENT_STEPS: usize = 100_000;
+const DEFAULT_COLOR: &str = "#000000";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+    /// Milliseconds since the candidate first touched the canvas
+    pub t: i64,
+    #[serde(default)]
+    pub pressure: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stroke {
+    #[serde(default = "default_color")]
+    pub color: String,
+    pub width: f32,
+    /// True for strokes drawn with the eraser tool
+    #[serde(default)]
+    pub erase: bool,
+    pub points: Vec<Point>,
+}
+
+fn default_color() -> String {
+    DEFAULT_COLOR.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Drawing {
+    pub width: u32,
+    pub height: u32,
+    /// Strokes in the order they were drawn
+    pub strokes: Vec<Stroke>,
+}
+
+/// One stroke on the replay timeline
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub index: usize,
+    pub started_at: i64,
+    pub ended_at: i64,
+    /// Hesitation before this stroke, since the end of the previous one
+    pub pause_before: i64,
+    pub point_count: usize,
+    pub erase: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayTimeline {
+    pub duration_ms: i64,
+    pub stroke_count: usize,
+    pub erase_count: usize,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Drawing {
+    /// Reject drawings that can't be replayed faithfully
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 || self.width > MAX_CANVAS || self.height > MAX_CANVAS {
+            return Err(format!("Canvas must be between 1 and {} pixels wide and high", MAX_CANVAS));
+        }
+
+        let mut last_t = i64::MIN;
+        for (i, stroke) in self.strokes.iter().enumerate() {
+            if stroke.points.is_empty() {
+                return Err(format!("Stroke {} has no points", i + 1));
+            }
+            if stroke.points.len() > MAX_POINTS_PER_STROKE {
+                return Err(format!("Stroke {} has more than {} points", i + 1, MAX_POINTS_PER_STROKE));
+            }
+            if !(stroke.width > 0.0 && stroke.width <= MAX_STROKE_WIDTH) {
+                return Err(format!("Stroke {} must be between 0 and {} pixels wide", i + 1, MAX_STROKE_WIDTH));
+            }
+            parse_color(&stroke.color).ok_or_else(|| format!("Stroke {} has an invalid color '{}'", i + 1, stroke.color))?;
+
+            for point in &stroke.points {
+                if !(0.0..=self.width as f32).contains(&point.x) || !(0.0..=self.height as f32).contains(&point.y) {
+                    return Err(format!("Stroke {} has a point outside the canvas", i + 1));
+                }
+                if point.t < last_t {
+                    return Err(format!("Stroke {} goes back in time; strokes must be in drawing order", i + 1));
+                }
+                last_t = point.t;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn duration_ms(&self) -> i64 {
+        let first = self.strokes.first().and_then(|s| s.points.first()).map(|p| p.t);
+        let last = self.strokes.last().and_then(|s| s.points.last()).map(|p| p.t);
+        match (first, last) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0,
+        }
+    }
+
+    pub fn replay_timeline(&self) -> ReplayTimeline {
+        let mut previous_end: Option<i64> = None;
+
+        let steps: Vec<ReplayStep> = self.strokes.iter().enumerate().map(|(index, stroke)| {
+            let started_at = stroke.points.first().map(|p| p.t).unwrap_or(0);
+            let ended_at = stroke.points.last().map(|p| p.t).unwrap_or(started_at);
+            let step = ReplayStep {
+                index,
+                started_at,
+                ended_at,
+                pause_before: previous_end.map(|end| started_at - end).unwrap_or(0),
+                point_count: stroke.points.len(),
+                erase: stroke.erase,
+            };
+            previous_end = Some(ended_at);
+            step
+        }).collect();
+
+        ReplayTimeline {
+            duration_ms: self.duration_ms(),
+            stroke_count: steps.len(),
+            erase_count: steps.iter().filter(|s| s.erase).count(),
+            steps,
+        }
+    }
+
+    /// The drawing as it looked `at_ms` into the session; `None` gives the finished drawing
+    pub fn at(&self, at_ms: Option<i64>) -> Drawing {
+        let cutoff = match at_ms {
+            Some(ms) => self.strokes.first().and_then(|s| s.points.first()).map(|p| p.t + ms),
+            None => None,
+        };
+
+        let strokes = match cutoff {
+            None => self.strokes.clone(),
+            Some(cutoff) => self.strokes.iter()
+                .map(|s| Stroke {
+                    points: s.points.iter().filter(|p| p.t <= cutoff).cloned().collect(),
+                    ..s.clone()
+                })
+                .filter(|s| !s.points.is_empty())
+                .collect(),
+        };
+
+        Drawing { width: self.width, height: self.height, strokes }
+    }
+}
+
+fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn escape_attr(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
+
+/// SVG with one path per stroke, in drawing order. Eraser strokes paint the background colour.
+pub fn render_svg(drawing: &Drawing) -> String {
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}"><rect width="100%" height="100%" fill="#ffffff"/>"##,
+        w = drawing.width,
+        h = drawing.height,
+    );
+
+    for (i, stroke) in drawing.strokes.iter().enumerate() {
+        let color = if stroke.erase { "#ffffff" } else { stroke.color.as_str() };
+        let mut d = String::new();
+        for (j, p) in stroke.points.iter().enumerate() {
+            d.push_str(&format!("{}{:.1} {:.1}", if j == 0 { "M" } else { " L" }, p.x, p.y));
+        }
+        // A single tap still needs a visible dot
+        if stroke.points.len() == 1 {
+            d.push_str(" l0 0");
+        }
+        svg.push_str(&format!(
+            r#"<path data-stroke="{}" d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            i + 1,
+            d,
+            escape_attr(color),
+            stroke.width,
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// PNG of the drawing, `scale` times the canvas size
+pub fn render_png(drawing: &Drawing, scale: f32) -> Result<Vec<u8>, String> {
+    let scale = scale.clamp(0.1, 4.0);
+    let width = ((drawing.width as f32 * scale).round() as u32).clamp(1, MAX_CANVAS);
+    let height = ((drawing.height as f32 * scale).round() as u32).clamp(1, MAX_CANVAS);
+    let mut canvas: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+
+    for stroke in &drawing.strokes {
+        let [r, g, b] = if stroke.erase { [255, 255, 255] } else { parse_color(&stroke.color).unwrap_or([0, 0, 0]) };
+        let color = Rgba([r, g, b, 255]);
+        let radius = (stroke.width.min(MAX_STROKE_WIDTH) * scale / 2.0).max(0.5);
+
+        let points: Vec<(f32, f32)> = stroke.points.iter().map(|p| (p.x * scale, p.y * scale)).collect();
+        stamp(&mut canvas, points[0], radius, color);
+        for pair in points.windows(2) {
+            let (x0, y0) = pair[0];
+            let (x1, y1) = pair[1];
+            // Stamp the brush along the segment, close enough together to look continuous
+            let steps = ((x1 - x0).hypot(y1 - y0) / (radius / 2.0).max(0.5)).ceil().max(1.0) as usize;
+            let steps = steps.min(MAX_SEGMENT_STEPS);
+            for step in 1..=steps {
+                let t = step as f32 / steps as f32;
+                stamp(&mut canvas, (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t), radius, color);
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    canvas.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("PNG encoding failed: {}", e))?;
+    Ok(png)
+}
+
+/// Paint a filled disc, the brush tip
+fn stamp(canvas: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, (cx, cy): (f32, f32), radius: f32, color: Rgba<u8>) {
+    let (width, height) = canvas.dimensions();
+    let min_x = (cx - radius).floor().max(0.0) as u32;
+    let min_y = (cy - radius).floor().max(0.0) as u32;
+    let max_x = ((cx + radius).ceil().max(0.0) as u32).min(width.saturating_sub(1));
+    let max_y = ((cy + radius).ceil().max(0.0) as u32).min(height.saturating_sub(1));
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            if dx * dx + dy * dy <= radius * radius {
+                canvas.put_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,151 @@
+// Drawing Unit Tests
+
+#[cfg(test)]
+mod drawing_tests {
+    use crate::drawing::{render_png, render_svg, Drawing, Point, Stroke};
+    use crate::db::Database;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn stroke(points: &[(f32, f32, i64)]) -> Stroke {
+        Stroke {
+            color: "#000000".to_string(),
+            width: 4.0,
+            erase: false,
+            points: points.iter().map(|&(x, y, t)| Point { x, y, t, pressure: None }).collect(),
+        }
+    }
+
+    /// A roof drawn first, then a wall after a pause
+    fn house() -> Drawing {
+        Drawing {
+            width: 100,
+            height: 80,
+            strokes: vec![
+                stroke(&[(10.0, 40.0, 0), (50.0, 10.0, 300), (90.0, 40.0, 600)]),
+                stroke(&[(10.0, 40.0, 1600), (10.0, 75.0, 1900)]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_validation_keeps_drawing_order() {
+        assert!(house().validate().is_ok());
+
+        let mut backwards = house();
+        backwards.strokes.swap(0, 1);
+        assert!(backwards.validate().is_err());
+
+        let mut empty_stroke = house();
+        empty_stroke.strokes.push(Stroke { points: vec![], ..stroke(&[]) });
+        assert!(empty_stroke.validate().is_err());
+
+        let mut bad_color = house();
+        bad_color.strokes[0].color = "red\"/><script>".to_string();
+        assert!(bad_color.validate().is_err());
+
+        let mut off_canvas = house();
+        off_canvas.strokes[0].points[1].x = 1e30;
+        assert!(off_canvas.validate().is_err());
+
+        let mut huge_brush = house();
+        huge_brush.strokes[0].width = 1e30;
+        assert!(huge_brush.validate().is_err());
+    }
+
+    #[test]
+    fn test_replay_timeline() {
+        let timeline = house().replay_timeline();
+
+        assert_eq!(timeline.duration_ms, 1900);
+        assert_eq!(timeline.stroke_count, 2);
+        assert_eq!(timeline.steps[0].pause_before, 0);
+        assert_eq!(timeline.steps[1].started_at, 1600);
+        assert_eq!(timeline.steps[1].pause_before, 1000);
+
+        // Halfway through the roof, the wall doesn't exist yet
+        let partial = house().at(Some(400));
+        assert_eq!(partial.strokes.len(), 1);
+        assert_eq!(partial.strokes[0].points.len(), 2);
+        assert_eq!(house().at(None).strokes.len(), 2);
+    }
+
+    #[test]
+    fn test_render_svg_and_png() {
+        let svg = render_svg(&house());
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(svg.find(r#"data-stroke="1""#).unwrap() < svg.find(r#"data-stroke="2""#).unwrap());
+
+        let png = render_png(&house(), 2.0).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (200, 160));
+        // The wall runs down x = 10, the corner is blank
+        assert_eq!(image.get_pixel(20, 120).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(190, 150).0, [255, 255, 255, 255]);
+    }
+
+    #[tokio::test]
+    async fn test_drawing_answer_roundtrip() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let tool_id = db.create_tool("HTP", "projective", "clinical", "House-Tree-Person").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "House", 1, None).await.unwrap();
+        let q_id = db.create_question(sub_id, "Draw a house", "drawing", serde_json::json!({}), 1).await.unwrap();
+        let event_id = db.create_event("Drawing event", None, None).await.unwrap();
+        let session_id = db.create_session(event_id, "P-001", None, None).await.unwrap();
+
+        db.save_drawing_answer(session_id, q_id, &house()).await.unwrap();
+
+        // Saving again replaces the drawing
+        let mut redrawn = house();
+        redrawn.strokes.truncate(1);
+        db.save_drawing_answer(session_id, q_id, &redrawn).await.unwrap();
+
+        let answer = db.get_drawing_answer(session_id, q_id).await.unwrap().unwrap();
+        assert_eq!(answer.stroke_count, 1);
+        assert_eq!(answer.duration_ms, 600);
+        assert_eq!(answer.drawing.width, 100);
+        assert_eq!(answer.drawing.strokes[0].points[1].t, 300);
+
+        assert_eq!(db.get_session_drawing_question_ids(session_id).await.unwrap(), vec![q_id]);
+        assert!(db.get_drawing_answer(session_id, q_id + 1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_drawing_answer_outlives_live_question() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let tool_id = db.create_tool("HTP", "projective", "clinical", "House-Tree-Person").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "House", 1, None).await.unwrap();
+        let q_id = db.create_question(sub_id, "Draw a house", "drawing", serde_json::json!({}), 1).await.unwrap();
+        let event_id = db.create_event("Drawing event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        let session_id = db.create_session(event_id, "P-001", None, None).await.unwrap();
+
+        db.save_drawing_answer(session_id, q_id, &house()).await.unwrap();
+
+        // Deleting the live question leaves the delivered snapshot and the answer intact
+        db.delete_question(q_id).await.unwrap();
+        let question = db.get_event_question(event_id, q_id).await.unwrap().unwrap();
+        assert_eq!(question.question_type, "drawing");
+        assert!(db.get_drawing_answer(session_id, q_id).await.unwrap().is_some());
+
+        // Questions from tools outside the event are not found
+        let other_tool = db.create_tool("DAP", "projective", "clinical", "Draw-a-Person").await.unwrap();
+        let other_sub = db.create_subtest(other_tool, "Person", 1, None).await.unwrap();
+        let other_q = db.create_question(other_sub, "Draw a person", "drawing", serde_json::json!({}), 1).await.unwrap();
+        assert!(db.get_event_question(event_id, other_q).await.unwrap().is_none());
+    }
+}
@@ -11,6 +11,18 @@ mod tests;
 mod recording;
 mod api_server;
 mod sync;
+mod scoring;
+mod bundle;
+mod question_import;
+mod question_types;
+mod media;
+mod localization;
+mod drawing;
+mod rating;
+mod blueprint;
+mod candidate_import;
+mod qr;
+mod ranking;
 
 pub mod tools {
     pub use crate::commands::tools::*;
@@ -81,6 +93,18 @@ pub fn run() {
                     eprintln!("Failed to seed dummy results: {}", e);
                 }
 
+                // Open and close scheduled events as their windows pass
+                let scheduler = database.clone();
+                tauri::async_runtime::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                    loop {
+                        interval.tick().await;
+                        if let Err(e) = scheduler.apply_event_schedules(chrono::Utc::now()).await {
+                            eprintln!("Failed to apply event schedules: {}", e);
+                        }
+                    }
+                });
+
                 app.manage(database);
             });
             Ok(())
@@ -92,9 +116,22 @@ pub fn run() {
             commands::create_session,
             commands::auth::register_user,
             commands::auth::login_user,
+            commands::auth::login_with_token,
             commands::auth::update_avatar,
             commands::auth::get_user_profile,
+            commands::sessions::start_session,
+            commands::sessions::pause_session,
+            commands::sessions::resume_session,
+            commands::sessions::take_extra_break,
+            commands::sessions::complete_session,
+            commands::sessions::terminate_session,
+            commands::sessions::get_session_history,
             commands::events::reset_participant,
+            commands::events::get_retake_policy,
+            commands::events::set_retake_policy,
+            commands::events::get_participant_attempts,
+            commands::events::get_participant_accommodations,
+            commands::events::set_participant_accommodations,
             commands::dashboard::get_all_users,
             commands::dashboard::get_events,
             commands::dashboard::create_event,
@@ -110,8 +147,45 @@ pub fn run() {
             commands::tools::create_subtest,
             commands::tools::delete_subtest,
             commands::tools::create_question,
+            commands::tools::import_questions,
             commands::tools::delete_question,
             commands::tools::update_question,
+            commands::tools::publish_tool_version,
+            commands::tools::get_tool_versions,
+            commands::tools::set_event_tool_version,
+            commands::tools::get_event_tool_content,
+            commands::tools::score_event_tool,
+            commands::tools::serialize_answer,
+            commands::tools::export_tool_bundle,
+            commands::tools::import_tool_bundle,
+            commands::media::upload_media,
+            commands::media::get_media_asset,
+            commands::localization::set_tool_content_locale,
+            commands::localization::set_question_translation,
+            commands::localization::delete_question_translation,
+            commands::localization::set_subtest_translation,
+            commands::localization::delete_subtest_translation,
+            commands::localization::set_event_locale,
+            commands::localization::set_participant_locale,
+            commands::localization::get_translation_report,
+            commands::drawings::save_drawing_answer,
+            commands::drawings::get_drawing_answer,
+            commands::drawings::render_drawing,
+            commands::drawings::get_drawing_replay,
+            commands::rating::queue_manual_scoring,
+            commands::rating::get_rating_queue,
+            commands::rating::assign_rating_items,
+            commands::rating::submit_rating,
+            commands::rating::get_item_ratings,
+            commands::rating::finalize_manual_scores,
+            commands::rating::assign_second_rater,
+            commands::rating::get_adjudication_queue,
+            commands::rating::adjudicate_rating,
+            commands::rating::get_reliability_report,
+            commands::blueprint::get_event_blueprint,
+            commands::blueprint::set_event_blueprint,
+            commands::blueprint::get_next_step,
+            commands::blueprint::complete_step,
             commands::notifications::get_notifications,
             commands::notifications::mark_notification_read,
             commands::notifications::mark_all_notifications_read,
@@ -121,11 +195,37 @@ pub fn run() {
             commands::kraepelin::complete_kraepelin_session,
             commands::events::get_event_details,
             commands::events::get_event_participants,
+            commands::events::get_participant_progress,
+            commands::events::get_event_statistics,
             commands::events::enroll_candidate_to_event,
             commands::events::add_participant_to_event,
             commands::events::remove_participant_from_event,
+            commands::events::withdraw_participant,
+            commands::events::mark_participant_no_show,
+            commands::events::get_event_waitlist,
+            commands::events::leave_event_waitlist,
+            commands::events::set_event_enrollment_settings,
+            commands::events::get_event_schedule,
+            commands::events::set_event_schedule,
+            commands::events::set_event_status,
+            commands::events::clone_event,
+            commands::candidates::import_candidates,
+            commands::candidates::get_participant_profile,
+            commands::candidates::save_participant_profile,
+            commands::candidates::delete_participant_profile,
+            commands::access_tokens::issue_access_tokens,
+            commands::access_tokens::get_event_access_tokens,
+            commands::access_tokens::revoke_access_token,
+            commands::access_tokens::render_access_token_qr,
+            commands::ranking::get_ranking_config,
+            commands::ranking::set_ranking_config,
+            commands::ranking::get_event_ranking,
             commands::events::get_my_events,
             commands::events::generate_event_code_cmd,
+            commands::events::get_event_code_status,
+            commands::events::set_event_code_validity,
+            commands::events::revoke_event_code,
+            commands::events::get_event_code_history,
             commands::events::delete_events,
             surveillance::check_camera_permission,
             surveillance::capture_frame,
@@ -155,7 +255,13 @@ pub fn run() {
             commands::sync::get_server_url,
             commands::sync::test_server_connection,
             commands::sync::trigger_sync,
-            commands::sync::get_sync_queue_status
+            commands::sync::get_sync_queue_status,
+            // Session recovery (Candidate)
+            commands::resume::save_session_progress,
+            commands::resume::resume_candidate_session,
+            // Offline media (Candidate)
+            commands::media::predownload_event_media,
+            commands::media::get_cached_media_path
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,245 @@
+// Content Localization
+// Picks the language a candidate sees and overlays translations onto tool content
+
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::db::localization::{QuestionTranslation, SubtestTranslation, ToolTranslations};
+use crate::db::models::FullToolStructure;
+
+/// Language of tools created before localization (the original content is Indonesian)
+pub const DEFAULT_LOCALE: &str = "id";
+
+/// Option keys that belong to scoring and are never taken from a translation
+const SCORING_OPTION_KEYS: &[&str] = &["correct", "scale_key"];
+
+/// Lists a translation may replace, each with the key its originals are kept under
+const TRANSLATED_LISTS: &[(&str, &str)] = &[
+    ("choices", "choice_values"),
+    ("items", "item_values"),
+    ("statements", "statement_values"),
+];
+
+/// `en_US`, `EN-us` and `en-us` are the same locale
+pub fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_lowercase()
+}
+
+/// Locales to try, in order: the candidate's choice, then the event's, each followed by
+/// its base language (`en-us` -> `en`). The tool's own language always ends the chain.
+pub fn fallback_chain(preferences: &[Option<&str>], content_locale: &str) -> Vec<String> {
+    let content_locale = normalize_locale(content_locale);
+    let mut chain: Vec<String> = Vec::new();
+
+    for locale in preferences.iter().flatten().map(|l| normalize_locale(l)) {
+        if locale.is_empty() {
+            continue;
+        }
+        let base = locale.split('-').next().unwrap_or("").to_string();
+        for candidate in [locale, base] {
+            if !candidate.is_empty() && !chain.contains(&candidate) {
+                chain.push(candidate);
+            }
+        }
+    }
+
+    // Anything after the content language would never be reached
+    if let Some(pos) = chain.iter().position(|l| *l == content_locale) {
+        chain.truncate(pos);
+    }
+    chain.push(content_locale);
+    chain
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalizedToolContent {
+    /// The first locale of the chain, i.e. what the candidate asked for
+    pub locale: String,
+    pub fallback_chain: Vec<String>,
+    /// Questions and subtests that had to fall back past the requested locale
+    pub fallback_count: i64,
+    /// The candidate's accommodations ask for large print
+    #[serde(default)]
+    pub large_font: bool,
+    pub structure: FullToolStructure,
+}
+
+/// Overlay translations onto `structure`. Each question and subtest uses the first
+/// locale in `chain` that has a translation, otherwise its base content.
+pub fn localize_structure(
+    mut structure: FullToolStructure,
+    translations: &ToolTranslations,
+    chain: &[String],
+) -> LocalizedToolContent {
+    let questions: HashMap<(i64, &str), &QuestionTranslation> = translations.questions.iter()
+        .map(|t| ((t.question_id, t.locale.as_str()), t))
+        .collect();
+    let subtests: HashMap<(i64, &str), &SubtestTranslation> = translations.subtests.iter()
+        .map(|t| ((t.subtest_id, t.locale.as_str()), t))
+        .collect();
+
+    let requested = chain.first().cloned().unwrap_or_else(|| structure.tool.content_locale.clone());
+    let mut fallback_count = 0;
+
+    for full in structure.subtests.iter_mut() {
+        let subtest_id = full.subtest.id;
+        match first_match(chain, &structure.tool.content_locale, |l| subtests.get(&(subtest_id, l)).copied()) {
+            (Some(t), used) => {
+                full.subtest.instructions = t.instructions.clone();
+                fallback_count += (used != requested) as i64;
+            }
+            (None, used) => fallback_count += (used != requested) as i64,
+        }
+
+        for question in full.questions.iter_mut() {
+            let question_id = question.id;
+            match first_match(chain, &structure.tool.content_locale, |l| questions.get(&(question_id, l)).copied()) {
+                (Some(t), used) => {
+                    question.question_text = t.question_text.clone();
+                    question.options = merge_options(question.options.take(), t.options.as_ref());
+                    fallback_count += (used != requested) as i64;
+                }
+                (None, used) => fallback_count += (used != requested) as i64,
+            }
+        }
+    }
+
+    LocalizedToolContent {
+        locale: requested,
+        fallback_chain: chain.to_vec(),
+        fallback_count,
+        large_font: false,
+        structure,
+    }
+}
+
+/// Walk the chain until a translation is found or the content language is reached
+fn first_match<'a, T>(
+    chain: &'a [String],
+    content_locale: &'a str,
+    lookup: impl Fn(&str) -> Option<&'a T>,
+) -> (Option<&'a T>, &'a str) {
+    for locale in chain {
+        if locale == content_locale {
+            return (None, locale);
+        }
+        if let Some(t) = lookup(locale) {
+            return (Some(t), locale);
+        }
+    }
+    (None, content_locale)
+}
+
+/// Translated options replace display keys only. When choices, ranking items or most/least
+/// statements are translated the originals are kept as `choice_values`, `item_values` and
+/// `statement_values`, which is what answers are scored against.
+fn merge_options(base: Option<serde_json::Value>, translated: Option<&serde_json::Value>) -> Option<serde_json::Value> {
+    let (mut base, translated) = match (base, translated) {
+        (Some(serde_json::Value::Object(base)), Some(serde_json::Value::Object(t))) => (base, t),
+        (base, _) => return base,
+    };
+
+    for (key, values_key) in TRANSLATED_LISTS {
+        if translated.contains_key(*key) {
+            if let Some(originals) = base.get(*key).cloned() {
+                base.insert(values_key.to_string(), originals);
+            }
+        }
+    }
+    for (key, value) in translated {
+        if !SCORING_OPTION_KEYS.contains(&key.as_str()) {
+            base.insert(key.clone(), value.clone());
+        }
+    }
+
+    Some(serde_json::Value::Object(base))
+}
+
+/// Translated choices, ranking items and statements must line up one-to-one with the originals
+pub fn validate_translated_options(base: Option<&serde_json::Value>, translated: Option<&serde_json::Value>) -> Result<(), String> {
+    for (key, _) in TRANSLATED_LISTS {
+        let count = |v: Option<&serde_json::Value>| v
+            .and_then(|o| o.get(*key))
+            .and_then(|c| c.as_array())
+            .map(|c| c.len());
+
+        match (count(base), count(translated)) {
+            (_, None) => {}
+            (Some(b), Some(t)) if b == t => {}
+            (b, Some(t)) => return Err(format!("Translation has {} {} but the question has {}", t, key, b.unwrap_or(0))),
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocaleCompleteness {
+    pub locale: String,
+    pub questions_translated: i64,
+    pub subtests_translated: i64,
+    pub percent_complete: f64,
+    pub missing_question_ids: Vec<i64>,
+    pub missing_subtest_ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranslationReport {
+    pub tool_id: i64,
+    pub content_locale: String,
+    pub total_questions: i64,
+    pub total_subtests: i64,
+    pub locales: Vec<LocaleCompleteness>,
+}
+
+/// Completeness per locale. Without an explicit list, every locale that has at least one translation is reported.
+pub fn translation_report(
+    structure: &FullToolStructure,
+    translations: &ToolTranslations,
+    locales: Option<&[String]>,
+) -> TranslationReport {
+    let locales: BTreeSet<String> = match locales {
+        Some(list) => list.iter().map(|l| normalize_locale(l)).collect(),
+        None => translations.questions.iter().map(|t| t.locale.clone())
+            .chain(translations.subtests.iter().map(|t| t.locale.clone()))
+            .collect(),
+    };
+
+    let question_ids: Vec<i64> = structure.subtests.iter()
+        .flat_map(|s| s.questions.iter().map(|q| q.id))
+        .collect();
+    let subtest_ids: Vec<i64> = structure.subtests.iter().map(|s| s.subtest.id).collect();
+    let total = (question_ids.len() + subtest_ids.len()) as f64;
+
+    let report_locales = locales.into_iter()
+        .filter(|l| *l != structure.tool.content_locale)
+        .map(|locale| {
+            let missing_question_ids: Vec<i64> = question_ids.iter().copied()
+                .filter(|id| !translations.questions.iter().any(|t| t.question_id == *id && t.locale == locale))
+                .collect();
+            let missing_subtest_ids: Vec<i64> = subtest_ids.iter().copied()
+                .filter(|id| !translations.subtests.iter().any(|t| t.subtest_id == *id && t.locale == locale))
+                .collect();
+
+            let translated = total - (missing_question_ids.len() + missing_subtest_ids.len()) as f64;
+            LocaleCompleteness {
+                questions_translated: (question_ids.len() - missing_question_ids.len()) as i64,
+                subtests_translated: (subtest_ids.len() - missing_subtest_ids.len()) as i64,
+                percent_complete: if total > 0.0 { (translated / total * 1000.0).round() / 10.0 } else { 100.0 },
+                missing_question_ids,
+                missing_subtest_ids,
+                locale,
+            }
+        })
+        .collect();
+
+    TranslationReport {
+        tool_id: structure.tool.id,
+        content_locale: structure.tool.content_locale.clone(),
+        total_questions: question_ids.len() as i64,
+        total_subtests: subtest_ids.len() as i64,
+        locales: report_locales,
+    }
+}
+
+#[cfg(test)]
+mod tests;
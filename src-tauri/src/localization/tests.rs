@@ -0,0 +1,152 @@
+// Content Localization Unit Tests
+
+#[cfg(test)]
+mod localization_tests {
+    use crate::localization::{fallback_chain, translation_report, validate_translated_options};
+    use crate::db::Database;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> Database {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .unwrap();
+
+        Database::new(pool)
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        // Candidate choice, its base language, then the event language, then the tool's own
+        assert_eq!(fallback_chain(&[Some("en_US"), Some("id")], "id"), vec!["en-us", "en", "id"]);
+        assert_eq!(fallback_chain(&[None, Some("en")], "id"), vec!["en", "id"]);
+        // Nothing after the content language is ever used
+        assert_eq!(fallback_chain(&[Some("id"), Some("en")], "id"), vec!["id"]);
+        assert_eq!(fallback_chain(&[None, None], "en"), vec!["en"]);
+    }
+
+    #[test]
+    fn test_translated_choices_must_line_up() {
+        let base = serde_json::json!({"choices": ["Ya", "Tidak"], "correct": "Ya"});
+        assert!(validate_translated_options(Some(&base), Some(&serde_json::json!({"choices": ["Yes", "No"]}))).is_ok());
+        assert!(validate_translated_options(Some(&base), Some(&serde_json::json!({"choices": ["Yes"]}))).is_err());
+        assert!(validate_translated_options(Some(&base), None).is_ok());
+
+        let ranking = serde_json::json!({"items": ["Rumah", "Pohon", "Orang"]});
+        assert!(validate_translated_options(Some(&ranking), Some(&serde_json::json!({"items": ["House", "Tree", "Person"]}))).is_ok());
+        assert!(validate_translated_options(Some(&ranking), Some(&serde_json::json!({"items": ["House", "Tree"]}))).is_err());
+
+        let most_least = serde_json::json!({"statements": ["Berani", "Tenang"]});
+        assert!(validate_translated_options(Some(&most_least), Some(&serde_json::json!({"statements": ["Bold", "Calm", "Kind"]}))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_translated_answers_resolve_to_originals() {
+        use crate::question_types::{is_correct, parse_answer, AnswerValue, QuestionType};
+
+        let db = setup_test_db().await;
+
+        let tool_id = db.create_tool("Prefs", "choice", "personality", "Translated lists").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Main", 1, None).await.unwrap();
+        let rank = db.create_question(sub_id, "Urutkan", "ranking", serde_json::json!({"items": ["Rumah", "Pohon", "Orang"], "correct": ["Pohon", "Rumah", "Orang"]}), 1).await.unwrap();
+        let pick = db.create_question(sub_id, "Pilih", "most_least", serde_json::json!({"statements": ["Berani", "Tenang", "Ramah"], "scale_key": {"Berani": "D", "Tenang": "S"}}), 2).await.unwrap();
+        db.upsert_question_translation(rank, "en", "Put in order", Some(serde_json::json!({"items": ["House", "Tree", "Person"]}))).await.unwrap();
+        db.upsert_question_translation(pick, "en", "Pick", Some(serde_json::json!({"statements": ["Bold", "Calm", "Kind"]}))).await.unwrap();
+
+        let event_id = db.create_event("Translated Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.unwrap();
+        let chain = fallback_chain(&[Some("en")], &snapshot.structure.tool.content_locale);
+        let content = snapshot.into_localized_candidate_content(&chain);
+        let questions = &content.structure.subtests[0].questions;
+
+        let ranking = questions[0].options.as_ref().unwrap();
+        assert_eq!(ranking["items"], serde_json::json!(["House", "Tree", "Person"]));
+        assert_eq!(ranking["item_values"], serde_json::json!(["Rumah", "Pohon", "Orang"]));
+        let order = parse_answer(QuestionType::Ranking, Some(ranking), r#"["Tree","House","Person"]"#).unwrap();
+        assert_eq!(order, AnswerValue::Ranking(vec!["Pohon".to_string(), "Rumah".to_string(), "Orang".to_string()]));
+        assert!(is_correct(QuestionType::Ranking, Some(ranking), r#"["Pohon","Rumah","Orang"]"#, r#"["Tree","House","Person"]"#));
+
+        let statements = questions[1].options.as_ref().unwrap();
+        assert_eq!(statements["statement_values"], serde_json::json!(["Berani", "Tenang", "Ramah"]));
+        let answer = parse_answer(QuestionType::MostLeast, Some(statements), r#"{"most":"Bold","least":"Calm"}"#).unwrap();
+        assert_eq!(answer, AnswerValue::MostLeast { most: "Berani".to_string(), least: "Tenang".to_string() });
+        assert!(parse_answer(QuestionType::MostLeast, Some(statements), r#"{"most":"Bold","least":"Loud"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_event_content_is_localized_from_snapshot() {
+        let db = setup_test_db().await;
+
+        let tool_id = db.create_tool("IST", "choice", "cognitive", "Localization test").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "SE", 1, None).await.unwrap();
+        let q1 = db.create_question(sub_id, "Pilih yang benar", "multiple_choice", serde_json::json!({"choices": ["Ya", "Tidak"], "correct": "Ya"}), 1).await.unwrap();
+        let q2 = db.create_question(sub_id, "Soal kedua", "multiple_choice", serde_json::json!({"choices": ["A", "B"], "correct": "B"}), 2).await.unwrap();
+
+        db.upsert_question_translation(q1, "en", "Pick the right one", Some(serde_json::json!({"choices": ["Yes", "No"], "correct": "Yes"}))).await.unwrap();
+        db.upsert_subtest_translation(sub_id, "EN", serde_json::json!({"text": "Read carefully"})).await.unwrap();
+
+        let event_id = db.create_event("Bilingual Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        db.set_event_locale(event_id, Some("en-GB")).await.unwrap();
+
+        // Translations added after publishing don't leak into the frozen package
+        db.upsert_question_translation(q2, "en", "Second question", None).await.unwrap();
+
+        let (participant, event) = db.get_locale_preferences(event_id, None).await.unwrap();
+        assert_eq!(participant, None);
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.unwrap();
+        let chain = fallback_chain(&[participant.as_deref(), event.as_deref()], &snapshot.structure.tool.content_locale);
+        assert_eq!(chain, vec!["en-gb", "en", "id"]);
+
+        let content = snapshot.into_localized_candidate_content(&chain);
+        let subtest = &content.structure.subtests[0];
+        assert_eq!(subtest.subtest.instructions["text"], "Read carefully");
+
+        let first = &subtest.questions[0];
+        assert_eq!(first.question_text, "Pick the right one");
+        let options = first.options.as_ref().unwrap();
+        assert_eq!(options["choices"], serde_json::json!(["Yes", "No"]));
+        assert_eq!(options["choice_values"], serde_json::json!(["Ya", "Tidak"]));
+        assert!(options.get("correct").is_none());
+
+        assert_eq!(subtest.questions[1].question_text, "Soal kedua");
+        // Everything fell back from en-gb: one subtest and two questions
+        assert_eq!(content.fallback_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_translation_report() {
+        let db = setup_test_db().await;
+
+        let tool_id = db.create_tool("DISC", "choice", "personality", "Report test").await.unwrap();
+        db.set_tool_content_locale(tool_id, "en").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Main", 1, None).await.unwrap();
+        let q1 = db.create_question(sub_id, "Bold", "multiple_choice", serde_json::json!({"choices": ["Most", "Least"]}), 1).await.unwrap();
+        let q2 = db.create_question(sub_id, "Calm", "multiple_choice", serde_json::json!({"choices": ["Most", "Least"]}), 2).await.unwrap();
+        db.upsert_question_translation(q1, "id", "Berani", None).await.unwrap();
+
+        let structure = db.get_full_tool_structure(tool_id).await.unwrap();
+        let translations = db.get_tool_translations(tool_id).await.unwrap();
+
+        let report = translation_report(&structure, &translations, Some(&["id".to_string(), "en".to_string(), "jv".to_string()]));
+        assert_eq!(report.content_locale, "en");
+        assert_eq!(report.total_questions, 2);
+
+        let locales: Vec<&str> = report.locales.iter().map(|l| l.locale.as_str()).collect();
+        assert_eq!(locales, vec!["id", "jv"]);
+
+        let indonesian = &report.locales[0];
+        assert_eq!(indonesian.questions_translated, 1);
+        assert_eq!(indonesian.missing_question_ids, vec![q2]);
+        assert_eq!(indonesian.missing_subtest_ids, vec![sub_id]);
+        assert!((indonesian.percent_complete - 33.3).abs() < 0.01);
+        assert_eq!(report.locales[1].percent_complete, 0.0);
+    }
+}
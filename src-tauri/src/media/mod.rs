@@ -0,0 +1,104 @@
+// Media Asset Store
+// Content-addressed storage for question media: a file is named by the SHA-256 of its bytes,
+// so identical uploads are stored once and a URL never changes meaning
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Route prefix the admin API serves assets under. Question `media_url`s point here.
+pub const MEDIA_ROUTE_PREFIX: &str = "/api/media/";
+
+/// Files never change for a given hash, so clients may cache them indefinitely
+pub const CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+#[derive(Debug, Clone)]
+pub struct StoredMedia {
+    pub content_hash: String,
+    pub original_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub storage_path: PathBuf,
+}
+
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+pub fn media_url(hash: &str) -> String {
+    format!("{}{}", MEDIA_ROUTE_PREFIX, hash)
+}
+
+/// Hash referenced by a `media_url`, if it points into the store
+pub fn hash_from_url(url: &str) -> Option<&str> {
+    url.strip_prefix(MEDIA_ROUTE_PREFIX).filter(|h| is_valid_hash(h))
+}
+
+/// Also guards the serving route against path tricks, since the hash becomes a file name
+pub fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+pub fn mime_type_for(file_name: &str) -> &'static str {
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Local file behind a `media_url`. Only store URLs have one; other values are never read from disk.
+pub fn local_path(media_dir: &Path, url: &str) -> Option<PathBuf> {
+    hash_from_url(url).map(|hash| media_dir.join(hash))
+}
+
+/// Question media either points into the store or at an external http(s) address
+pub fn is_supported_url(url: &str) -> bool {
+    hash_from_url(url).is_some() || url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Write `data` into the store. A file that's already there is left untouched.
+pub fn store_bytes(media_dir: &Path, original_name: &str, data: &[u8]) -> Result<StoredMedia, std::io::Error> {
+    let hash = content_hash(data);
+    let storage_path = media_dir.join(&hash);
+
+    if !storage_path.is_file() {
+        std::fs::create_dir_all(media_dir)?;
+        // Write under a temporary name first so a crash can't leave a truncated file behind a valid hash
+        let partial = media_dir.join(format!("{}.partial", hash));
+        std::fs::write(&partial, data)?;
+        std::fs::rename(&partial, &storage_path)?;
+    }
+
+    Ok(StoredMedia {
+        content_hash: hash,
+        original_name: original_name.to_string(),
+        mime_type: mime_type_for(original_name).to_string(),
+        size_bytes: data.len() as i64,
+        storage_path,
+    })
+}
+
+pub fn store_file(media_dir: &Path, source: &Path) -> Result<StoredMedia, std::io::Error> {
+    let data = std::fs::read(source)?;
+    let name = source.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("media");
+    store_bytes(media_dir, name, &data)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,77 @@
+// Media Store Unit Tests
+
+#[cfg(test)]
+mod media_tests {
+    use crate::media::{self, store_bytes, hash_from_url, local_path};
+    use crate::db::Database;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[test]
+    fn test_store_is_content_addressed() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = store_bytes(dir.path(), "figure.PNG", b"pixels").unwrap();
+        let second = store_bytes(dir.path(), "copy.png", b"pixels").unwrap();
+
+        assert_eq!(first.content_hash, second.content_hash);
+        assert_eq!(first.content_hash.len(), 64);
+        assert_eq!(first.mime_type, "image/png");
+        assert_eq!(first.storage_path, dir.path().join(&first.content_hash));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_media_urls() {
+        let hash = media::content_hash(b"pixels");
+        let url = media::media_url(&hash);
+
+        assert_eq!(hash_from_url(&url), Some(hash.as_str()));
+        assert_eq!(hash_from_url("/api/media/../../etc/passwd"), None);
+        assert_eq!(hash_from_url("https://example.com/figure.png"), None);
+
+        let dir = std::path::Path::new("/data/media");
+        assert_eq!(local_path(dir, &url), Some(dir.join(&hash)));
+        assert_eq!(local_path(dir, "/tmp/figure.png"), None);
+
+        assert!(media::is_supported_url(&url));
+        assert!(media::is_supported_url("https://example.com/figure.png"));
+        assert!(!media::is_supported_url("/tmp/figure.png"));
+        assert!(!media::is_supported_url("file:///etc/passwd"));
+    }
+
+    #[tokio::test]
+    async fn test_event_media_assets_follow_packages() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let dir = tempfile::tempdir().unwrap();
+        let stored = store_bytes(dir.path(), "matrix.png", b"matrix").unwrap();
+        let asset = db.register_media_asset(&stored).await.unwrap();
+
+        // Registering the same content again returns the same asset
+        let again = db.register_media_asset(&stored).await.unwrap();
+        assert_eq!(asset.id, again.id);
+
+        let tool_id = db.create_tool("Matrices", "choice", "cognitive", "Media test").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Set A", 1, None).await.unwrap();
+        let q_id = db.create_question(sub_id, "Complete the pattern", "multiple_choice", serde_json::json!({"choices": ["1", "2"]}), 1).await.unwrap();
+        sqlx::query("UPDATE questions SET media_url = ? WHERE id = ?")
+            .bind(asset.url())
+            .bind(q_id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let event_id = db.create_event("Media Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+
+        let assets = db.get_event_media_assets(event_id).await.unwrap();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].content_hash, stored.content_hash);
+    }
+}
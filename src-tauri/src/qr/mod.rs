@@ -0,0 +1,72 @@
+// QR Codes
+// Printable QR codes for participant access tokens. The qrcode crate does the encoding;
+// rendering stays here so PNG output goes through the same image version as the rest of the app.
+
+use image::{ImageBuffer, ImageOutputFormat, Luma};
+use qrcode::{Color, EcLevel, QrCode};
+use std::io::Cursor;
+
+/// Light modules around the code, as the QR spec requires for reliable scanning
+const QUIET_ZONE: usize = 4;
+/// Pixels per module for PNG output
+const DEFAULT_SCALE: u32 = 8;
+const MAX_SCALE: u32 = 32;
+
+/// Modules of the code as rows of dark (true) / light (false), quiet zone included
+fn modules(data: &str) -> Result<Vec<Vec<bool>>, String> {
+    // Medium error correction survives a crease or smudge on a printed invitation
+    let code = QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)
+        .map_err(|e| format!("Could not encode QR code: {}", e))?;
+    let width = code.width();
+    let colors = code.to_colors();
+    let size = width + QUIET_ZONE * 2;
+
+    Ok((0..size).map(|y| {
+        (0..size).map(|x| {
+            let inside = (QUIET_ZONE..QUIET_ZONE + width).contains(&x) && (QUIET_ZONE..QUIET_ZONE + width).contains(&y);
+            inside && colors[(y - QUIET_ZONE) * width + (x - QUIET_ZONE)] == Color::Dark
+        }).collect()
+    }).collect())
+}
+
+/// Scalable SVG, one unit per module; size it with CSS when printing
+pub fn render_svg(data: &str) -> Result<String, String> {
+    let rows = modules(data)?;
+    let size = rows.len();
+
+    // One path of 1x1 squares keeps the markup small enough to inline in a sheet
+    let mut path = String::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, dark) in row.iter().enumerate() {
+            if *dark {
+                path.push_str(&format!("M{} {}h1v1h-1z", x, y));
+            }
+        }
+    }
+
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" shape-rendering=\"crispEdges\">\
+         <rect width=\"{size}\" height=\"{size}\" fill=\"#ffffff\"/><path d=\"{path}\" fill=\"#000000\"/></svg>",
+        size = size,
+        path = path,
+    ))
+}
+
+/// Grayscale PNG, `scale` pixels per module
+pub fn render_png(data: &str, scale: Option<u32>) -> Result<Vec<u8>, String> {
+    let rows = modules(data)?;
+    let scale = scale.unwrap_or(DEFAULT_SCALE).clamp(1, MAX_SCALE);
+    let size = rows.len() as u32 * scale;
+
+    let image: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_fn(size, size, |x, y| {
+        if rows[(y / scale) as usize][(x / scale) as usize] { Luma([0]) } else { Luma([255]) }
+    });
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("PNG encoding failed: {}", e))?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,37 @@
+// QR Code Unit Tests
+
+#[cfg(test)]
+mod qr_tests {
+    use crate::qr::{render_png, render_svg};
+
+    #[test]
+    fn test_svg_is_square_with_quiet_zone() {
+        let svg = render_svg("ABCD-EFGH-JKLM-NPQR").unwrap();
+        assert!(svg.starts_with("<svg"));
+
+        let size: usize = svg.split("viewBox=\"0 0 ").nth(1).unwrap()
+            .split(' ').next().unwrap()
+            .parse().unwrap();
+        assert!(size >= 21 + 8);
+        // Nothing is drawn in the quiet zone
+        assert!(!svg.contains("M0 ") && !svg.contains("M3 "));
+        assert!(svg.contains("h1v1h-1z"));
+    }
+
+    #[test]
+    fn test_png_scales_per_module() {
+        let small = image::load_from_memory(&render_png("ABCD-EFGH-JKLM-NPQR", Some(1)).unwrap()).unwrap();
+        let large = image::load_from_memory(&render_png("ABCD-EFGH-JKLM-NPQR", Some(4)).unwrap()).unwrap();
+        assert_eq!(large.width(), small.width() * 4);
+        assert_eq!(small.width(), small.height());
+
+        // Out of range scales are clamped rather than producing huge images
+        let clamped = image::load_from_memory(&render_png("ABCD", Some(1000)).unwrap()).unwrap();
+        assert!(clamped.width() <= 32 * (21 + 8 + 4 * 10));
+    }
+
+    #[test]
+    fn test_oversized_data_is_an_error() {
+        assert!(render_svg(&"A".repeat(10_000)).is_err());
+    }
+}
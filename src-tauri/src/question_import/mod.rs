@@ -0,0 +1,365 @@
+// Bulk Question Import
+// Reads question rows from a CSV or XLSX sheet and validates them before anything is written
+
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+
+use crate::media;
+use crate::question_types::{self, QuestionType};
+
+/// Columns recognised in the header row (case-insensitive). Only `text` and `type` are required.
+pub const COLUMNS: &[&str] = &["text", "type", "choices", "correct_answer", "scale_key", "media"];
+
+/// Separator between choices (and scale key entries) inside a cell
+const LIST_SEPARATOR: char = '|';
+
+#[derive(Debug)]
+pub enum ImportError {
+    IoError(std::io::Error),
+    CsvError(csv::Error),
+    SpreadsheetError(String),
+    MissingColumn(String),
+    UnsupportedFormat(String),
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(err: std::io::Error) -> Self {
+        ImportError::IoError(err)
+    }
+}
+
+impl From<csv::Error> for ImportError {
+    fn from(err: csv::Error) -> Self {
+        ImportError::CsvError(err)
+    }
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::IoError(e) => write!(f, "File error: {}", e),
+            ImportError::CsvError(e) => write!(f, "Invalid CSV: {}", e),
+            ImportError::SpreadsheetError(e) => write!(f, "Invalid spreadsheet: {}", e),
+            ImportError::MissingColumn(c) => write!(f, "Missing required column '{}'", c),
+            ImportError::UnsupportedFormat(ext) => write!(f, "Unsupported file type '{}', use .csv or .xlsx", ext),
+        }
+    }
+}
+
+/// One raw spreadsheet row. `row_number` is the 1-based line in the sheet, header included.
+#[derive(Debug, Clone, Default)]
+pub struct ImportRow {
+    pub row_number: usize,
+    pub text: String,
+    pub question_type: String,
+    pub choices: String,
+    pub correct_answer: String,
+    pub scale_key: String,
+    pub media: String,
+}
+
+/// A validated row, ready for insertion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewQuestion {
+    pub text: String,
+    pub question_type: String,
+    pub options: serde_json::Value,
+    pub media_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowError {
+    pub row: usize,
+    pub column: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuestionImportReport {
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub errors: Vec<RowError>,
+    /// Empty unless every row was valid and the import was committed
+    pub created_ids: Vec<i64>,
+}
+
+pub fn read_rows(path: &Path) -> Result<Vec<ImportRow>, ImportError> {
+    rows_from_table(read_table(path)?)
+}
+
+/// All cells of a CSV or spreadsheet file, header row included
+pub(crate) fn read_table(path: &Path) -> Result<Vec<Vec<String>>, ImportError> {
+    let extension = path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "csv" => read_csv(path),
+        "xlsx" | "xls" | "ods" => read_spreadsheet(path),
+        other => Err(ImportError::UnsupportedFormat(other.to_string())),
+    }
+}
+
+fn read_csv(path: &Path) -> Result<Vec<Vec<String>>, ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)?;
+
+    let mut table = Vec::new();
+    for record in reader.records() {
+        table.push(record?.iter().map(|c| c.to_string()).collect());
+    }
+    Ok(table)
+}
+
+/// First worksheet of an Excel/ODS workbook
+fn read_spreadsheet(path: &Path) -> Result<Vec<Vec<String>>, ImportError> {
+    use calamine::{open_workbook_auto, Reader};
+
+    let mut workbook = open_workbook_auto(path)
+        .map_err(|e| ImportError::SpreadsheetError(e.to_string()))?;
+    let range = workbook.worksheet_range_at(0)
+        .ok_or_else(|| ImportError::SpreadsheetError("Workbook has no sheets".to_string()))?
+        .map_err(|e| ImportError::SpreadsheetError(e.to_string()))?;
+
+    Ok(range.rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect())
+}
+
+fn rows_from_table(table: Vec<Vec<String>>) -> Result<Vec<ImportRow>, ImportError> {
+    let mut lines = table.into_iter();
+    let header: Vec<String> = lines.next()
+        .unwrap_or_default()
+        .iter()
+        .map(|h| h.trim().to_lowercase().replace(' ', "_"))
+        .collect();
+
+    let column = |name: &str| header.iter().position(|h| h == name);
+    for required in ["text", "type"] {
+        if column(required).is_none() {
+            return Err(ImportError::MissingColumn(required.to_string()));
+        }
+    }
+    let indexes: Vec<Option<usize>> = COLUMNS.iter().map(|c| column(c)).collect();
+
+    let mut rows = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let cell = |col: usize| -> String {
+            indexes[col]
+                .and_then(|idx| line.get(idx))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        // Skip fully blank lines, common at the end of exported sheets
+        if line.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+
+        rows.push(ImportRow {
+            row_number: i + 2,
+            text: cell(0),
+            question_type: cell(1).to_lowercase(),
+            choices: cell(2),
+            correct_answer: cell(3),
+            scale_key: cell(4),
+            media: cell(5),
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Validate every row, collecting all problems instead of stopping at the first one
+pub fn validate_rows(rows: &[ImportRow]) -> (Vec<NewQuestion>, Vec<RowError>) {
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+
+    for row in rows {
+        match validate_row(row) {
+            Ok(question) => valid.push(question),
+            Err(mut row_errors) => errors.append(&mut row_errors),
+        }
+    }
+
+    (valid, errors)
+}
+
+fn validate_row(row: &ImportRow) -> Result<NewQuestion, Vec<RowError>> {
+    let mut errors = Vec::new();
+    let mut error = |column: &str, message: String| errors.push(RowError {
+        row: row.row_number,
+        column: column.to_string(),
+        message,
+    });
+
+    if row.text.is_empty() {
+        error("text", "Question text is required".to_string());
+    }
+
+    let mut choices = split_list(&row.choices);
+
+    let question_type = QuestionType::parse(&row.question_type);
+
+    match question_type {
+        Some(QuestionType::MultipleChoice) => {
+            if choices.len() < 2 {
+                error("choices", "Multiple choice questions need at least 2 choices".to_string());
+            }
+        }
+        Some(QuestionType::TrueFalse) => {
+            if choices.is_empty() {
+                choices = vec!["True".to_string(), "False".to_string()];
+            } else if choices.len() != 2 {
+                error("choices", "True/false questions need exactly 2 choices".to_string());
+            }
+        }
+        Some(QuestionType::Pair) => {
+            if choices.len() != 2 {
+                error("choices", "Pair questions need exactly 2 statements".to_string());
+            }
+        }
+        Some(QuestionType::Text | QuestionType::Drawing | QuestionType::KraepelinColumn) => {
+            if !choices.is_empty() {
+                error("choices", format!("'{}' questions don't take choices", row.question_type));
+            }
+            if !row.correct_answer.is_empty() {
+                error("correct_answer", format!("'{}' questions can't be auto-scored", row.question_type));
+            }
+        }
+        Some(QuestionType::Likert | QuestionType::MostLeast) => {
+            if !row.correct_answer.is_empty() {
+                error("correct_answer", format!("'{}' questions are scored by scale, not by a correct answer", row.question_type));
+            }
+        }
+        Some(QuestionType::Ranking) => {}
+        Some(QuestionType::Numeric) => {
+            if !choices.is_empty() {
+                error("choices", "Numeric questions don't take choices".to_string());
+            }
+        }
+        None if row.question_type.is_empty() => error("type", "Question type is required".to_string()),
+        None => error("type", format!("Unknown question type '{}'", row.question_type)),
+    }
+
+    // For ranking the correct answer is an order (`B|A|C`), checked by the option schema
+    let single_choice = matches!(
+        question_type,
+        Some(QuestionType::MultipleChoice | QuestionType::TrueFalse | QuestionType::Pair)
+    );
+    if single_choice && !row.correct_answer.is_empty() && !choices.contains(&row.correct_answer) {
+        error("correct_answer", format!("'{}' is not one of the choices", row.correct_answer));
+    }
+
+    let scale_key = match parse_scale_key(&row.scale_key) {
+        Ok(key) => key,
+        Err(message) => {
+            error("scale_key", message);
+            None
+        }
+    };
+
+    if !row.media.is_empty() && !media::is_supported_url(&row.media) {
+        error("media", format!("'{}' is not a stored media URL or an http(s) address", row.media));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Unknown types were rejected above
+    let question_type = question_type.unwrap_or(QuestionType::MultipleChoice);
+    let options = build_options(question_type, choices, &row.correct_answer, scale_key);
+
+    if let Err(message) = question_types::validate_options(question_type, Some(&options)) {
+        return Err(vec![RowError {
+            row: row.row_number,
+            column: "choices".to_string(),
+            message,
+        }]);
+    }
+
+    Ok(NewQuestion {
+        text: row.text.clone(),
+        question_type: row.question_type.clone(),
+        options,
+        media_url: if row.media.is_empty() { None } else { Some(row.media.clone()) },
+    })
+}
+
+/// Lay the cells out in the option schema of the question type
+fn build_options(
+    question_type: QuestionType,
+    choices: Vec<String>,
+    correct_answer: &str,
+    scale_key: Option<serde_json::Value>,
+) -> serde_json::Value {
+    let mut options = match question_type {
+        // Choices are the scale labels; without them it's a plain 1-5 scale
+        QuestionType::Likert => {
+            let mut options = serde_json::json!({ "min": 1, "max": 5 });
+            if !choices.is_empty() {
+                options["max"] = serde_json::json!(choices.len());
+                options["labels"] = serde_json::json!(choices);
+            }
+            options
+        }
+        QuestionType::Ranking => serde_json::json!({
+            "items": choices,
+            "correct": if correct_answer.is_empty() { serde_json::Value::Null } else { serde_json::json!(split_list(correct_answer)) },
+        }),
+        QuestionType::MostLeast => serde_json::json!({ "statements": choices }),
+        QuestionType::Numeric => serde_json::json!({ "correct": correct_answer }),
+        _ => serde_json::json!({
+            "choices": choices,
+            "correct": correct_answer,
+        }),
+    };
+
+    if let Some(key) = scale_key {
+        options["scale_key"] = key;
+    }
+    options
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split(LIST_SEPARATOR)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Scale keys map answers to the scale they count towards. Accepted forms:
+/// a JSON value, `A:ach|B:def` (choice -> scale), or a single scale code.
+fn parse_scale_key(value: &str) -> Result<Option<serde_json::Value>, String> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    if value.starts_with('{') || value.starts_with('[') {
+        return serde_json::from_str(value)
+            .map(Some)
+            .map_err(|e| format!("Invalid JSON scale key: {}", e));
+    }
+
+    if !value.contains(':') {
+        return Ok(Some(serde_json::Value::String(value.to_string())));
+    }
+
+    let mut map = serde_json::Map::new();
+    for entry in split_list(value) {
+        match entry.split_once(':') {
+            Some((choice, scale)) if !choice.trim().is_empty() && !scale.trim().is_empty() => {
+                map.insert(choice.trim().to_string(), serde_json::Value::String(scale.trim().to_string()));
+            }
+            _ => return Err(format!("Invalid scale key entry '{}', expected choice:scale", entry)),
+        }
+    }
+    Ok(Some(serde_json::Value::Object(map)))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,119 @@
+// Bulk Question Import Unit Tests
+
+#[cfg(test)]
+mod question_import_tests {
+    use crate::question_import::{read_rows, validate_rows};
+    use crate::db::Database;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::io::Write;
+
+    fn write_csv(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_valid_rows_build_options() {
+        let file = write_csv(
+            "Text,Type,Choices,Correct Answer,Scale Key,Media\n\
+             \"2, 4, 8, ...\",multiple_choice,16|12|10,16,,\n\
+             Which is more like you?,pair,I lead|I follow,,A:dom|B:def,\n\
+             Draw a tree,drawing,,,,https://cdn.example.com/tree.png\n"
+        );
+
+        let rows = read_rows(file.path()).unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let (valid, errors) = validate_rows(&rows);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+        assert_eq!(valid[0].text, "2, 4, 8, ...");
+        assert_eq!(valid[0].options["choices"], serde_json::json!(["16", "12", "10"]));
+        assert_eq!(valid[0].options["correct"], "16");
+        assert_eq!(valid[1].options["scale_key"], serde_json::json!({"A": "dom", "B": "def"}));
+        assert_eq!(valid[2].media_url.as_deref(), Some("https://cdn.example.com/tree.png"));
+    }
+
+    #[test]
+    fn test_invalid_rows_are_reported_per_row() {
+        let file = write_csv(
+            "text,type,choices,correct_answer,media\n\
+             ,multiple_choice,A|B,C,\n\
+             Essay,text,A|B,,\n\
+             Pick one,matrix,,,\n\
+             Fine,true_false,,True,\n\
+             Draw a house,drawing,,,/home/admin/house.png\n"
+        );
+
+        let rows = read_rows(file.path()).unwrap();
+        let (valid, errors) = validate_rows(&rows);
+
+        assert_eq!(valid.len(), 1);
+        let located: Vec<(usize, &str)> = errors.iter().map(|e| (e.row, e.column.as_str())).collect();
+        assert_eq!(located, vec![
+            (2, "text"),
+            (2, "correct_answer"),
+            (3, "choices"),
+            (4, "type"),
+            (6, "media"),
+        ]);
+    }
+
+    #[test]
+    fn test_new_question_types() {
+        let file = write_csv(
+            "text,type,choices,correct_answer,scale_key\n\
+             I enjoy parties,likert,STS|TS|N|S|SS,,E\n\
+             Order by size,ranking,Cat|Horse|Mouse,Mouse|Cat|Horse,\n\
+             Pick most and least,most_least,Bold|Calm|Kind,,Bold:D|Calm:S|Kind:S\n\
+             12 x 3 = ?,numeric,,36,\n\
+             Rate me,likert,,Yes,\n\
+             Order these,ranking,A|B,A|C,\n"
+        );
+
+        let rows = read_rows(file.path()).unwrap();
+        let (valid, errors) = validate_rows(&rows);
+
+        assert_eq!(valid.len(), 4);
+        assert_eq!(valid[0].options["max"], 5);
+        assert_eq!(valid[0].options["scale_key"], "E");
+        assert_eq!(valid[1].options["correct"], serde_json::json!(["Mouse", "Cat", "Horse"]));
+        assert_eq!(valid[2].options["statements"], serde_json::json!(["Bold", "Calm", "Kind"]));
+        assert_eq!(valid[3].options["correct"], "36");
+
+        let located: Vec<(usize, &str)> = errors.iter().map(|e| (e.row, e.column.as_str())).collect();
+        assert_eq!(located, vec![(6, "correct_answer"), (7, "choices")]);
+    }
+
+    #[test]
+    fn test_missing_required_column() {
+        let file = write_csv("question,choices\nQ1,A|B\n");
+        assert!(read_rows(file.path()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_insert_appends_to_subtest() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let tool_id = db.create_tool("Import Tool", "choice", "cognitive", "Import test").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Items", 1, None).await.unwrap();
+        db.create_question(sub_id, "Existing", "multiple_choice", serde_json::json!({"choices": ["A", "B"]}), 1).await.unwrap();
+
+        let file = write_csv("text,type,choices\nNew 1,multiple_choice,A|B\nNew 2,true_false,\n");
+        let (valid, _) = validate_rows(&read_rows(file.path()).unwrap());
+
+        let ids = db.create_questions_bulk(sub_id, &valid).await.unwrap();
+        assert_eq!(ids.len(), 2);
+
+        let questions = db.get_questions_by_subtest(sub_id).await.unwrap();
+        let order: Vec<(&str, i64)> = questions.iter().map(|q| (q.question_text.as_str(), q.sequence_order)).collect();
+        assert_eq!(order, vec![("Existing", 1), ("New 1", 2), ("New 2", 3)]);
+    }
+}
@@ -0,0 +1,323 @@
+// Question Types
+// Option schemas, answer formats and answer checking for each question type
+//
+// Answers travel as strings (`local_answers.answer`, `SubmittedAnswer.answer`).
+// Simple types keep the raw value; structured answers are JSON:
+//   likert      "4"
+//   ranking     ["B","A","C"]
+//   most_least  {"most":"Bold","least":"Calm"}
+//   numeric     "12.5"
+
+use serde::{Serialize, Deserialize};
+
+use crate::rating::Rubric;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionType {
+    MultipleChoice,
+    TrueFalse,
+    Pair,
+    Text,
+    Drawing,
+    KraepelinColumn,
+    Likert,
+    Ranking,
+    MostLeast,
+    Numeric,
+}
+
+impl QuestionType {
+    pub const ALL: [QuestionType; 10] = [
+        QuestionType::MultipleChoice,
+        QuestionType::TrueFalse,
+        QuestionType::Pair,
+        QuestionType::Text,
+        QuestionType::Drawing,
+        QuestionType::KraepelinColumn,
+        QuestionType::Likert,
+        QuestionType::Ranking,
+        QuestionType::MostLeast,
+        QuestionType::Numeric,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuestionType::MultipleChoice => "multiple_choice",
+            QuestionType::TrueFalse => "true_false",
+            QuestionType::Pair => "pair",
+            QuestionType::Text => "text",
+            QuestionType::Drawing => "drawing",
+            QuestionType::KraepelinColumn => "kraepelin_column",
+            QuestionType::Likert => "likert",
+            QuestionType::Ranking => "ranking",
+            QuestionType::MostLeast => "most_least",
+            QuestionType::Numeric => "numeric",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|t| t.as_str() == value)
+    }
+}
+
+/// A parsed answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum AnswerValue {
+    Choice(String),
+    Likert(i64),
+    Ranking(Vec<String>),
+    MostLeast { most: String, least: String },
+    Numeric(f64),
+    Text(String),
+}
+
+impl AnswerValue {
+    /// The string form stored with responses
+    pub fn serialize(&self) -> String {
+        match self {
+            AnswerValue::Choice(s) | AnswerValue::Text(s) => s.clone(),
+            AnswerValue::Likert(v) => v.to_string(),
+            AnswerValue::Numeric(v) => v.to_string(),
+            AnswerValue::Ranking(items) => serde_json::to_string(items).unwrap_or_default(),
+            AnswerValue::MostLeast { most, least } => {
+                serde_json::json!({ "most": most, "least": least }).to_string()
+            }
+        }
+    }
+}
+
+/// Likert scale bounds from `{"min": 1, "max": 5}`. Defaults to a 1-5 scale.
+pub fn likert_bounds(options: Option<&serde_json::Value>) -> (i64, i64) {
+    let get = |key: &str, default: i64| options
+        .and_then(|o| o.get(key))
+        .and_then(|v| v.as_i64())
+        .unwrap_or(default);
+    (get("min", 1), get("max", 5))
+}
+
+fn string_list(options: &serde_json::Value, key: &str) -> Result<Vec<String>, String> {
+    let list = options.get(key)
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| format!("'{}' must be a list", key))?;
+
+    list.iter()
+        .map(|v| v.as_str().map(|s| s.to_string()).ok_or_else(|| format!("'{}' must only contain text", key)))
+        .collect()
+}
+
+fn distinct_list(options: &serde_json::Value, key: &str, minimum: usize) -> Result<Vec<String>, String> {
+    let items = string_list(options, key)?;
+    if items.len() < minimum {
+        return Err(format!("'{}' needs at least {} entries", key, minimum));
+    }
+    let mut seen = std::collections::HashSet::new();
+    if let Some(dup) = items.iter().find(|i| !seen.insert(i.as_str())) {
+        return Err(format!("'{}' contains '{}' twice", key, dup));
+    }
+    Ok(items)
+}
+
+/// Check a question's `options` against the schema of its type.
+///
+/// The original types only need an object (or nothing); the editor has always been loose with them.
+pub fn validate_options(question_type: QuestionType, options: Option<&serde_json::Value>) -> Result<(), String> {
+    let empty = serde_json::json!({});
+    let options = match options {
+        Some(serde_json::Value::Null) | None => &empty,
+        Some(o @ serde_json::Value::Object(_)) => o,
+        Some(_) => return Err("Options must be a JSON object".to_string()),
+    };
+
+    match question_type {
+        QuestionType::Likert => {
+            let (min, max) = likert_bounds(Some(options));
+            if min >= max {
+                return Err(format!("Likert scale min ({}) must be below max ({})", min, max));
+            }
+            if max - min + 1 > 11 {
+                return Err("Likert scales are limited to 11 points".to_string());
+            }
+            if options.get("labels").is_some() {
+                let labels = string_list(options, "labels")?;
+                if labels.len() as i64 != max - min + 1 {
+                    return Err(format!("Expected {} labels for a {}-{} scale, got {}", max - min + 1, min, max, labels.len()));
+                }
+            }
+            if options.get("reverse").map(|r| !r.is_boolean()).unwrap_or(false) {
+                return Err("'reverse' must be true or false".to_string());
+            }
+        }
+        QuestionType::Ranking => {
+            let items = distinct_list(options, "items", 2)?;
+            if let Some(correct) = options.get("correct").filter(|c| !is_blank(c)) {
+                let order: Vec<String> = serde_json::from_value(correct.clone())
+                    .map_err(|_| "'correct' must be the items in their correct order".to_string())?;
+                if !is_permutation(&order, &items) {
+                    return Err("'correct' must list every item exactly once".to_string());
+                }
+            }
+        }
+        QuestionType::MostLeast => {
+            let statements = distinct_list(options, "statements", 2)?;
+            if let Some(key) = options.get("scale_key").and_then(|k| k.as_object()) {
+                if let Some(unknown) = key.keys().find(|k| !statements.contains(k)) {
+                    return Err(format!("Scale key refers to unknown statement '{}'", unknown));
+                }
+            }
+        }
+        QuestionType::Numeric => {
+            for key in ["min", "max", "tolerance", "correct"] {
+                if let Some(v) = options.get(key).filter(|v| !is_blank(v)) {
+                    if number_of(v).is_none() {
+                        return Err(format!("'{}' must be a number", key));
+                    }
+                }
+            }
+            if let (Some(min), Some(max)) = (options.get("min").and_then(number_of), options.get("max").and_then(number_of)) {
+                if min > max {
+                    return Err(format!("Numeric min ({}) is above max ({})", min, max));
+                }
+            }
+            if options.get("tolerance").and_then(number_of).map(|t| t < 0.0).unwrap_or(false) {
+                return Err("'tolerance' can't be negative".to_string());
+            }
+        }
+        QuestionType::Text | QuestionType::Drawing if options.get("rubric").is_some() => {
+            Rubric::from_options(Some(options))?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Parse and validate a raw answer against its question
+pub fn parse_answer(question_type: QuestionType, options: Option<&serde_json::Value>, raw: &str) -> Result<AnswerValue, String> {
+    let raw = raw.trim();
+
+    match question_type {
+        QuestionType::Likert => {
+            let value: i64 = raw.parse().map_err(|_| format!("'{}' is not a scale point", raw))?;
+            let (min, max) = likert_bounds(options);
+            if value < min || value > max {
+                return Err(format!("{} is outside the {}-{} scale", value, min, max));
+            }
+            Ok(AnswerValue::Likert(value))
+        }
+        QuestionType::Ranking => {
+            let order: Vec<String> = serde_json::from_str(raw).map_err(|_| "Ranking answers must be a JSON list".to_string())?;
+            let order: Vec<String> = order.iter().map(|i| to_original(options, "items", "item_values", i)).collect();
+            let items = original_list(options, "items", "item_values")?;
+            if !is_permutation(&order, &items) {
+                return Err("Ranking must list every item exactly once".to_string());
+            }
+            Ok(AnswerValue::Ranking(order))
+        }
+        QuestionType::MostLeast => {
+            #[derive(Deserialize)]
+            struct Pick { most: String, least: String }
+
+            let pick: Pick = serde_json::from_str(raw).map_err(|_| "Most/least answers need 'most' and 'least'".to_string())?;
+            if pick.most == pick.least {
+                return Err("The same statement can't be both most and least".to_string());
+            }
+            let pick = Pick {
+                most: to_original(options, "statements", "statement_values", &pick.most),
+                least: to_original(options, "statements", "statement_values", &pick.least),
+            };
+            let statements = original_list(options, "statements", "statement_values")?;
+            for picked in [&pick.most, &pick.least] {
+                if !statements.contains(picked) {
+                    return Err(format!("'{}' is not one of the statements", picked));
+                }
+            }
+            Ok(AnswerValue::MostLeast { most: pick.most, least: pick.least })
+        }
+        QuestionType::Numeric => {
+            let value: f64 = raw.replace(',', ".").parse().map_err(|_| format!("'{}' is not a number", raw))?;
+            let bound = |key: &str| options.and_then(|o| o.get(key)).and_then(number_of);
+            if bound("min").map(|m| value < m).unwrap_or(false) || bound("max").map(|m| value > m).unwrap_or(false) {
+                return Err(format!("{} is out of range", value));
+            }
+            Ok(AnswerValue::Numeric(value))
+        }
+        QuestionType::Text | QuestionType::Drawing => Ok(AnswerValue::Text(raw.to_string())),
+        _ => Ok(AnswerValue::Choice(raw.to_string())),
+    }
+}
+
+/// Canonical stored form of an answer sent as JSON by the test UI
+/// (`4`, `["B","A"]`, `{"most":..,"least":..}` or a plain string)
+pub fn serialize_answer(question_type: QuestionType, options: Option<&serde_json::Value>, answer: &serde_json::Value) -> Result<String, String> {
+    let raw = match answer {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    parse_answer(question_type, options, &raw).map(|value| value.serialize())
+}
+
+/// Whether `answer` matches the `expected` key. Numeric answers may carry a `tolerance`.
+pub fn is_correct(question_type: QuestionType, options: Option<&serde_json::Value>, expected: &str, answer: &str) -> bool {
+    match question_type {
+        QuestionType::Numeric => {
+            let tolerance = options.and_then(|o| o.get("tolerance")).and_then(number_of).unwrap_or(0.0);
+            match (expected.trim().replace(',', ".").parse::<f64>(), answer.trim().replace(',', ".").parse::<f64>()) {
+                (Ok(e), Ok(a)) => (e - a).abs() <= tolerance + f64::EPSILON,
+                _ => false,
+            }
+        }
+        QuestionType::Ranking => {
+            let parse = |s: &str| serde_json::from_str::<Vec<String>>(s).ok();
+            let answer = parse(answer).map(|order| order.iter()
+                .map(|i| to_original(options, "items", "item_values", i))
+                .collect::<Vec<_>>());
+            matches!((parse(expected), answer), (Some(e), Some(a)) if e == a)
+        }
+        _ => answer.trim() == expected.trim(),
+    }
+}
+
+/// The original entries of a list. Localized options keep them under `values_key`
+/// while `key` holds the translated text shown to the candidate.
+fn original_list(options: Option<&serde_json::Value>, key: &str, values_key: &str) -> Result<Vec<String>, String> {
+    match options {
+        Some(o) if o.get(values_key).is_some() => string_list(o, values_key),
+        Some(o) => string_list(o, key),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Map a displayed (translated) entry back to the original at the same position.
+/// Anything else, including entries that already are originals, is kept as is.
+fn to_original(options: Option<&serde_json::Value>, key: &str, values_key: &str, picked: &str) -> String {
+    let displayed = options.and_then(|o| string_list(o, key).ok()).unwrap_or_default();
+    let values = options.and_then(|o| string_list(o, values_key).ok()).unwrap_or_default();
+
+    displayed.iter()
+        .position(|d| d == picked)
+        .and_then(|i| values.get(i))
+        .cloned()
+        .unwrap_or_else(|| picked.to_string())
+}
+
+fn is_permutation(order: &[String], items: &[String]) -> bool {
+    let mut a: Vec<&String> = order.iter().collect();
+    let mut b: Vec<&String> = items.iter().collect();
+    a.sort();
+    b.sort();
+    a == b
+}
+
+fn is_blank(value: &serde_json::Value) -> bool {
+    value.is_null() || value.as_str().map(|s| s.is_empty()).unwrap_or(false)
+}
+
+/// Numbers may come in as JSON numbers or numeric strings (spreadsheet imports)
+fn number_of(value: &serde_json::Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+#[cfg(test)]
+mod tests;
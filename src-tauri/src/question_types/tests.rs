@@ -0,0 +1,125 @@
+// Question Type Unit Tests
+
+#[cfg(test)]
+mod question_types_tests {
+    use crate::question_types::{validate_options, parse_answer, serialize_answer, is_correct, AnswerValue, QuestionType};
+    use crate::db::models::{FullSubtest, FullToolStructure, Question, Tool, ToolSubtest};
+    use crate::db::packages::PackageSnapshot;
+    use crate::scoring::{score_snapshot, SubmittedAnswer};
+    use serde_json::json;
+
+    #[test]
+    fn test_option_schemas() {
+        assert!(validate_options(QuestionType::Likert, Some(&json!({"min": 1, "max": 5, "labels": ["1", "2", "3", "4", "5"]}))).is_ok());
+        assert!(validate_options(QuestionType::Likert, Some(&json!({"min": 1, "max": 4, "labels": ["a", "b"]}))).is_err());
+        assert!(validate_options(QuestionType::Likert, Some(&json!({"min": 5, "max": 1}))).is_err());
+
+        assert!(validate_options(QuestionType::Ranking, Some(&json!({"items": ["A", "B", "C"], "correct": ["C", "A", "B"]}))).is_ok());
+        assert!(validate_options(QuestionType::Ranking, Some(&json!({"items": ["A", "B"], "correct": ["A", "A"]}))).is_err());
+        assert!(validate_options(QuestionType::Ranking, Some(&json!({"items": ["A", "A"]}))).is_err());
+
+        assert!(validate_options(QuestionType::MostLeast, Some(&json!({"statements": ["Bold", "Calm"], "scale_key": {"Bold": "D"}}))).is_ok());
+        assert!(validate_options(QuestionType::MostLeast, Some(&json!({"statements": ["Bold", "Calm"], "scale_key": {"Loud": "I"}}))).is_err());
+
+        assert!(validate_options(QuestionType::Numeric, Some(&json!({"min": 0, "max": 100, "correct": "42"}))).is_ok());
+        assert!(validate_options(QuestionType::Numeric, Some(&json!({"tolerance": -1}))).is_err());
+
+        // The original types stay permissive
+        assert!(validate_options(QuestionType::MultipleChoice, Some(&json!({"choices": ["A"]}))).is_ok());
+        assert!(validate_options(QuestionType::MultipleChoice, Some(&json!(["A", "B"]))).is_err());
+    }
+
+    #[test]
+    fn test_answer_parsing_and_serialization() {
+        let likert = json!({"min": 1, "max": 5});
+        assert_eq!(parse_answer(QuestionType::Likert, Some(&likert), "4"), Ok(AnswerValue::Likert(4)));
+        assert!(parse_answer(QuestionType::Likert, Some(&likert), "6").is_err());
+
+        let most_least = json!({"statements": ["Bold", "Calm", "Kind"]});
+        let pick = serialize_answer(QuestionType::MostLeast, Some(&most_least), &json!({"most": "Bold", "least": "Kind"})).unwrap();
+        assert_eq!(parse_answer(QuestionType::MostLeast, Some(&most_least), &pick).unwrap(), AnswerValue::MostLeast { most: "Bold".to_string(), least: "Kind".to_string() });
+        assert!(serialize_answer(QuestionType::MostLeast, Some(&most_least), &json!({"most": "Bold", "least": "Bold"})).is_err());
+
+        let ranking = json!({"items": ["A", "B", "C"]});
+        assert_eq!(serialize_answer(QuestionType::Ranking, Some(&ranking), &json!(["B", "C", "A"])).unwrap(), r#"["B","C","A"]"#);
+        assert!(serialize_answer(QuestionType::Ranking, Some(&ranking), &json!(["B", "C"])).is_err());
+
+        assert_eq!(serialize_answer(QuestionType::Numeric, None, &json!("3,5")).unwrap(), "3.5");
+        assert!(is_correct(QuestionType::Numeric, Some(&json!({"tolerance": 0.1})), "3.45", "3.5"));
+        assert!(!is_correct(QuestionType::Numeric, None, "3.45", "3.5"));
+    }
+
+    fn question(id: i64, question_type: &str, options: serde_json::Value) -> Question {
+        Question {
+            id,
+            subtest_id: 1,
+            question_text: format!("Q{}", id),
+            question_type: question_type.to_string(),
+            options: Some(options),
+            media_url: None,
+            sequence_order: id,
+        }
+    }
+
+    #[test]
+    fn test_scoring_new_types() {
+        let now = chrono::Utc::now().naive_utc();
+        let snapshot = PackageSnapshot {
+            schema_version: 2,
+            version: 1,
+            structure: FullToolStructure {
+                tool: Tool {
+                    id: 1,
+                    name: "Inventory".to_string(),
+                    tool_type: "choice".to_string(),
+                    category: "personality".to_string(),
+                    config: json!({}),
+                    created_at: now,
+                    updated_at: now,
+                    content_locale: "id".to_string(),
+                },
+                subtests: vec![FullSubtest {
+                    subtest: ToolSubtest {
+                        id: 1,
+                        tool_id: 1,
+                        subtest_name: "Main".to_string(),
+                        time_limit_seconds: None,
+                        instructions: json!({}),
+                        question_count: 5,
+                        sequence_order: 1,
+                    },
+                    questions: vec![
+                        question(1, "likert", json!({"min": 1, "max": 5, "scale_key": "E"})),
+                        question(2, "likert", json!({"min": 1, "max": 5, "scale_key": "E", "reverse": true})),
+                        question(3, "most_least", json!({"statements": ["Bold", "Calm", "Kind"], "scale_key": {"Bold": "D", "Calm": "S", "Kind": "S"}})),
+                        question(4, "ranking", json!({"items": ["A", "B", "C"], "correct": ["C", "B", "A"]})),
+                        question(5, "numeric", json!({"correct": 12, "tolerance": 0.5})),
+                    ],
+                }],
+            },
+            answer_keys: vec![],
+            translations: Default::default(),
+        };
+
+        let answer = |id: i64, value: &str| SubmittedAnswer { question_id: id, answer: Some(value.to_string()) };
+        let summary = score_snapshot(&snapshot, &[
+            answer(1, "4"),
+            answer(2, "2"),
+            answer(3, r#"{"most":"Bold","least":"Calm"}"#),
+            answer(4, r#"["C","B","A"]"#),
+            answer(5, "12.4"),
+        ]);
+
+        // Only the ranking and numeric items have a key
+        assert_eq!(summary.scorable, 2);
+        assert_eq!(summary.raw_score, 2);
+        assert_eq!(summary.answered, 5);
+
+        // 4 + reversed 2 (= 4)
+        assert_eq!(summary.scales["E"].total, 8.0);
+        assert_eq!(summary.scales["E"].items, 2);
+        assert_eq!(summary.scales["D"].most, 1);
+        assert_eq!(summary.scales["S"].least, 1);
+        assert_eq!(summary.scales["S"].total, -1.0);
+    }
+}
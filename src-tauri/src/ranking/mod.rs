@@ -0,0 +1,291 @@
+// Candidate Ranking
+// Weighted composite of tool scores, minimum cutoffs on tool totals or single scales,
+// and the tie-break rules that order candidates with the same composite. Tools are
+// scored on different ranges, so each is turned into a T-score before weighting.
+
+use serde::{Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// Composites closer than this count as equal and go to the tie-breaks
+const COMPOSITE_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolWeight {
+    pub tool_id: i64,
+    /// Filled in when the configuration is read back
+    #[serde(default)]
+    pub tool_name: String,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreCutoff {
+    pub tool_id: i64,
+    #[serde(default)]
+    pub tool_name: String,
+    /// A scale in the tool's report (`scores.scales`); `None` for the tool's total score
+    #[serde(default)]
+    pub scale: Option<String>,
+    pub min_score: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// Higher score on the most heavily weighted tool, then the next one
+    HighestWeightedTool,
+    /// Whoever finished their last weighted tool first
+    EarliestCompletion,
+    FewestViolations,
+}
+
+impl TieBreak {
+    pub fn defaults() -> Vec<TieBreak> {
+        vec![TieBreak::HighestWeightedTool, TieBreak::EarliestCompletion]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankingConfig {
+    pub weights: Vec<ToolWeight>,
+    #[serde(default)]
+    pub cutoffs: Vec<ScoreCutoff>,
+    #[serde(default = "TieBreak::defaults")]
+    pub tie_breaks: Vec<TieBreak>,
+}
+
+/// A participant's counted report for one tool
+#[derive(Debug, Clone)]
+pub struct ToolResult {
+    pub tool_id: i64,
+    /// The report's total; `None` when it has none, which counts as not done
+    pub score: Option<f64>,
+    /// The report's `scores` JSON, for scale cutoffs
+    pub scores: serde_json::Value,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CandidateScores {
+    pub user_id: i64,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub results: Vec<ToolResult>,
+    pub violations: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeightedScore {
+    pub tool_id: i64,
+    pub tool_name: String,
+    pub weight: f64,
+    pub score: Option<f64>,
+    /// T-score (mean 50, SD 10) among the candidates with a score on this tool
+    pub standard_score: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CutoffResult {
+    pub tool_id: i64,
+    pub tool_name: String,
+    pub scale: Option<String>,
+    pub min_score: f64,
+    pub score: Option<f64>,
+    pub passed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankedCandidate {
+    /// Shared by candidates still tied after every tie-break; `None` until all weighted tools are done
+    pub rank: Option<i64>,
+    pub user_id: i64,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub composite: Option<f64>,
+    /// Every cutoff met
+    pub passed: bool,
+    pub scores: Vec<WeightedScore>,
+    pub cutoffs: Vec<CutoffResult>,
+    pub completed_at: Option<String>,
+    pub violations: i64,
+}
+
+pub fn validate_config(config: &RankingConfig) -> Result<(), String> {
+    if config.weights.is_empty() {
+        return Err("Give at least one tool a weight".to_string());
+    }
+
+    let mut tools = HashSet::new();
+    for w in &config.weights {
+        if !w.weight.is_finite() || w.weight <= 0.0 {
+            return Err(format!("Weight for tool {} must be a positive number", w.tool_id));
+        }
+        if !tools.insert(w.tool_id) {
+            return Err(format!("Tool {} is weighted more than once", w.tool_id));
+        }
+    }
+
+    let mut cutoffs = HashSet::new();
+    for c in &config.cutoffs {
+        if !c.min_score.is_finite() {
+            return Err(format!("Cutoff for tool {} must be a number", c.tool_id));
+        }
+        let scale = c.scale.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        if !cutoffs.insert((c.tool_id, scale)) {
+            return Err(format!("Tool {} has more than one cutoff for the same score", c.tool_id));
+        }
+    }
+
+    let mut rules = HashSet::new();
+    if let Some(rule) = config.tie_breaks.iter().find(|r| !rules.insert(**r)) {
+        return Err(format!("Tie-break {:?} is listed twice", rule));
+    }
+    Ok(())
+}
+
+/// A scale's score in a report: `scores.scales.<name>` is either a number or a
+/// scale tally with a `total`
+pub fn scale_score(scores: &serde_json::Value, scale: &str) -> Option<f64> {
+    let value = scores.get("scales")?.get(scale)?;
+    value.as_f64().or_else(|| value.get("total").and_then(|t| t.as_f64()))
+}
+
+/// Mean and sample standard deviation of a tool's scores; `None` for the SD with fewer than two
+fn cohort_stats(scores: &[f64]) -> (f64, Option<f64>) {
+    let n = scores.len() as f64;
+    let mean = if scores.is_empty() { 0.0 } else { scores.iter().sum::<f64>() / n };
+    let sd = (scores.len() > 1).then(|| (scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt());
+    (mean, sd)
+}
+
+/// A score as a T-score within its cohort. Everyone sits at 50 when the scores don't spread.
+pub fn standard_score(score: f64, mean: f64, sd: Option<f64>) -> f64 {
+    match sd {
+        Some(sd) if sd > COMPOSITE_EPSILON => 50.0 + 10.0 * (score - mean) / sd,
+        _ => 50.0,
+    }
+}
+
+/// Work out composites and cutoffs, then rank. Candidates missing a weighted tool come
+/// last, unranked.
+pub fn rank_candidates(config: &RankingConfig, candidates: Vec<CandidateScores>) -> Vec<RankedCandidate> {
+    let total_weight: f64 = config.weights.iter().map(|w| w.weight).sum();
+    // Heaviest first, for the highest-weighted-tool tie-break
+    let mut by_weight: Vec<&ToolWeight> = config.weights.iter().collect();
+    by_weight.sort_by(|a, b| b.weight.partial_cmp(&a.weight).unwrap_or(Ordering::Equal));
+    let cohorts: HashMap<i64, (f64, Option<f64>)> = config.weights.iter().map(|w| {
+        let scores: Vec<f64> = candidates.iter()
+            .filter_map(|c| c.results.iter().find(|r| r.tool_id == w.tool_id)?.score)
+            .collect();
+        (w.tool_id, cohort_stats(&scores))
+    }).collect();
+
+    let mut ranked: Vec<RankedCandidate> = candidates.into_iter().map(|candidate| {
+        let results: HashMap<i64, &ToolResult> = candidate.results.iter().map(|r| (r.tool_id, r)).collect();
+
+        let scores: Vec<WeightedScore> = config.weights.iter().map(|w| {
+            let score = results.get(&w.tool_id).and_then(|r| r.score);
+            let (mean, sd) = cohorts[&w.tool_id];
+            WeightedScore {
+                tool_id: w.tool_id,
+                tool_name: w.tool_name.clone(),
+                weight: w.weight,
+                score,
+                standard_score: score.map(|s| standard_score(s, mean, sd)),
+            }
+        }).collect();
+        let complete = scores.iter().all(|s| s.score.is_some());
+        let composite = complete.then(|| {
+            scores.iter().map(|s| s.weight * s.standard_score.unwrap_or(0.0)).sum::<f64>() / total_weight
+        });
+
+        let cutoffs: Vec<CutoffResult> = config.cutoffs.iter().map(|c| {
+            let scale = c.scale.as_deref().map(str::trim).filter(|s| !s.is_empty());
+            let score = results.get(&c.tool_id).and_then(|r| match scale {
+                Some(scale) => scale_score(&r.scores, scale),
+                None => r.score,
+            });
+            CutoffResult {
+                tool_id: c.tool_id,
+                tool_name: c.tool_name.clone(),
+                scale: scale.map(str::to_string),
+                min_score: c.min_score,
+                passed: score.map(|s| s >= c.min_score).unwrap_or(false),
+                score,
+            }
+        }).collect();
+
+        let completed_at = complete
+            .then(|| config.weights.iter().filter_map(|w| results.get(&w.tool_id)?.completed_at.clone()).max())
+            .flatten();
+
+        RankedCandidate {
+            rank: None,
+            user_id: candidate.user_id,
+            username: candidate.username,
+            full_name: candidate.full_name,
+            composite,
+            passed: complete && cutoffs.iter().all(|c| c.passed),
+            scores,
+            cutoffs,
+            completed_at,
+            violations: candidate.violations,
+        }
+    }).collect();
+
+    let compare = |a: &RankedCandidate, b: &RankedCandidate| compare_candidates(a, b, &by_weight, &config.tie_breaks);
+    ranked.sort_by(|a, b| match (a.composite, b.composite) {
+        (Some(_), Some(_)) => compare(a, b).then(a.username.cmp(&b.username)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.username.cmp(&b.username),
+    });
+
+    for i in 0..ranked.len() {
+        if ranked[i].composite.is_none() {
+            break;
+        }
+        ranked[i].rank = Some(match i {
+            0 => 1,
+            _ if compare(&ranked[i - 1], &ranked[i]) == Ordering::Equal => ranked[i - 1].rank.unwrap_or(1),
+            _ => i as i64 + 1,
+        });
+    }
+    ranked
+}
+
+/// `Less` when `a` ranks ahead of `b`
+fn compare_candidates(a: &RankedCandidate, b: &RankedCandidate, by_weight: &[&ToolWeight], tie_breaks: &[TieBreak]) -> Ordering {
+    let (ca, cb) = (a.composite.unwrap_or(0.0), b.composite.unwrap_or(0.0));
+    if (ca - cb).abs() > COMPOSITE_EPSILON {
+        return cb.partial_cmp(&ca).unwrap_or(Ordering::Equal);
+    }
+
+    for rule in tie_breaks {
+        let ordering = match rule {
+            TieBreak::HighestWeightedTool => by_weight.iter()
+                .map(|w| {
+                    let score = |c: &RankedCandidate| c.scores.iter().find(|s| s.tool_id == w.tool_id).and_then(|s| s.score).unwrap_or(0.0);
+                    score(b).partial_cmp(&score(a)).unwrap_or(Ordering::Equal)
+                })
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal),
+            // Missing times sort last
+            TieBreak::EarliestCompletion => match (&a.completed_at, &b.completed_at) {
+                (Some(x), Some(y)) => x.cmp(y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            TieBreak::FewestViolations => a.violations.cmp(&b.violations),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,118 @@
+// Candidate Ranking Unit Tests
+
+#[cfg(test)]
+mod ranking_tests {
+    use crate::ranking::{rank_candidates, scale_score, standard_score, validate_config, CandidateScores, RankingConfig, ScoreCutoff, TieBreak, ToolResult, ToolWeight};
+    use serde_json::json;
+
+    fn weight(tool_id: i64, weight: f64) -> ToolWeight {
+        ToolWeight { tool_id, tool_name: format!("Tool {}", tool_id), weight }
+    }
+
+    fn candidate(user_id: i64, username: &str, results: &[(i64, f64, &str)], violations: i64) -> CandidateScores {
+        CandidateScores {
+            user_id,
+            username: username.to_string(),
+            full_name: None,
+            results: results.iter().map(|(tool_id, score, completed_at)| ToolResult {
+                tool_id: *tool_id,
+                score: Some(*score),
+                scores: json!({"total_score": score, "scales": {"E": {"total": score / 10.0}}}),
+                completed_at: Some(completed_at.to_string()),
+            }).collect(),
+            violations,
+        }
+    }
+
+    #[test]
+    fn test_weighted_composite_and_cutoffs() {
+        let config = RankingConfig {
+            weights: vec![weight(1, 2.0), weight(2, 1.0)],
+            cutoffs: vec![
+                ScoreCutoff { tool_id: 1, tool_name: String::new(), scale: None, min_score: 50.0 },
+                ScoreCutoff { tool_id: 2, tool_name: String::new(), scale: Some("E".to_string()), min_score: 4.0 },
+            ],
+            tie_breaks: TieBreak::defaults(),
+        };
+        let ranked = rank_candidates(&config, vec![
+            candidate(1, "ana", &[(1, 60.0, "2026-02-01 10:00:00"), (2, 30.0, "2026-02-01 11:00:00")], 0),
+            candidate(2, "budi", &[(1, 90.0, "2026-02-01 10:00:00"), (2, 60.0, "2026-02-01 11:00:00")], 0),
+            candidate(3, "citra", &[(1, 75.0, "2026-02-01 10:00:00")], 0),
+        ]);
+
+        let summary: Vec<(&str, Option<i64>, bool)> = ranked.iter().map(|r| (r.username.as_str(), r.rank, r.passed)).collect();
+        assert_eq!(summary, vec![("budi", Some(1), true), ("ana", Some(2), false), ("citra", None, false)]);
+        // Tool 1 (60, 90, 75) has SD 15, so Budi is at T 60 and Ana at T 40; tool 2 spreads 30 and 60
+        let tool2 = 5.0 * 2f64.sqrt();
+        assert!((ranked[0].composite.unwrap() - (2.0 * 60.0 + 50.0 + tool2) / 3.0).abs() < 1e-9);
+        assert!((ranked[1].composite.unwrap() - (2.0 * 40.0 + 50.0 - tool2) / 3.0).abs() < 1e-9);
+        assert_eq!(ranked[0].scores[0].score, Some(90.0));
+        // Ana's E scale is 3.0, below the cutoff; Citra has no result for tool 2 at all
+        assert!(ranked[1].cutoffs[0].passed && !ranked[1].cutoffs[1].passed);
+        assert_eq!(ranked[2].cutoffs[1].score, None);
+    }
+
+    #[test]
+    fn test_tie_breaks_in_order() {
+        let config = |tie_breaks: Vec<TieBreak>| RankingConfig { weights: vec![weight(1, 1.0), weight(2, 1.0)], cutoffs: vec![], tie_breaks };
+        // Same composite (70); dewi is stronger on tool 1, eko finished earlier, fajar has no violations
+        let candidates = || vec![
+            candidate(1, "dewi", &[(1, 80.0, "2026-02-01 12:00:00"), (2, 60.0, "2026-02-01 12:00:00")], 2),
+            candidate(2, "eko", &[(1, 70.0, "2026-02-01 09:00:00"), (2, 70.0, "2026-02-01 09:00:00")], 1),
+            candidate(3, "fajar", &[(1, 60.0, "2026-02-01 10:00:00"), (2, 80.0, "2026-02-01 10:00:00")], 0),
+        ];
+        let order = |tie_breaks| rank_candidates(&config(tie_breaks), candidates()).into_iter()
+            .map(|r| (r.username, r.rank.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(order(vec![TieBreak::HighestWeightedTool]), vec![("dewi".to_string(), 1), ("eko".to_string(), 2), ("fajar".to_string(), 3)]);
+        assert_eq!(order(vec![TieBreak::EarliestCompletion]), vec![("eko".to_string(), 1), ("fajar".to_string(), 2), ("dewi".to_string(), 3)]);
+        assert_eq!(order(vec![TieBreak::FewestViolations]), vec![("fajar".to_string(), 1), ("eko".to_string(), 2), ("dewi".to_string(), 3)]);
+        // No rules: everyone shares first place
+        assert_eq!(order(vec![]), vec![("dewi".to_string(), 1), ("eko".to_string(), 1), ("fajar".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_scores_are_standardised_per_tool() {
+        // Tool 2 is scored out of 10, tool 1 out of 100; equal weights should mean equal say
+        let config = RankingConfig { weights: vec![weight(1, 1.0), weight(2, 1.0)], cutoffs: vec![], tie_breaks: vec![] };
+        let mut missing_total = candidate(3, "gita", &[(1, 100.0, "2026-02-01 10:00:00"), (2, 10.0, "2026-02-01 10:00:00")], 0);
+        missing_total.results[1].score = None;
+        let ranked = rank_candidates(&config, vec![
+            candidate(1, "hadi", &[(1, 90.0, "2026-02-01 10:00:00"), (2, 2.0, "2026-02-01 10:00:00")], 0),
+            candidate(2, "intan", &[(1, 80.0, "2026-02-01 10:00:00"), (2, 8.0, "2026-02-01 10:00:00")], 0),
+            missing_total,
+        ]);
+
+        let summary: Vec<(&str, Option<i64>)> = ranked.iter().map(|r| (r.username.as_str(), r.rank)).collect();
+        assert_eq!(summary, vec![("intan", Some(1)), ("hadi", Some(2)), ("gita", None)]);
+        // A report without a total is missing, not a zero pulling the others up
+        assert_eq!((ranked[2].composite, ranked[2].scores[1].score), (None, None));
+        assert_eq!(ranked[1].scores[1].standard_score, Some(standard_score(2.0, 5.0, Some(18f64.sqrt()))));
+        assert_eq!(standard_score(7.0, 7.0, None), 50.0);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = RankingConfig { weights: vec![weight(1, 1.0)], cutoffs: vec![], tie_breaks: TieBreak::defaults() };
+        assert!(validate_config(&config).is_ok());
+
+        config.weights.push(weight(1, 2.0));
+        assert!(validate_config(&config).is_err());
+        config.weights = vec![weight(1, 0.0)];
+        assert!(validate_config(&config).is_err());
+        config.weights = vec![];
+        assert!(validate_config(&config).is_err());
+
+        config.weights = vec![weight(1, 1.0)];
+        config.tie_breaks = vec![TieBreak::FewestViolations, TieBreak::FewestViolations];
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_scale_score_formats() {
+        assert_eq!(scale_score(&json!({"scales": {"E": {"total": 8.0, "items": 2}}}), "E"), Some(8.0));
+        assert_eq!(scale_score(&json!({"scales": {"E": 5}}), "E"), Some(5.0));
+        assert_eq!(scale_score(&json!({"total_score": 5}), "E"), None);
+    }
+}
@@ -0,0 +1,191 @@
+// Manual Scoring
+// Rubrics for answers that can't be scored automatically (text and drawing questions)
+
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+
+pub mod reliability;
+
+use crate::db::rating::{RatingItem, RatingScore};
+use crate::question_types::QuestionType;
+
+/// Double-scored totals further apart than this share of the rubric's range go to adjudication
+const DEFAULT_MAX_DISAGREEMENT: f64 = 0.2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RubricCriterion {
+    pub key: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Scoring rubric, read from a question's `options.rubric`:
+/// `{"criteria": [{"key": "detail", "label": "Detail", "min": 0, "max": 3}, ...], "max_disagreement": 2}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rubric {
+    pub criteria: Vec<RubricCriterion>,
+    /// Largest difference between two raters' totals that doesn't need adjudication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_disagreement: Option<f64>,
+}
+
+impl Rubric {
+    /// A question without a rubric can't be rated; scores would have no scale to sit on
+    pub fn from_options(options: Option<&serde_json::Value>) -> Result<Self, String> {
+        let rubric: Rubric = match options.and_then(|o| o.get("rubric")).filter(|r| !r.is_null()) {
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|e| format!("Invalid rubric: {}", e))?,
+            None => return Err("The question has no rubric".to_string()),
+        };
+        rubric.validate()?;
+        Ok(rubric)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.criteria.is_empty() {
+            return Err("A rubric needs at least one criterion".to_string());
+        }
+        let mut seen = std::collections::HashSet::new();
+        for criterion in &self.criteria {
+            if criterion.key.trim().is_empty() {
+                return Err("Rubric criteria need a key".to_string());
+            }
+            if !seen.insert(criterion.key.as_str()) {
+                return Err(format!("Rubric criterion '{}' appears twice", criterion.key));
+            }
+            if !criterion.min.is_finite() || !criterion.max.is_finite() || criterion.min >= criterion.max {
+                return Err(format!("Rubric criterion '{}' needs min below max", criterion.key));
+            }
+        }
+        if self.max_disagreement.is_some_and(|d| !d.is_finite() || d < 0.0) {
+            return Err("'max_disagreement' can't be negative".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn min_total(&self) -> f64 {
+        self.criteria.iter().map(|c| c.min).sum()
+    }
+
+    pub fn max_total(&self) -> f64 {
+        self.criteria.iter().map(|c| c.max).sum()
+    }
+
+    /// Whether two raters' totals are too far apart to finalise without adjudication
+    pub fn needs_adjudication(&self, first: f64, second: f64) -> bool {
+        let allowed = self.max_disagreement
+            .unwrap_or((self.max_total() - self.min_total()) * DEFAULT_MAX_DISAGREEMENT);
+        (first - second).abs() > allowed + f64::EPSILON
+    }
+
+    /// Total of a rating. Every criterion must be scored, within its range.
+    pub fn total(&self, scores: &BTreeMap<String, f64>) -> Result<f64, String> {
+        if let Some(unknown) = scores.keys().find(|k| !self.criteria.iter().any(|c| &c.key == *k)) {
+            return Err(format!("'{}' is not a criterion of this rubric", unknown));
+        }
+
+        self.criteria.iter().try_fold(0.0, |total, criterion| {
+            let value = *scores.get(&criterion.key)
+                .ok_or_else(|| format!("Missing score for '{}'", criterion.key))?;
+            if !value.is_finite() || value < criterion.min || value > criterion.max {
+                return Err(format!("'{}' must be between {} and {}", criterion.key, criterion.min, criterion.max));
+            }
+            Ok(total + value)
+        })
+    }
+}
+
+/// Question types that go to the rater queue instead of the answer key
+pub fn needs_manual_scoring(question_type: QuestionType) -> bool {
+    matches!(question_type, QuestionType::Text | QuestionType::Drawing)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualItemScore {
+    pub item_id: i64,
+    pub question_id: i64,
+    pub tool_id: i64,
+    pub score: f64,
+    pub max_score: f64,
+    pub criteria: BTreeMap<String, f64>,
+    pub comment: Option<String>,
+    /// Raters whose scores make up `score`: one rater, both double-scoring raters, or the adjudicator
+    pub rater_ids: Vec<i64>,
+}
+
+/// Manual scores of a session, merged into its report under `scores.manual`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualScoreSummary {
+    pub total: f64,
+    pub max_total: f64,
+    pub items: Vec<ManualItemScore>,
+}
+
+impl ManualScoreSummary {
+    pub fn from_items(items: Vec<ManualItemScore>) -> Self {
+        Self {
+            total: items.iter().map(|i| i.score).sum(),
+            max_total: items.iter().map(|i| i.max_score).sum(),
+            items,
+        }
+    }
+}
+
+/// Final manual scores of a session. Fails while any item is unscored or awaits adjudication.
+///
+/// An adjudicator's score replaces the raters'; double-scored items otherwise take the mean of both raters.
+pub fn summarize_session(items: &[RatingItem], ratings: &HashMap<i64, Vec<RatingScore>>) -> Result<ManualScoreSummary, String> {
+    let unscored = items.iter().filter(|i| i.status != "scored").count();
+    if unscored > 0 {
+        return Err(format!("{} answer(s) still need scoring", unscored));
+    }
+    let unresolved = items.iter().filter(|i| i.needs_adjudication && i.adjudicated_by.is_none()).count();
+    if unresolved > 0 {
+        return Err(format!("{} answer(s) await adjudication", unresolved));
+    }
+
+    let mut scores = Vec::with_capacity(items.len());
+    for item in items {
+        let item_ratings = ratings.get(&item.id).map(|r| r.as_slice()).unwrap_or_default();
+        let by = |rater: Option<i64>| rater.and_then(|id| item_ratings.iter().find(|r| r.rater_id == id));
+
+        let counted: Vec<&RatingScore> = if let Some(adjudication) = by(item.adjudicated_by) {
+            vec![adjudication]
+        } else if item.required_ratings > 1 {
+            [by(item.assigned_to), by(item.second_rater)].into_iter().flatten().collect()
+        } else {
+            // The assigned rater's score counts; without an assignment, the latest one
+            by(item.assigned_to).or(item_ratings.last()).into_iter().collect()
+        };
+        if counted.is_empty() {
+            return Err(format!("Item {} is marked scored but has no rating", item.id));
+        }
+
+        let count = counted.len() as f64;
+        let mut criteria: BTreeMap<String, f64> = BTreeMap::new();
+        for rating in &counted {
+            for (key, value) in &rating.criteria {
+                *criteria.entry(key.clone()).or_default() += value / count;
+            }
+        }
+
+        scores.push(ManualItemScore {
+            item_id: item.id,
+            question_id: item.question_id,
+            tool_id: item.tool_id,
+            score: counted.iter().map(|r| r.total).sum::<f64>() / count,
+            max_score: item.rubric()?.max_total(),
+            criteria,
+            comment: counted.iter().rev().find_map(|r| r.comment.clone()),
+            rater_ids: counted.iter().map(|r| r.rater_id).collect(),
+        });
+    }
+
+    Ok(ManualScoreSummary::from_items(scores))
+}
+
+#[cfg(test)]
+mod tests;
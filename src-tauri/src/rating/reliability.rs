@@ -0,0 +1,155 @@
+// Inter-rater Reliability
+// Agreement between two raters who scored the same items
+
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+use super::Rubric;
+use crate::db::rating::DoubleScoredPair;
+
+/// Agreement statistics for the double-scored items of one rubric (question)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReliabilityReport {
+    pub question_id: i64,
+    pub tool_id: i64,
+    /// Items scored by both raters
+    pub items: i64,
+    pub exact_agreement: f64,
+    /// `None` where the statistic is undefined, e.g. both raters always gave the same score
+    pub kappa: Option<f64>,
+    pub weighted_kappa: Option<f64>,
+    pub icc: Option<f64>,
+    pub flagged: i64,
+}
+
+/// Distinct scores given by either rater, ascending
+fn categories(pairs: &[(f64, f64)]) -> Vec<f64> {
+    let mut values: Vec<f64> = pairs.iter().flat_map(|&(a, b)| [a, b]).collect();
+    values.sort_by(|a, b| a.total_cmp(b));
+    values.dedup();
+    values
+}
+
+fn index_of(categories: &[f64], value: f64) -> usize {
+    categories.iter().position(|&c| c == value).unwrap_or(0)
+}
+
+/// Observed and chance-expected proportions per pair of categories
+fn agreement_tables(pairs: &[(f64, f64)], categories: &[f64]) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+    let k = categories.len();
+    let n = pairs.len() as f64;
+    let mut observed = vec![vec![0.0; k]; k];
+    let mut first = vec![0.0; k];
+    let mut second = vec![0.0; k];
+
+    for &(a, b) in pairs {
+        let (i, j) = (index_of(categories, a), index_of(categories, b));
+        observed[i][j] += 1.0 / n;
+        first[i] += 1.0 / n;
+        second[j] += 1.0 / n;
+    }
+
+    let expected = first.iter().map(|p| second.iter().map(|q| p * q).collect()).collect();
+    (observed, expected)
+}
+
+/// Cohen's kappa, treating each distinct score as a category
+pub fn cohen_kappa(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.is_empty() {
+        return None;
+    }
+    let categories = categories(pairs);
+    let (observed, expected) = agreement_tables(pairs, &categories);
+
+    let po: f64 = (0..categories.len()).map(|i| observed[i][i]).sum();
+    let pe: f64 = (0..categories.len()).map(|i| expected[i][i]).sum();
+    if (1.0 - pe).abs() < f64::EPSILON {
+        return None;
+    }
+    Some((po - pe) / (1.0 - pe))
+}
+
+/// Cohen's kappa with quadratic weights: near misses count as partial agreement.
+/// Distances are relative to the rubric's score range.
+pub fn weighted_kappa(pairs: &[(f64, f64)], min: f64, max: f64) -> Option<f64> {
+    if pairs.is_empty() || max <= min {
+        return None;
+    }
+    let categories = categories(pairs);
+    let (observed, expected) = agreement_tables(pairs, &categories);
+
+    let mut observed_disagreement = 0.0;
+    let mut expected_disagreement = 0.0;
+    for (i, a) in categories.iter().enumerate() {
+        for (j, b) in categories.iter().enumerate() {
+            let weight = ((a - b) / (max - min)).powi(2);
+            observed_disagreement += weight * observed[i][j];
+            expected_disagreement += weight * expected[i][j];
+        }
+    }
+    if expected_disagreement.abs() < f64::EPSILON {
+        return None;
+    }
+    Some(1.0 - observed_disagreement / expected_disagreement)
+}
+
+/// ICC(2,1): two-way random effects, absolute agreement, single rater
+pub fn icc(pairs: &[(f64, f64)]) -> Option<f64> {
+    let n = pairs.len() as f64;
+    if pairs.len() < 2 {
+        return None;
+    }
+    let k = 2.0;
+
+    let grand = pairs.iter().map(|&(a, b)| a + b).sum::<f64>() / (n * k);
+    let rater_means = [
+        pairs.iter().map(|p| p.0).sum::<f64>() / n,
+        pairs.iter().map(|p| p.1).sum::<f64>() / n,
+    ];
+
+    let ss_rows: f64 = pairs.iter().map(|&(a, b)| ((a + b) / k - grand).powi(2)).sum::<f64>() * k;
+    let ss_cols: f64 = rater_means.iter().map(|m| (m - grand).powi(2)).sum::<f64>() * n;
+    let ss_total: f64 = pairs.iter().flat_map(|&(a, b)| [a, b]).map(|x| (x - grand).powi(2)).sum();
+    let ss_error = ss_total - ss_rows - ss_cols;
+
+    let ms_rows = ss_rows / (n - 1.0);
+    let ms_cols = ss_cols / (k - 1.0);
+    let ms_error = ss_error / ((n - 1.0) * (k - 1.0));
+
+    let denominator = ms_rows + (k - 1.0) * ms_error + k * (ms_cols - ms_error) / n;
+    if denominator.abs() < f64::EPSILON {
+        return None;
+    }
+    Some((ms_rows - ms_error) / denominator)
+}
+
+/// Round for display; the statistics are never reported with more precision than this
+pub fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// One report per question (each question carries its own rubric)
+pub fn reliability_reports(pairs: &[DoubleScoredPair]) -> Vec<ReliabilityReport> {
+    let mut by_question: BTreeMap<i64, Vec<&DoubleScoredPair>> = BTreeMap::new();
+    for pair in pairs {
+        by_question.entry(pair.question_id).or_default().push(pair);
+    }
+
+    by_question.into_iter().map(|(question_id, group)| {
+        // A corrupt rubric leaves weighted kappa out rather than guessing the scale
+        let rubric: Option<Rubric> = serde_json::from_value(group[0].rubric.clone()).ok();
+        let totals: Vec<(f64, f64)> = group.iter().map(|p| (p.first_total, p.second_total)).collect();
+        let agreed = totals.iter().filter(|(a, b)| (a - b).abs() < f64::EPSILON).count();
+
+        ReliabilityReport {
+            question_id,
+            tool_id: group[0].tool_id,
+            items: totals.len() as i64,
+            exact_agreement: round3(agreed as f64 / totals.len() as f64),
+            kappa: cohen_kappa(&totals).map(round3),
+            weighted_kappa: rubric.and_then(|r| weighted_kappa(&totals, r.min_total(), r.max_total())).map(round3),
+            icc: icc(&totals).map(round3),
+            flagged: group.iter().filter(|p| p.needs_adjudication).count() as i64,
+        }
+    }).collect()
+}
@@ -0,0 +1,243 @@
+// Manual Scoring Unit Tests
+
+#[cfg(test)]
+mod rating_tests {
+    use crate::rating::{summarize_session, Rubric};
+    use crate::rating::reliability::{cohen_kappa, icc, weighted_kappa};
+    use crate::db::Database;
+    use crate::db::rating::{RatingError, RatingItem, RatingScore};
+    use crate::scoring::SubmittedAnswer;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use serde_json::json;
+    use std::collections::{BTreeMap, HashMap};
+
+    async fn ratings_of(db: &Database, items: &[RatingItem]) -> HashMap<i64, Vec<RatingScore>> {
+        let mut ratings = HashMap::new();
+        for item in items {
+            ratings.insert(item.id, db.get_item_ratings(item.id).await.unwrap());
+        }
+        ratings
+    }
+
+    fn scores(pairs: &[(&str, f64)]) -> BTreeMap<String, f64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_rubric_scoring() {
+        let rubric = Rubric::from_options(Some(&json!({"rubric": {"criteria": [
+            {"key": "detail", "label": "Detail", "min": 0, "max": 3},
+            {"key": "proportion", "label": "Proportion", "min": 0, "max": 2}
+        ]}}))).unwrap();
+
+        assert_eq!(rubric.max_total(), 5.0);
+        assert_eq!(rubric.total(&scores(&[("detail", 2.0), ("proportion", 1.5)])), Ok(3.5));
+        assert!(rubric.total(&scores(&[("detail", 2.0)])).is_err());
+        assert!(rubric.total(&scores(&[("detail", 4.0), ("proportion", 1.0)])).is_err());
+        assert!(rubric.total(&scores(&[("detail", 1.0), ("proportion", 1.0), ("shading", 1.0)])).is_err());
+
+        // No rubric means no scale to rate on
+        assert!(Rubric::from_options(Some(&json!({}))).is_err());
+        assert!(Rubric::from_options(None).is_err());
+        assert!(Rubric::from_options(Some(&json!({"rubric": {"criteria": []}}))).is_err());
+        assert!(Rubric::from_options(Some(&json!({"rubric": {"criteria": [{"key": "a", "min": 2, "max": 1}]}}))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rating_queue_flow() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let tool_id = db.create_tool("Essay", "projective", "clinical", "Manual scoring").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Writing", 1, None).await.unwrap();
+        let essay = db.create_question(sub_id, "Describe yourself", "text", json!({
+            "rubric": {"criteria": [{"key": "content", "min": 0, "max": 5}, {"key": "structure", "min": 0, "max": 3}]}
+        }), 1).await.unwrap();
+        let mc = db.create_question(sub_id, "Pick one", "multiple_choice", json!({"choices": ["A", "B"], "correct": "A"}), 2).await.unwrap();
+        let skipped = db.create_question(sub_id, "Anything else?", "text", json!({}), 3).await.unwrap();
+
+        let event_id = db.create_event("Rating Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        let session_id = db.create_session(event_id, "P-001", None, None).await.unwrap();
+        let rater_id = db.create_user("rater", "hash", "operator").await.unwrap();
+
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.unwrap();
+        let answers = vec![
+            SubmittedAnswer { question_id: essay, answer: Some("I am curious".to_string()) },
+            SubmittedAnswer { question_id: mc, answer: Some("A".to_string()) },
+            SubmittedAnswer { question_id: skipped, answer: Some("  ".to_string()) },
+        ];
+
+        // Only the answered text question is queued, and only once
+        assert_eq!(db.enqueue_rating_items(event_id, session_id, &snapshot, &answers).await.unwrap(), 1);
+        assert_eq!(db.enqueue_rating_items(event_id, session_id, &snapshot, &answers).await.unwrap(), 0);
+
+        let queue = db.get_rating_queue(event_id, Some("pending"), None).await.unwrap();
+        assert_eq!(queue.len(), 1);
+        let item = &queue[0];
+        assert_eq!(item.question_id, essay);
+        assert_eq!(item.rubric().unwrap().max_total(), 8.0);
+
+        assert_eq!(db.assign_rating_items(&[item.id], rater_id).await.unwrap(), 1);
+        assert_eq!(db.get_rating_queue(event_id, None, Some(rater_id)).await.unwrap().len(), 1);
+
+        // Not everything is scored yet
+        let items = db.get_session_rating_items(session_id).await.unwrap();
+        assert!(summarize_session(&items, &HashMap::new()).is_err());
+
+        let criteria = scores(&[("content", 4.0), ("structure", 2.0)]);
+        let total = item.rubric().unwrap().total(&criteria).unwrap();
+        let rating = db.record_rating(item.id, rater_id, &criteria, total, Some("Reflective")).await.unwrap();
+        assert_eq!(rating.total, 6.0);
+
+        let items = db.get_session_rating_items(session_id).await.unwrap();
+        let ratings: HashMap<i64, _> = HashMap::from([(item.id, db.get_item_ratings(item.id).await.unwrap())]);
+        let summary = summarize_session(&items, &ratings).unwrap();
+        assert_eq!(summary.total, 6.0);
+        assert_eq!(summary.max_total, 8.0);
+
+        // Merging keeps whatever else the report already holds
+        db.create_report(session_id, json!({"raw_score": 1}), json!({})).await.unwrap();
+        let report_id = db.merge_manual_scores(session_id, &summary).await.unwrap();
+        let report = db.get_report_by_id(report_id).await.unwrap();
+        assert_eq!(report.scores["raw_score"], 1);
+        assert_eq!(report.scores["manual"]["total"], 6.0);
+        assert_eq!(report.scores["manual"]["items"][0]["comment"], "Reflective");
+    }
+
+    #[test]
+    fn test_reliability_statistics() {
+        let pairs = [(0.0, 0.0), (1.0, 1.0), (2.0, 3.0), (3.0, 3.0), (4.0, 4.0), (1.0, 2.0), (2.0, 2.0), (3.0, 4.0)];
+
+        assert!((cohen_kappa(&pairs).unwrap() - 0.5294).abs() < 1e-3);
+        assert!((weighted_kappa(&pairs, 0.0, 4.0).unwrap() - 0.8889).abs() < 1e-3);
+        assert!((icc(&pairs).unwrap() - 0.9014).abs() < 1e-3);
+
+        // Perfect agreement
+        let same = [(1.0, 1.0), (2.0, 2.0), (4.0, 4.0)];
+        assert!((cohen_kappa(&same).unwrap() - 1.0).abs() < 1e-9);
+        assert!((icc(&same).unwrap() - 1.0).abs() < 1e-9);
+
+        // Undefined when there is no variation at all
+        assert_eq!(cohen_kappa(&[(2.0, 2.0), (2.0, 2.0)]), None);
+        assert_eq!(icc(&[(1.0, 1.0)]), None);
+    }
+
+    #[tokio::test]
+    async fn test_double_scoring_and_adjudication() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let tool_id = db.create_tool("HTP", "projective", "clinical", "Double scoring").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "House", 1, None).await.unwrap();
+        let q_id = db.create_question(sub_id, "Describe the house", "text", json!({
+            "rubric": {"criteria": [{"key": "score", "min": 0, "max": 10}], "max_disagreement": 2}
+        }), 1).await.unwrap();
+
+        let event_id = db.create_event("Reliability Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.unwrap();
+        let first = db.create_user("first", "hash", "operator").await.unwrap();
+        let second = db.create_user("second", "hash", "operator").await.unwrap();
+        let judge = db.create_user("judge", "hash", "admin").await.unwrap();
+
+        let mut item_ids = Vec::new();
+        for participant in ["P-1", "P-2"] {
+            let session_id = db.create_session(event_id, participant, None, None).await.unwrap();
+            let answers = vec![SubmittedAnswer { question_id: q_id, answer: Some("A big house".to_string()) }];
+            db.enqueue_rating_items(event_id, session_id, &snapshot, &answers).await.unwrap();
+            item_ids.push(db.get_session_rating_items(session_id).await.unwrap()[0].id);
+        }
+
+        db.assign_rating_items(&item_ids, first).await.unwrap();
+        assert_eq!(db.assign_second_rater(&item_ids, second).await.unwrap(), 2);
+        // The first rater can't also be the second
+        assert_eq!(db.assign_second_rater(&item_ids, first).await.unwrap(), 0);
+
+        // One score isn't enough for a double-scored item
+        db.record_rating(item_ids[0], first, &scores(&[("score", 6.0)]), 6.0, None).await.unwrap();
+        assert_eq!(db.get_rating_item(item_ids[0]).await.unwrap().status, "assigned");
+        db.record_rating(item_ids[0], second, &scores(&[("score", 7.0)]), 7.0, None).await.unwrap();
+        assert_eq!(db.get_rating_item(item_ids[0]).await.unwrap().status, "scored");
+
+        db.record_rating(item_ids[1], first, &scores(&[("score", 2.0)]), 2.0, None).await.unwrap();
+        db.record_rating(item_ids[1], second, &scores(&[("score", 8.0)]), 8.0, None).await.unwrap();
+
+        let rubric = db.get_rating_item(item_ids[1]).await.unwrap().rubric().unwrap();
+        assert!(!rubric.needs_adjudication(6.0, 7.0));
+        assert!(rubric.needs_adjudication(2.0, 8.0));
+        db.set_needs_adjudication(item_ids[1], true).await.unwrap();
+        assert_eq!(db.get_adjudication_queue(event_id).await.unwrap().len(), 1);
+
+        let pairs = db.get_double_scored_pairs(event_id).await.unwrap();
+        let reports = crate::rating::reliability::reliability_reports(&pairs);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].items, 2);
+        assert_eq!(reports[0].flagged, 1);
+        assert_eq!(reports[0].exact_agreement, 0.0);
+
+        // The flagged item blocks the report until it is adjudicated
+        let flagged_session = db.get_rating_item(item_ids[1]).await.unwrap().session_id;
+        let items = db.get_session_rating_items(flagged_session).await.unwrap();
+        assert!(summarize_session(&items, &ratings_of(&db, &items).await).is_err());
+
+        db.record_rating(item_ids[1], judge, &scores(&[("score", 5.0)]), 5.0, Some("Settled")).await.unwrap();
+        db.mark_adjudicated(item_ids[1], judge).await.unwrap();
+        let items = db.get_session_rating_items(flagged_session).await.unwrap();
+        let summary = summarize_session(&items, &ratings_of(&db, &items).await).unwrap();
+        assert_eq!(summary.total, 5.0);
+        assert_eq!(summary.items[0].rater_ids, vec![judge]);
+
+        // Agreeing raters are averaged
+        let agreed_session = db.get_rating_item(item_ids[0]).await.unwrap().session_id;
+        let items = db.get_session_rating_items(agreed_session).await.unwrap();
+        let summary = summarize_session(&items, &ratings_of(&db, &items).await).unwrap();
+        assert_eq!(summary.total, 6.5);
+        assert_eq!(summary.items[0].rater_ids, vec![first, second]);
+
+        // With only a second rater set, the first other rater to score claims the item
+        let session_id = db.create_session(event_id, "P-3", None, None).await.unwrap();
+        let answers = vec![SubmittedAnswer { question_id: q_id, answer: Some("A small house".to_string()) }];
+        db.enqueue_rating_items(event_id, session_id, &snapshot, &answers).await.unwrap();
+        let unclaimed = db.get_session_rating_items(session_id).await.unwrap()[0].id;
+        db.assign_second_rater(&[unclaimed], second).await.unwrap();
+        assert!(!db.claim_rating_item(unclaimed, second).await.unwrap());
+        assert!(db.claim_rating_item(unclaimed, first).await.unwrap());
+        assert!(!db.claim_rating_item(unclaimed, judge).await.unwrap());
+        assert_eq!(db.get_rating_item(unclaimed).await.unwrap().assigned_to, Some(first));
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_rejects_missing_rubric() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let db = Database::new(pool);
+
+        let tool_id = db.create_tool("Unrated Essay", "projective", "personality", "No rubric").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Essay", 1, None).await.unwrap();
+        let q_id = db.create_question(sub_id, "Describe yourself", "text", json!({}), 1).await.unwrap();
+        let event_id = db.create_event("Unrated Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        let session_id = db.create_session(event_id, "P-001", None, None).await.unwrap();
+
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.unwrap();
+        let answers = vec![SubmittedAnswer { question_id: q_id, answer: Some("I am curious".to_string()) }];
+        let result = db.enqueue_rating_items(event_id, session_id, &snapshot, &answers).await;
+        assert!(matches!(result, Err(RatingError::InvalidRubric { question_id, .. }) if question_id == q_id));
+        assert!(db.get_session_rating_items(session_id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+// Objective Scoring
+// Scores candidate answers against the frozen package snapshot of an event
+
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::db::models::{AnswerKey, Question};
+use crate::db::packages::PackageSnapshot;
+use crate::question_types::{self, AnswerValue, QuestionType};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmittedAnswer {
+    pub question_id: i64,
+    pub answer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubtestScore {
+    pub subtest_id: i64,
+    pub subtest_name: String,
+    pub correct: i64,
+    pub answered: i64,
+    pub scorable: i64,
+}
+
+/// Trait scale tally from Likert and most/least items.
+/// Likert points add to `total`; most/least picks count in `most`/`least` and `total` is their difference.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScaleScore {
+    pub total: f64,
+    pub most: i64,
+    pub least: i64,
+    pub items: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScoreSummary {
+    pub package_version: i64,
+    pub raw_score: i64,
+    pub answered: i64,
+    pub scorable: i64,
+    pub subtests: Vec<SubtestScore>,
+    #[serde(default)]
+    pub scales: BTreeMap<String, ScaleScore>,
+}
+
+/// Correct answer for a question: an `answer_keys` row wins over `options.correct`
+pub fn correct_answer(question: &Question, keys: &HashMap<i64, &AnswerKey>) -> Option<String> {
+    if let Some(key) = keys.get(&question.id) {
+        return Some(key.correct_answer.clone());
+    }
+
+    // Personality items carry `"correct": ""`, which means there is no key
+    match question.options.as_ref().and_then(|o| o.get("correct")) {
+        Some(serde_json::Value::String(s)) if s.is_empty() => None,
+        Some(serde_json::Value::String(s)) => Some(s.clone()),
+        Some(serde_json::Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    }
+}
+
+/// Score answers against a snapshot. Questions without a key (text, drawing,
+/// personality items) are not scorable and don't count towards the total.
+pub fn score_snapshot(snapshot: &PackageSnapshot, answers: &[SubmittedAnswer]) -> ScoreSummary {
+    let keys: HashMap<i64, &AnswerKey> = snapshot.answer_keys.iter()
+        .map(|k| (k.question_id, k))
+        .collect();
+    let given: HashMap<i64, &str> = answers.iter()
+        .filter_map(|a| a.answer.as_deref().map(|ans| (a.question_id, ans)))
+        .collect();
+
+    let mut subtests = Vec::new();
+    let mut scales: BTreeMap<String, ScaleScore> = BTreeMap::new();
+
+    for full in &snapshot.structure.subtests {
+        let mut score = SubtestScore {
+            subtest_id: full.subtest.id,
+            subtest_name: full.subtest.subtest_name.clone(),
+            correct: 0,
+            answered: 0,
+            scorable: 0,
+        };
+
+        for question in &full.questions {
+            let answer = given.get(&question.id);
+            if answer.is_some() {
+                score.answered += 1;
+            }
+
+            let question_type = QuestionType::parse(&question.question_type).unwrap_or(QuestionType::MultipleChoice);
+            let options = question.options.as_ref();
+
+            if let Some(expected) = correct_answer(question, &keys) {
+                score.scorable += 1;
+                if answer.map(|a| question_types::is_correct(question_type, options, &expected, a)).unwrap_or(false) {
+                    score.correct += 1;
+                }
+            }
+
+            if let Some(value) = answer.and_then(|a| question_types::parse_answer(question_type, options, a).ok()) {
+                add_to_scales(&mut scales, options, &value);
+            }
+        }
+
+        subtests.push(score);
+    }
+
+    ScoreSummary {
+        package_version: snapshot.version,
+        raw_score: subtests.iter().map(|s| s.correct).sum(),
+        answered: subtests.iter().map(|s| s.answered).sum(),
+        scorable: subtests.iter().map(|s| s.scorable).sum(),
+        subtests,
+        scales,
+    }
+}
+
+/// Likert items name their scale in `scale_key` (and may be `reverse`d);
+/// most/least items map each statement to a scale.
+fn add_to_scales(scales: &mut BTreeMap<String, ScaleScore>, options: Option<&serde_json::Value>, value: &AnswerValue) {
+    let scale_key = options.and_then(|o| o.get("scale_key"));
+
+    match value {
+        AnswerValue::Likert(points) => {
+            let scale = match scale_key.and_then(|k| k.as_str()) {
+                Some(scale) => scale,
+                None => return,
+            };
+            let (min, max) = question_types::likert_bounds(options);
+            let reverse = options.and_then(|o| o.get("reverse")).and_then(|r| r.as_bool()).unwrap_or(false);
+            let points = if reverse { min + max - points } else { *points };
+
+            let entry = scales.entry(scale.to_string()).or_default();
+            entry.total += points as f64;
+            entry.items += 1;
+        }
+        AnswerValue::MostLeast { most, least } => {
+            let scale_of = |statement: &str| scale_key
+                .and_then(|k| k.get(statement))
+                .and_then(|s| s.as_str())
+                .map(|s| s.to_string());
+
+            if let Some(scale) = scale_of(most) {
+                let entry = scales.entry(scale).or_default();
+                entry.most += 1;
+                entry.total += 1.0;
+                entry.items += 1;
+            }
+            if let Some(scale) = scale_of(least) {
+                let entry = scales.entry(scale).or_default();
+                entry.least += 1;
+                entry.total -= 1.0;
+                entry.items += 1;
+            }
+        }
+        _ => {}
+    }
+}
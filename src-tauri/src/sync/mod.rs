@@ -3,9 +3,11 @@
 
 use reqwest::{Client, multipart};
 use serde::{Serialize, Deserialize};
+use std::path::Path;
 use std::time::Duration;
 
 use crate::db::candidate::CandidateDatabase;
+use crate::media;
 
 #[derive(Debug)]
 pub enum SyncError {
@@ -13,6 +15,7 @@ pub enum SyncError {
     ServerError(u16),
     DatabaseError(sqlx::Error),
     SerializationError(serde_json::Error),
+    IoError(std::io::Error),
 }
 
 impl From<reqwest::Error> for SyncError {
@@ -33,6 +36,12 @@ impl From<serde_json::Error> for SyncError {
     }
 }
 
+impl From<std::io::Error> for SyncError {
+    fn from(err: std::io::Error) -> Self {
+        SyncError::IoError(err)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct TestResultPayload {
     session_id: String,
@@ -56,6 +65,23 @@ struct SubmitResponse {
     result_id: Option<i64>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct MediaManifestEntry {
+    content_hash: String,
+    url: String,
+    mime_type: String,
+    size_bytes: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MediaDownloadReport {
+    pub total: usize,
+    pub downloaded: usize,
+    pub already_cached: usize,
+    /// Hashes that failed to download or didn't match their content
+    pub failed: Vec<String>,
+}
+
 pub struct SyncService {
     client: Client,
     server_url: String,
@@ -200,6 +226,65 @@ impl SyncService {
             Err(SyncError::ServerError(response.status().as_u16()))
         }
     }
+
+    /// Download every media file an event uses into `cache_dir` so the test can run offline.
+    /// Files already cached are skipped; a failed file doesn't stop the rest.
+    pub async fn download_event_media(&self, event_id: i64, cache_dir: &Path) -> Result<MediaDownloadReport, SyncError> {
+        println!("🔄 Pre-downloading media for event: {}", event_id);
+
+        let url = format!("{}/api/event-media/{}", self.server_url, event_id);
+        let response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(SyncError::ServerError(response.status().as_u16()));
+        }
+        let manifest: Vec<MediaManifestEntry> = response.json().await?;
+
+        let mut report = MediaDownloadReport {
+            total: manifest.len(),
+            ..Default::default()
+        };
+
+        for entry in manifest {
+            if let Some(cached) = self.db.get_cached_media(&entry.content_hash).await? {
+                if Path::new(&cached.file_path).is_file() {
+                    report.already_cached += 1;
+                    continue;
+                }
+            }
+
+            let data = match self.client.get(format!("{}{}", self.server_url, entry.url)).send().await {
+                Ok(response) if response.status().is_success() => response.bytes().await?,
+                Ok(response) => {
+                    eprintln!("❌ Media {} returned {}", entry.content_hash, response.status());
+                    report.failed.push(entry.content_hash);
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("❌ Media {} failed: {:?}", entry.content_hash, e);
+                    report.failed.push(entry.content_hash);
+                    continue;
+                }
+            };
+
+            if media::content_hash(&data) != entry.content_hash {
+                eprintln!("❌ Media {} is corrupt, hash mismatch", entry.content_hash);
+                report.failed.push(entry.content_hash);
+                continue;
+            }
+
+            let stored = media::store_bytes(cache_dir, &entry.content_hash, &data)?;
+            self.db.cache_media(
+                &stored.content_hash,
+                &stored.storage_path.to_string_lossy(),
+                &entry.mime_type,
+                stored.size_bytes,
+            ).await?;
+            report.downloaded += 1;
+        }
+
+        println!("✅ Media ready: {} downloaded, {} cached, {} failed", report.downloaded, report.already_cached, report.failed.len());
+        Ok(report)
+    }
 }
 
 /// Background sync worker
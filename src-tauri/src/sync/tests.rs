@@ -167,4 +167,63 @@ mod sync_tests {
         assert!(result.is_err());
         mock.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn test_download_event_media_from_api_server() {
+        use crate::db::Database;
+        use crate::sync::SyncError;
+        use std::sync::Arc;
+
+        // The real router, so a renamed route breaks this test instead of the candidate app
+        let server_pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&server_pool).await.unwrap();
+        let server_db = Database::new(server_pool.clone());
+
+        let store = tempfile::tempdir().unwrap();
+        let stored = crate::media::store_bytes(store.path(), "figure.png", b"fake-png").unwrap();
+        server_db.register_media_asset(&stored).await.unwrap();
+        let tool_id = server_db.create_tool("Media Tool", "choice", "cognitive", "Sync test").await.unwrap();
+        let sub_id = server_db.create_subtest(tool_id, "Figures", 1, None).await.unwrap();
+        let q_id = server_db.create_question(sub_id, "Which figure?", "multiple_choice", serde_json::json!({"choices": ["A", "B"]}), 1).await.unwrap();
+        sqlx::query("UPDATE questions SET media_url = ? WHERE id = ?")
+            .bind(crate::media::media_url(&stored.content_hash))
+            .bind(q_id)
+            .execute(&server_pool)
+            .await
+            .unwrap();
+        let event_id = server_db.create_event("Media Event", None, None).await.unwrap();
+        server_db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, crate::api_server::router(Arc::new(server_db))).await.unwrap();
+        });
+
+        let candidate_pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!("../../migrations/candidate/003_media_cache.sql"))
+            .execute(&candidate_pool)
+            .await
+            .unwrap();
+        let sync_service = SyncService::new(CandidateDatabase::new(candidate_pool), server_url);
+
+        let cache = tempfile::tempdir().unwrap();
+        let report = sync_service.download_event_media(event_id, cache.path()).await.unwrap();
+        assert_eq!((report.total, report.downloaded, report.failed.len()), (1, 1, 0));
+        let report = sync_service.download_event_media(event_id, cache.path()).await.unwrap();
+        assert_eq!(report.already_cached, 1);
+
+        assert!(matches!(
+            sync_service.download_event_media(event_id + 1, cache.path()).await,
+            Err(SyncError::ServerError(404))
+        ));
+    }
 }
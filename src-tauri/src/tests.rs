@@ -73,7 +73,7 @@ mod tests {
         let event_name = "Test Event 2026";
         let description = Some("Unit test event description".to_string());
 
-        let event_id = db.create_event(event_name, description.clone())
+        let event_id = db.create_event(event_name, description.clone(), None)
             .await
             .expect("Failed to create event");
 
@@ -92,9 +92,9 @@ mod tests {
         let db = setup_test_db().await;
         let event_name = "Unique Event";
         
-        let _ = db.create_event(event_name, None).await.unwrap();
+        let _ = db.create_event(event_name, None, None).await.unwrap();
         
-        let result = db.create_event(event_name, None).await;
+        let result = db.create_event(event_name, None, None).await;
         assert!(result.is_err()); // Unique constraint check
     }
 
@@ -121,4 +121,704 @@ mod tests {
         assert_eq!(questions.len(), 2);
         assert_eq!(questions[0].question_text, "Q1");
     }
+
+    #[tokio::test]
+    async fn test_session_lifecycle_transitions() {
+        use crate::db::session_lifecycle::SessionStatus;
+
+        let db = setup_test_db().await;
+        let event_id = db.create_event("Lifecycle Event", None, None).await.unwrap();
+        let user_id = db.create_user("lifecycle_user", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+
+        let session_id = db.create_session(event_id, "P-001", Some(user_id), None).await.unwrap();
+        let participants = db.get_event_participants(event_id).await.unwrap();
+        assert_eq!(participants[0].status, "in_progress");
+
+        let paused = db.transition_session(session_id, SessionStatus::Paused, Some("Fire drill")).await.unwrap();
+        assert_eq!(paused.status, "paused");
+        assert_eq!(paused.status_reason.as_deref(), Some("Fire drill"));
+        assert!(paused.paused_at.is_some());
+
+        let resumed = db.transition_session(session_id, SessionStatus::Active, None).await.unwrap();
+        assert!(resumed.paused_at.is_none());
+
+        let completed = db.transition_session(session_id, SessionStatus::Completed, None).await.unwrap();
+        assert!(completed.completed_at.is_some());
+
+        let participants = db.get_event_participants(event_id).await.unwrap();
+        assert_eq!(participants[0].status, "completed");
+        assert!(participants[0].completed_at.is_some());
+
+        let history = db.get_session_transitions(session_id).await.unwrap();
+        let steps: Vec<(&str, &str)> = history.iter()
+            .map(|t| (t.from_status.as_str(), t.to_status.as_str()))
+            .collect();
+        assert_eq!(steps, vec![
+            ("pending", "active"),
+            ("active", "paused"),
+            ("paused", "active"),
+            ("active", "completed"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_session_invalid_transition_rejected() {
+        use crate::db::session_lifecycle::{SessionStatus, SessionStateError};
+
+        let db = setup_test_db().await;
+        let event_id = db.create_event("Terminal Event", None, None).await.unwrap();
+        let session_id = db.create_session(event_id, "P-002", None, None).await.unwrap();
+
+        db.transition_session(session_id, SessionStatus::Terminated, Some("Cheating")).await.unwrap();
+
+        let result = db.transition_session(session_id, SessionStatus::Active, None).await;
+        assert!(matches!(
+            result,
+            Err(SessionStateError::InvalidTransition { from: SessionStatus::Terminated, to: SessionStatus::Active })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_event_package_is_frozen_snapshot() {
+        use crate::scoring::{score_snapshot, SubmittedAnswer};
+
+        let db = setup_test_db().await;
+        let tool_id = db.create_tool("Snapshot Tool", "choice", "cognitive", "Snapshot test").await.unwrap();
+        let sub_id = db.create_subtest(tool_id, "Verbal", 1, Some(300)).await.unwrap();
+        let q_id = db.create_question(sub_id, "Q1", "multiple_choice", serde_json::json!({"choices": ["A", "B"], "correct": "A"}), 1).await.unwrap();
+
+        let event_id = db.create_event("Snapshot Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+
+        // Editing the live question must not change what the event delivers
+        db.update_question(q_id, "Q1 edited", "multiple_choice", serde_json::json!({"choices": ["A", "B"], "correct": "B"})).await.unwrap();
+
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.unwrap();
+        assert_eq!(snapshot.version, 1);
+        assert_eq!(snapshot.structure.subtests[0].questions[0].question_text, "Q1");
+
+        let answers = vec![SubmittedAnswer { question_id: q_id, answer: Some("A".to_string()) }];
+        let summary = score_snapshot(&snapshot, &answers);
+        assert_eq!(summary.raw_score, 1);
+        assert_eq!(summary.scorable, 1);
+
+        let delivered = snapshot.into_candidate_structure();
+        let options = delivered.subtests[0].questions[0].options.as_ref().unwrap();
+        assert!(options.get("correct").is_none());
+
+        // Publishing explicitly creates the next version
+        let v2 = db.publish_tool_package(tool_id).await.unwrap();
+        assert_eq!(v2.version, "2");
+        db.set_event_tool_package(event_id, tool_id, v2.id).await.unwrap();
+
+        let snapshot = db.get_event_tool_snapshot(event_id, tool_id).await.unwrap();
+        assert_eq!(snapshot.version, 2);
+        assert_eq!(snapshot.structure.subtests[0].questions[0].question_text, "Q1 edited");
+
+        // Attaching to another event freezes the tool as it is now, not the last published version
+        db.update_question(q_id, "Q1 edited again", "multiple_choice", serde_json::json!({"choices": ["A", "B"], "correct": "B"})).await.unwrap();
+        let next_event = db.create_event("Snapshot Event 2", None, None).await.unwrap();
+        db.add_tools_to_event(next_event, vec![tool_id]).await.unwrap();
+        let snapshot = db.get_event_tool_snapshot(next_event, tool_id).await.unwrap();
+        assert_eq!(snapshot.version, 3);
+        assert_eq!(snapshot.structure.subtests[0].questions[0].question_text, "Q1 edited again");
+
+        // Attaching again keeps the version the event already has
+        db.add_tools_to_event(event_id, vec![tool_id]).await.unwrap();
+        assert_eq!(db.get_event_tool_snapshot(event_id, tool_id).await.unwrap().version, 2);
+        assert_eq!(db.get_tool_packages(tool_id).await.unwrap().len(), 3);
+
+        // A tool added in a later call goes after the ones the event already has
+        let second_tool = db.create_tool("Second Tool", "choice", "cognitive", "Added later").await.unwrap();
+        db.add_tools_to_event(event_id, vec![second_tool]).await.unwrap();
+        let orders: Vec<(i64, i64)> = sqlx::query_as(
+            "SELECT p.tool_id, ep.sequence_order FROM event_packages ep JOIN packages p ON p.id = ep.package_id WHERE ep.event_id = ? ORDER BY ep.sequence_order"
+        )
+        .bind(event_id)
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(orders, vec![(tool_id, 0), (second_tool, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_accommodations_extend_time_and_mark_report() {
+        use crate::db::accommodations::Accommodations;
+
+        let db = setup_test_db().await;
+        let tool_id = db.create_tool("Timed Tool", "speed", "cognitive", "Accommodation test").await.unwrap();
+        db.create_subtest(tool_id, "Timed", 1, Some(600)).await.unwrap();
+        db.create_subtest(tool_id, "Untimed", 2, None).await.unwrap();
+
+        let event_id = db.create_event("Accommodation Event", None, None).await.unwrap();
+        let user_id = db.create_user("accommodated", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+
+        // Nothing set means standard conditions
+        let standard = db.get_participant_accommodations(event_id, user_id).await.unwrap();
+        assert!(standard.is_standard());
+
+        let extended = Accommodations { time_multiplier: 1.5, extra_breaks: 2, break_minutes: 10, large_font: true, notes: Some("Dyslexia".to_string()) };
+        assert!(Accommodations { time_multiplier: 0.5, ..extended.clone() }.validate().is_err());
+        assert!(Accommodations { break_minutes: 0, ..extended.clone() }.validate().is_err());
+        db.set_participant_accommodations(event_id, user_id, &extended).await.unwrap();
+        assert!(db.set_participant_accommodations(event_id, user_id + 1, &extended).await.is_err());
+
+        let mut structure = db.get_full_tool_structure(tool_id).await.unwrap();
+        db.get_participant_accommodations(event_id, user_id).await.unwrap().apply_to_structure(&mut structure);
+        assert_eq!(structure.subtests[0].subtest.time_limit_seconds, Some(900));
+        assert_eq!(structure.subtests[1].subtest.time_limit_seconds, None);
+
+        // Reports note the non-standard administration
+        let session_id = db.create_session(event_id, "accommodated", Some(user_id), None).await.unwrap();
+        let report_id = db.create_report(session_id, serde_json::json!({"raw_score": 10}), serde_json::json!({})).await.unwrap();
+        let report = db.get_report_by_id(report_id).await.unwrap();
+        assert_eq!(report.scores["administration"]["standard"], false);
+        assert_eq!(report.scores["administration"]["accommodations"]["time_multiplier"], 1.5);
+
+        let result = db.get_test_result_by_id(report_id).await.unwrap();
+        assert!(!result.standard_administration);
+        assert_eq!(result.raw_score, 10);
+
+        // Extra breaks are offered with the next step and used up as the candidate takes them
+        use crate::db::accommodations::EXTRA_BREAK_REASON;
+        use crate::db::session_lifecycle::SessionStatus;
+        let next = db.get_session_next_step(session_id).await.unwrap();
+        assert_eq!((next.extra_breaks_left, next.extra_break_seconds), (2, 600));
+        db.transition_session(session_id, SessionStatus::Paused, Some(EXTRA_BREAK_REASON)).await.unwrap();
+        db.transition_session(session_id, SessionStatus::Active, None).await.unwrap();
+        db.transition_session(session_id, SessionStatus::Paused, Some("Fire drill")).await.unwrap();
+        assert_eq!(db.get_extra_breaks_left(session_id).await.unwrap(), 1);
+        let other_tool = db.create_session(event_id, "accommodated", Some(user_id), None).await.unwrap();
+        assert_eq!(db.get_extra_breaks_left(other_tool).await.unwrap(), 1);
+
+        // Overrunning a break counts as testing time, but never below zero paused time
+        assert_eq!(db.charge_break_overrun(session_id, 30).await.unwrap().paused_seconds, 0);
+    }
+
+    #[tokio::test]
+    async fn test_enrollment_capacity_deadline_and_waitlist() {
+        use crate::db::enrollment::{self, EnrollmentError, EnrollmentOutcome};
+        use crate::db::session_lifecycle::SessionStatus;
+
+        let db = setup_test_db().await;
+        let event_id = db.create_event("Small Event", None, None).await.unwrap();
+        db.set_event_enrollment_settings(event_id, Some(1), None).await.unwrap();
+        let first = db.create_user("first", "hash", "participant").await.unwrap();
+        let second = db.create_user("second", "hash", "participant").await.unwrap();
+        let third = db.create_user("third", "hash", "participant").await.unwrap();
+
+        assert!(matches!(db.enroll_participant(event_id, first, None, true).await, Ok(EnrollmentOutcome::Enrolled { .. })));
+        assert!(matches!(db.enroll_participant(event_id, first, None, true).await, Err(EnrollmentError::AlreadyEnrolled)));
+        assert!(matches!(db.enroll_participant(event_id, second, None, false).await, Err(EnrollmentError::EventFull { capacity: 1 })));
+        assert_eq!(db.enroll_participant(event_id, second, None, true).await.unwrap(), EnrollmentOutcome::Waitlisted { position: 1 });
+        assert_eq!(db.enroll_participant(event_id, third, None, true).await.unwrap(), EnrollmentOutcome::Waitlisted { position: 2 });
+        assert!(matches!(db.enroll_participant(event_id, third, None, true).await, Err(EnrollmentError::AlreadyWaitlisted { position: 2 })));
+
+        // A terminated session keeps the place; withdrawing hands it to the head of the waitlist
+        let session_id = db.create_session(event_id, "first", Some(first), None).await.unwrap();
+        db.transition_session(session_id, SessionStatus::Terminated, Some("Left")).await.unwrap();
+        assert!(!db.check_participant_access(event_id, second).await.unwrap());
+        assert_eq!(db.get_event_participants(event_id).await.unwrap()[0].status, "in_progress");
+        db.withdraw_participant(event_id, first, "Left").await.unwrap();
+        assert!(db.check_participant_access(event_id, second).await.unwrap());
+        let waitlist = db.get_event_waitlist(event_id).await.unwrap();
+        assert_eq!(waitlist.len(), 1);
+        assert_eq!((waitlist[0].user_id, waitlist[0].position), (third, 1));
+
+        // Raising the capacity lets the rest in
+        assert_eq!(db.set_event_enrollment_settings(event_id, Some(3), None).await.unwrap(), vec![third]);
+        assert_eq!(db.get_event_details(event_id).await.unwrap().waitlist_count, 0);
+
+        // Deadlines
+        let late = db.create_user("late", "hash", "participant").await.unwrap();
+        db.set_event_enrollment_settings(event_id, None, Some("2020-01-01".to_string())).await.unwrap();
+        assert!(matches!(db.enroll_participant(event_id, late, None, true).await, Err(EnrollmentError::DeadlinePassed { .. })));
+        assert!(matches!(db.enroll_participant(event_id + 100, late, None, true).await, Err(EnrollmentError::EventNotFound)));
+
+        // Commands hand the UI a kind to branch on alongside the message
+        let failure = crate::commands::events::EnrollmentFailure::from(db.enroll_participant(event_id, late, None, true).await.unwrap_err());
+        assert_eq!(serde_json::to_value(&failure).unwrap(), serde_json::json!({
+            "kind": "deadline_passed",
+            "message": "Enrollment for this event closed on 2020-01-01",
+        }));
+
+        assert_eq!(enrollment::parse_deadline("2026-03-01"), chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap().and_hms_opt(23, 59, 59));
+        assert!(enrollment::parse_deadline("2026-03-01T08:30").is_some());
+        assert!(enrollment::parse_deadline("2026-03-01 08:30:00").is_some());
+        assert!(enrollment::validate_enrollment_settings(Some(0), None).is_err());
+        assert!(enrollment::validate_enrollment_settings(Some(10), Some("next week")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_event_schedule_and_lifecycle() {
+        use crate::db::event_lifecycle::{self, EventLifecycleError, EventStatus, ScheduleWindow};
+        use chrono::{Duration, TimeZone, Utc};
+
+        let tz = event_lifecycle::parse_timezone("WIB").unwrap();
+        assert_eq!(tz.to_string(), "+07:00");
+        assert_eq!(event_lifecycle::parse_timezone("+0530").unwrap().local_minus_utc(), 19800);
+        assert!(event_lifecycle::parse_timezone("Mars/Olympus").is_none());
+        assert_eq!(
+            event_lifecycle::parse_schedule_time("2026-03-01 08:00", tz),
+            Some(Utc.with_ymd_and_hms(2026, 3, 1, 1, 0, 0).unwrap())
+        );
+
+        let db = setup_test_db().await;
+        let user_id = db.create_user("scheduled", "hash", "participant").await.unwrap();
+
+        // An event date schedules the whole day
+        let dated = db.create_event("Dated Event", None, Some("2020-01-01".to_string())).await.unwrap();
+        let schedule = db.get_event_schedule(dated).await.unwrap();
+        assert!(schedule.starts_at.is_some() && schedule.ends_at.is_some());
+        assert_eq!(schedule.window, ScheduleWindow::Closed);
+
+        // Upcoming window: no sessions yet, and the event stays a draft
+        let event_id = db.create_event("Scheduled Event", None, None).await.unwrap();
+        let start = Utc::now() + Duration::hours(2);
+        let end = start + Duration::hours(3);
+        let schedule = db.set_event_schedule(event_id, Some(&start.to_rfc3339()), Some(&end.to_rfc3339()), Some("+07:00")).await.unwrap();
+        assert_eq!(schedule.window, ScheduleWindow::Upcoming);
+        assert_eq!(schedule.status, "draft");
+        assert!(matches!(db.check_session_window(event_id).await, Err(EventLifecycleError::NotOpenYet { .. })));
+        assert!(matches!(
+            db.set_event_schedule(event_id, Some("2026-03-01 10:00"), Some("2026-03-01 09:00"), None).await,
+            Err(EventLifecycleError::InvalidSchedule(_))
+        ));
+
+        // The scheduler opens it; once the window has passed it stays active but closed
+        let changed = db.apply_event_schedules(start + Duration::minutes(1)).await.unwrap();
+        assert_eq!(changed, vec![(event_id, EventStatus::Active)]);
+        assert!(db.apply_event_schedules(end).await.unwrap().is_empty());
+        let past = Utc::now() - Duration::hours(1);
+        db.set_event_schedule(event_id, Some(&(past - Duration::hours(3)).to_rfc3339()), Some(&past.to_rfc3339()), None).await.unwrap();
+        assert!(matches!(db.check_session_window(event_id).await, Err(EventLifecycleError::WindowClosed { .. })));
+
+        // A closed window can still be extended
+        let schedule = db.set_event_schedule(event_id, Some(&past.to_rfc3339()), Some(&end.to_rfc3339()), None).await.unwrap();
+        assert_eq!((schedule.status.as_str(), schedule.window), ("active", ScheduleWindow::Open));
+        assert!(db.check_session_window(event_id).await.is_ok());
+
+        // Archived events are read-only
+        db.set_event_status(event_id, EventStatus::Archived).await.unwrap();
+        assert!(matches!(db.ensure_event_editable(event_id).await, Err(EventLifecycleError::Archived)));
+        assert!(matches!(db.set_event_status(event_id, EventStatus::Active).await, Err(EventLifecycleError::Archived)));
+        assert!(matches!(db.check_session_window(event_id).await, Err(EventLifecycleError::Archived)));
+        assert!(db.enroll_participant(event_id, user_id, None, true).await.is_err());
+
+        // Manual transitions are validated
+        let manual = db.create_event("Manual Event", None, None).await.unwrap();
+        assert!(matches!(db.check_session_window(manual).await, Err(EventLifecycleError::NotActive)));
+        assert_eq!(db.set_event_status(manual, EventStatus::Active).await.unwrap().status, "active");
+        assert!(db.check_session_window(manual).await.is_ok());
+        assert_eq!(db.set_event_status(manual, EventStatus::Archived).await.unwrap().status, "archived");
+        assert!(!EventStatus::Archived.can_transition_to(EventStatus::Draft));
+    }
+
+    #[tokio::test]
+    async fn test_clone_event_copies_setup_but_not_sessions() {
+        use crate::db::event_clone::{self, CloneEventOptions};
+        use crate::db::retakes::{ReportAttempt, RetakePolicy};
+        use crate::ranking::{RankingConfig, ScoreCutoff, TieBreak, ToolWeight};
+
+        let db = setup_test_db().await;
+        let tool_a = db.create_tool("Clone Tool A", "choice", "cognitive", "First").await.unwrap();
+        let tool_b = db.create_tool("Clone Tool B", "choice", "personality", "Second").await.unwrap();
+        let event_id = db.create_event("Monthly Battery", None, Some("2026-01-15".to_string())).await.unwrap();
+        db.add_tools_to_event(event_id, vec![tool_b, tool_a]).await.unwrap();
+        db.set_event_enrollment_settings(event_id, Some(20), Some("2026-01-10".to_string())).await.unwrap();
+        let ranking = RankingConfig {
+            weights: vec![ToolWeight { tool_id: tool_a, tool_name: String::new(), weight: 2.0 }],
+            cutoffs: vec![ScoreCutoff { tool_id: tool_b, tool_name: String::new(), scale: Some("E".to_string()), min_score: 4.0 }],
+            tie_breaks: vec![TieBreak::FewestViolations],
+        };
+        db.set_ranking_config(event_id, &ranking).await.unwrap();
+        let retakes = RetakePolicy { max_attempts: Some(3), cooldown_hours: Some(48), report_attempt: ReportAttempt::Best };
+        db.set_retake_policy(event_id, &retakes).await.unwrap();
+
+        let kept = db.create_user("kept", "hash", "participant").await.unwrap();
+        let gone = db.create_user("gone", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, kept, None).await.unwrap();
+        db.add_participant_to_event(event_id, gone, None).await.unwrap();
+        db.create_session(event_id, "gone", Some(gone), None).await.unwrap();
+        db.withdraw_participant(event_id, gone, "Moved away").await.unwrap();
+        db.create_session(event_id, "kept", Some(kept), None).await.unwrap();
+
+        let options = CloneEventOptions { name: Some("Monthly Battery - February".to_string()), copy_participants: true, shift_days: Some(31) };
+        let clone_id = db.clone_event(event_id, &options).await.unwrap();
+
+        let original = db.get_event_details(event_id).await.unwrap();
+        let clone = db.get_event_details(clone_id).await.unwrap();
+        assert_eq!(clone.status, "draft");
+        assert!(clone.event_code.is_some());
+        assert_ne!(clone.event_code, original.event_code);
+        assert_eq!(clone.max_participants, Some(20));
+        assert_eq!(clone.enrollment_deadline.as_deref(), Some("2026-02-10"));
+        assert_eq!(clone.timezone, original.timezone);
+        assert!(clone.starts_at > original.starts_at);
+
+        // Packages keep their order; only active candidates come along, and no sessions
+        let tools: Vec<i64> = db.get_event_packages(clone_id).await.unwrap().into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(tools, vec![tool_b, tool_a]);
+        let participants = db.get_event_participants(clone_id).await.unwrap();
+        assert_eq!(participants.len(), 1);
+        assert_eq!((participants[0].user_id, participants[0].status.as_str()), (kept, "enrolled"));
+        assert_eq!(db.count_event_sessions(clone_id).await.unwrap(), 0);
+        assert_eq!(db.get_ranking_config(clone_id).await.unwrap(), db.get_ranking_config(event_id).await.unwrap());
+        let cloned_retakes = db.get_retake_policy(clone_id).await.unwrap();
+        assert_eq!((cloned_retakes.max_attempts, cloned_retakes.cooldown_hours, cloned_retakes.report_attempt), (Some(3), Some(48), ReportAttempt::Best));
+
+        // Names stay unique
+        assert!(db.clone_event(event_id, &options).await.is_err());
+        let default_name = db.clone_event(event_id, &CloneEventOptions::default()).await.unwrap();
+        assert_eq!(db.get_event_details(default_name).await.unwrap().event_name, "Monthly Battery (copy)");
+        assert_eq!(db.get_event_participants(default_name).await.unwrap().len(), 0);
+
+        assert_eq!(event_clone::shift_deadline("2026-01-31 17:00", 1), "2026-02-01T17:00:00");
+    }
+
+    #[tokio::test]
+    async fn test_participant_profile_in_results() {
+        use crate::db::participants::ParticipantProfile;
+
+        let db = setup_test_db().await;
+        let event_id = db.create_event("Profile Event", None, None).await.unwrap();
+        let user_id = db.create_user("ayu", "hash", "participant").await.unwrap();
+        let other = db.create_user("dewi", "hash", "participant").await.unwrap();
+
+        let mut profile = ParticipantProfile {
+            full_name: "  Ayu Lestari ".to_string(),
+            nik: Some("3171 0123 4567 8901".to_string()),
+            date_of_birth: Some("15/06/1995".to_string()),
+            gender: Some("P".to_string()),
+            education: Some("s-1".to_string()),
+            position_applied: Some("Analyst".to_string()),
+            ..Default::default()
+        };
+        profile.normalize().unwrap();
+        assert_eq!(profile.nik.as_deref(), Some("3171012345678901"));
+        assert_eq!(profile.date_of_birth.as_deref(), Some("1995-06-15"));
+        assert_eq!(profile.gender.as_deref(), Some("female"));
+        assert_eq!(profile.education.as_deref(), Some("S1"));
+        assert_eq!(profile.age_on(chrono::NaiveDate::from_ymd_opt(2025, 6, 14).unwrap()), Some(29));
+        assert_eq!(profile.age_on(chrono::NaiveDate::from_ymd_opt(2025, 6, 15).unwrap()), Some(30));
+
+        let saved = db.save_participant_profile(user_id, &profile).await.unwrap();
+        assert_eq!((saved.user_id, saved.full_name.as_str()), (user_id, "Ayu Lestari"));
+        // A NIK belongs to one person
+        assert!(db.save_participant_profile(other, &profile).await.is_err());
+
+        let mut invalid = ParticipantProfile { full_name: "X".to_string(), gender: Some("unknown".to_string()), ..Default::default() };
+        assert!(invalid.normalize().is_err());
+
+        let session_id = db.create_session(event_id, "ayu", Some(user_id), None).await.unwrap();
+        let report_id = db.create_report(session_id, serde_json::json!({"total_score": 10}), serde_json::json!({})).await.unwrap();
+        let result = db.get_test_result_by_id(report_id).await.unwrap();
+        assert_eq!(result.full_name.as_deref(), Some("Ayu Lestari"));
+        assert_eq!(result.gender.as_deref(), Some("female"));
+        assert_eq!(result.position_applied.as_deref(), Some("Analyst"));
+        assert_eq!(result.age, profile.age_on(chrono::Local::now().date_naive()));
+
+        db.delete_participant_profile(user_id).await.unwrap();
+        assert!(db.get_participant_profile(user_id).await.unwrap().is_none());
+        assert!(db.delete_participant_profile(user_id).await.is_err());
+        // The report keeps the age it was generated with
+        assert!(db.get_test_result_by_id(report_id).await.unwrap().age.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_access_tokens_are_single_use() {
+        use crate::candidate_import::hash_token;
+        use crate::db::access_tokens::AccessTokenError;
+
+        let db = setup_test_db().await;
+        let event_id = db.create_event("Token Event", None, None).await.unwrap();
+        let user_id = db.create_user("token.user", "hash", "participant").await.unwrap();
+        let outsider = db.create_user("outsider", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+        db.set_participant_name(user_id, "Token User").await.unwrap();
+
+        let now = chrono::Utc::now();
+        let expires = now + chrono::Duration::days(1);
+        assert!(matches!(
+            db.issue_access_token(event_id, outsider, &hash_token("OUTSIDER"), expires).await,
+            Err(AccessTokenError::NotEnrolled)
+        ));
+
+        // Reissuing replaces the unused token
+        db.issue_access_token(event_id, user_id, &hash_token("FIRST"), expires).await.unwrap();
+        db.issue_access_token(event_id, user_id, &hash_token("SECOND"), expires).await.unwrap();
+        assert_eq!(db.get_event_access_tokens(event_id).await.unwrap().len(), 1);
+        assert!(matches!(db.redeem_access_token(&hash_token("FIRST"), now).await, Err(AccessTokenError::Invalid)));
+
+        let login = db.redeem_access_token(&hash_token("second"), now).await.unwrap();
+        assert_eq!((login.user.id, login.event_id), (user_id, event_id));
+        assert_eq!(login.full_name.as_deref(), Some("Token User"));
+        assert!(matches!(db.redeem_access_token(&hash_token("SECOND"), now).await, Err(AccessTokenError::AlreadyUsed { .. })));
+
+        let tokens = db.get_event_access_tokens(event_id).await.unwrap();
+        assert!(tokens[0].used_at.is_some());
+        // Used tokens stay on record
+        assert!(db.revoke_access_token(tokens[0].id).await.is_err());
+
+        let expiring = db.issue_access_token(event_id, user_id, &hash_token("THIRD"), expires).await.unwrap();
+        assert!(matches!(
+            db.redeem_access_token(&hash_token("THIRD"), expires + chrono::Duration::seconds(1)).await,
+            Err(AccessTokenError::Expired { .. })
+        ));
+        db.revoke_access_token(expiring).await.unwrap();
+        assert!(matches!(db.redeem_access_token(&hash_token("THIRD"), now).await, Err(AccessTokenError::Invalid)));
+
+        // Leaving the event voids an invitation that was never used
+        db.issue_access_token(event_id, user_id, &hash_token("FOURTH"), expires).await.unwrap();
+        db.withdraw_participant(event_id, user_id, "Moved away").await.unwrap();
+        assert!(matches!(db.redeem_access_token(&hash_token("FOURTH"), now).await, Err(AccessTokenError::Invalid)));
+        assert_eq!(db.get_event_access_tokens(event_id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_event_code_rotation_and_revocation() {
+        use crate::db::event_codes::EventCodeError;
+
+        let db = setup_test_db().await;
+        let admin = db.create_user("code.admin", "hash", "admin").await.unwrap();
+        let candidate = db.create_user("code.candidate", "hash", "participant").await.unwrap();
+        let event_id = db.create_event("Code Event", None, None).await.unwrap();
+        let first = db.generate_event_code().await.unwrap();
+        db.update_event_code(event_id, &first).await.unwrap();
+
+        assert_eq!(db.get_event_by_code(&first.to_lowercase()).await.unwrap().id, event_id);
+        db.record_event_code_use(event_id).await.unwrap();
+
+        // Candidates can't rotate codes
+        assert!(matches!(db.rotate_event_code(event_id, candidate).await, Err(EventCodeError::NotAllowed)));
+        assert!(matches!(db.revoke_event_code(event_id, candidate + 100).await, Err(EventCodeError::NotAllowed)));
+        let second = db.rotate_event_code(event_id, admin).await.unwrap();
+        assert_ne!(second, first);
+        assert!(matches!(db.get_event_by_code(&first).await, Err(EventCodeError::Replaced)));
+        assert!(matches!(db.get_event_by_code("ZZZZZZ").await, Err(EventCodeError::NotFound)));
+        assert_eq!(db.get_event_code_status(event_id).await.unwrap().use_count, 0);
+
+        // Validity window, in the event's timezone
+        assert!(db.set_event_code_validity(event_id, Some("2030-01-02"), Some("2030-01-01")).await.is_err());
+        let status = db.set_event_code_validity(event_id, Some("2030-01-01 08:00"), None).await.unwrap();
+        assert!(!status.usable);
+        assert!(matches!(db.get_event_by_code(&second).await, Err(EventCodeError::NotYetValid { .. })));
+        db.set_event_code_validity(event_id, None, Some("2020-01-01 08:00")).await.unwrap();
+        assert!(matches!(db.get_event_by_code(&second).await, Err(EventCodeError::Expired { .. })));
+        db.set_event_code_validity(event_id, None, None).await.unwrap();
+
+        let status = db.revoke_event_code(event_id, admin).await.unwrap();
+        assert!(status.revoked_at.is_some() && !status.usable);
+        assert!(matches!(db.get_event_by_code(&second).await, Err(EventCodeError::Revoked { .. })));
+        // Revoking twice records it once
+        db.revoke_event_code(event_id, admin).await.unwrap();
+
+        let third = db.rotate_event_code(event_id, admin).await.unwrap();
+        assert!(db.get_event_by_code(&third).await.is_ok());
+
+        let history = db.get_event_code_history(event_id).await.unwrap();
+        let entries: Vec<(&str, &str, i64)> = history.iter()
+            .map(|h| (h.event_code.as_str(), h.action.as_str(), h.use_count))
+            .collect();
+        assert_eq!(entries, vec![(second.as_str(), "revoked", 0), (first.as_str(), "rotated", 1)]);
+        assert_eq!(history[1].changed_by_username.as_deref(), Some("code.admin"));
+    }
+
+    #[tokio::test]
+    async fn test_participant_progress_follows_tools() {
+        use crate::db::session_lifecycle::SessionStatus;
+
+        let db = setup_test_db().await;
+        let tools: Vec<i64> = create_tools(&db, &["Progress A", "Progress B", "Progress C"]).await;
+        let event_id = db.create_event("Progress Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, tools.clone()).await.unwrap();
+        let user_id = db.create_user("progress.user", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+
+        let progress = |participants: Vec<crate::db::models::ParticipantInfo>| {
+            let p = &participants[0];
+            (p.status.clone(), p.tools_completed, p.tools_total)
+        };
+        assert_eq!(progress(db.get_event_participants(event_id).await.unwrap()), ("enrolled".to_string(), 0, 3));
+
+        // One session per tool, as the Kraepelin runner does: finishing it doesn't finish the event
+        let first = db.create_session(event_id, "progress.user", Some(user_id), Some(serde_json::json!({"tool_id": tools[0]}))).await.unwrap();
+        assert_eq!(progress(db.get_event_participants(event_id).await.unwrap()).0, "in_progress");
+        db.transition_session(first, SessionStatus::Completed, None).await.unwrap();
+        assert_eq!(progress(db.get_event_participants(event_id).await.unwrap()), ("in_progress".to_string(), 1, 3));
+
+        // Older clients only send the tool's name; a report counts the tool as done
+        let second = db.create_session(event_id, "progress.user", Some(user_id), Some(serde_json::json!({"testName": "Progress B"}))).await.unwrap();
+        db.create_report(second, serde_json::json!({}), serde_json::json!({})).await.unwrap();
+        assert_eq!(progress(db.get_event_participants(event_id).await.unwrap()), ("in_progress".to_string(), 2, 3));
+
+        // Walking the blueprint doesn't finish a tool that has no result
+        let battery = db.create_session(event_id, "progress.user", Some(user_id), None).await.unwrap();
+        for _ in 0..2 {
+            let step = db.get_session_next_step(battery).await.unwrap().step.unwrap();
+            db.complete_session_step(battery, step.id, false).await.unwrap();
+        }
+        let last = db.get_session_next_step(battery).await.unwrap().step.unwrap();
+        assert_eq!(last.tool_id, Some(tools[2]));
+        assert!(db.complete_session_step(battery, last.id, false).await.is_err());
+        assert_eq!(progress(db.get_event_participants(event_id).await.unwrap()), ("in_progress".to_string(), 2, 3));
+
+        let third = db.create_session(event_id, "progress.user", Some(user_id), Some(serde_json::json!({"tool_id": tools[2]}))).await.unwrap();
+        db.create_report(third, serde_json::json!({}), serde_json::json!({})).await.unwrap();
+        assert!(db.complete_session_step(battery, last.id, false).await.unwrap().finished);
+        let participants = db.get_event_participants(event_id).await.unwrap();
+        assert_eq!(progress(participants), ("completed".to_string(), 3, 3));
+
+        let detail = db.get_participant_progress(event_id, user_id).await.unwrap();
+        let names: Vec<(&str, bool)> = detail.tools.iter().map(|t| (t.tool_name.as_str(), t.completed)).collect();
+        assert_eq!(names, vec![("Progress A", true), ("Progress B", true), ("Progress C", true)]);
+        assert!(db.get_event_participants(event_id).await.unwrap()[0].completed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_retake_keeps_previous_attempts() {
+        use crate::db::retakes::{ReportAttempt, RetakeError, RetakePolicy};
+        use crate::db::session_lifecycle::SessionStatus;
+
+        let db = setup_test_db().await;
+        let event_id = db.create_event("Retake Event", None, None).await.unwrap();
+        let user_id = db.create_user("retake.user", "hash", "participant").await.unwrap();
+        db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+        db.set_retake_policy(event_id, &RetakePolicy { max_attempts: Some(2), cooldown_hours: None, report_attempt: ReportAttempt::Best })
+            .await
+            .unwrap();
+
+        let metadata = Some(serde_json::json!({"testName": "Retake Test"}));
+        let first = db.create_session(event_id, "retake.user", Some(user_id), metadata.clone()).await.unwrap();
+        db.transition_session(first, SessionStatus::Completed, None).await.unwrap();
+        let first_report = db.create_report(first, serde_json::json!({"total_score": 0}), serde_json::json!({})).await.unwrap();
+
+        assert_eq!(db.start_retake(event_id, user_id).await.unwrap(), 2);
+        assert_eq!(db.get_event_participants(event_id).await.unwrap()[0].status, "enrolled");
+
+        let second = db.create_session(event_id, "retake.user", Some(user_id), metadata).await.unwrap();
+        db.transition_session(second, SessionStatus::Completed, None).await.unwrap();
+        let second_report = db.create_report(second, serde_json::json!({"valid": false}), serde_json::json!({})).await.unwrap();
+
+        // Both attempts are kept; a real zero beats an attempt that has no total
+        let selected = |results: Vec<crate::db::models::TestResultDTO>| {
+            let mut r: Vec<(i64, i64, bool)> = results.iter().map(|r| (r.id, r.attempt_number, r.selected)).collect();
+            r.sort();
+            r
+        };
+        assert_eq!(selected(db.get_all_test_results().await.unwrap()), vec![(first_report, 1, true), (second_report, 2, false)]);
+
+        db.set_retake_policy(event_id, &RetakePolicy { max_attempts: Some(2), cooldown_hours: None, report_attempt: ReportAttempt::Latest })
+            .await
+            .unwrap();
+        assert!(db.get_test_result_by_id(second_report).await.unwrap().selected);
+
+        assert!(matches!(db.start_retake(event_id, user_id).await, Err(RetakeError::AttemptLimit { max_attempts: 2 })));
+
+        db.set_retake_policy(event_id, &RetakePolicy { max_attempts: None, cooldown_hours: Some(24), report_attempt: ReportAttempt::Latest })
+            .await
+            .unwrap();
+        assert!(matches!(db.start_retake(event_id, user_id).await, Err(RetakeError::CoolingOff { .. })));
+
+        let attempts = db.get_participant_attempts(event_id, user_id).await.unwrap();
+        let summary: Vec<(i64, i64, bool)> = attempts.iter().map(|a| (a.attempt_number, a.report_count, a.superseded_at.is_some())).collect();
+        assert_eq!(summary, vec![(1, 1, true), (2, 1, false)]);
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_and_no_show_release_places() {
+        use crate::db::enrollment::EnrollmentOutcome;
+        use crate::db::withdrawal::WithdrawalError;
+
+        let db = setup_test_db().await;
+        let event_id = db.create_event("Withdrawal Event", None, None).await.unwrap();
+        db.set_event_enrollment_settings(event_id, Some(1), None).await.unwrap();
+        let first = db.create_user("leaving.user", "hash", "participant").await.unwrap();
+        let second = db.create_user("waiting.user", "hash", "participant").await.unwrap();
+        let third = db.create_user("absent.user", "hash", "participant").await.unwrap();
+        db.enroll_participant(event_id, first, Some("Referred by HR".to_string()), true).await.unwrap();
+        assert!(matches!(db.enroll_participant(event_id, second, None, true).await.unwrap(), EnrollmentOutcome::Waitlisted { .. }));
+        assert!(matches!(db.enroll_participant(event_id, third, None, true).await.unwrap(), EnrollmentOutcome::Waitlisted { .. }));
+
+        let session_id = db.create_session(event_id, "leaving.user", Some(first), None).await.unwrap();
+        assert!(matches!(db.mark_participant_no_show(event_id, first, None).await, Err(WithdrawalError::AlreadyStarted)));
+        assert!(matches!(db.withdraw_participant(event_id, first, "  ").await, Err(WithdrawalError::ReasonRequired)));
+
+        let outcome = db.withdraw_participant(event_id, first, "Accepted another offer").await.unwrap();
+        assert_eq!(outcome.terminated_sessions, vec![session_id]);
+        assert_eq!(outcome.promoted, vec![second]);
+        assert_eq!(db.get_session_by_id(session_id).await.unwrap().status, "terminated");
+        let participants = db.get_event_participants(event_id).await.unwrap();
+        let notes = participants.iter().find(|p| p.user_id == first).unwrap().notes.clone();
+        assert_eq!(notes.as_deref(), Some("Referred by HR\nWithdrawn: Accepted another offer"));
+        assert!(matches!(db.withdraw_participant(event_id, first, "Again").await, Err(WithdrawalError::AlreadyLeft { .. })));
+
+        let outcome = db.mark_participant_no_show(event_id, second, None).await.unwrap();
+        assert_eq!((outcome.status.as_str(), outcome.promoted), ("no_show", vec![third]));
+
+        let details = db.get_event_details(event_id).await.unwrap();
+        assert_eq!((details.participant_count, details.withdrawn_count, details.no_show_count, details.waitlist_count), (3, 1, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_event_ranking_uses_counted_reports() {
+        use crate::db::ranking::RankingError;
+        use crate::db::session_lifecycle::SessionStatus;
+        use crate::ranking::{RankingConfig, ScoreCutoff, TieBreak, ToolWeight};
+
+        let db = setup_test_db().await;
+        let tools = create_tools(&db, &["Ranking Reasoning", "Ranking Personality"]).await;
+        let event_id = db.create_event("Ranking Event", None, None).await.unwrap();
+        db.add_tools_to_event(event_id, tools.clone()).await.unwrap();
+        assert!(matches!(db.get_event_ranking(event_id).await, Err(RankingError::NotConfigured)));
+
+        let outsider = create_tools(&db, &["Ranking Outsider"]).await[0];
+        let mut config = RankingConfig {
+            weights: vec![
+                ToolWeight { tool_id: tools[0], tool_name: String::new(), weight: 3.0 },
+                ToolWeight { tool_id: outsider, tool_name: String::new(), weight: 1.0 },
+            ],
+            cutoffs: vec![ScoreCutoff { tool_id: tools[1], tool_name: String::new(), scale: Some("E".to_string()), min_score: 5.0 }],
+            tie_breaks: vec![TieBreak::EarliestCompletion],
+        };
+        assert!(matches!(db.set_ranking_config(event_id, &config).await, Err(RankingError::InvalidConfig(_))));
+        config.weights[1].tool_id = tools[1];
+        let saved = db.set_ranking_config(event_id, &config).await.unwrap();
+        assert_eq!(saved.weights[0].tool_name, "Ranking Reasoning");
+        assert_eq!(saved.cutoffs[0].scale.as_deref(), Some("E"));
+
+        let mut users = Vec::new();
+        for (name, reasoning, personality, extraversion) in [("rank.one", 70, 40, 6), ("rank.two", 80, 60, 3), ("rank.gone", 99, 99, 9)] {
+            let user_id = db.create_user(name, "hash", "participant").await.unwrap();
+            db.add_participant_to_event(event_id, user_id, None).await.unwrap();
+            for (tool_id, score) in [(tools[0], reasoning), (tools[1], personality)] {
+                let session_id = db.create_session(event_id, name, Some(user_id), Some(serde_json::json!({"tool_id": tool_id}))).await.unwrap();
+                db.transition_session(session_id, SessionStatus::Completed, None).await.unwrap();
+                let scores = serde_json::json!({"total_score": score, "scales": {"E": {"total": extraversion}}});
+                db.create_report(session_id, scores, serde_json::json!({})).await.unwrap();
+            }
+            users.push(user_id);
+        }
+        db.withdraw_participant(event_id, users[2], "Declined").await.unwrap();
+
+        // With two candidates each tool's T-scores are 50 ± 5√2; rank.two is ahead on both but misses the E cutoff
+        let ranking = db.get_event_ranking(event_id).await.unwrap();
+        let summary: Vec<(&str, Option<i64>, bool)> = ranking.iter()
+            .map(|c| (c.username.as_str(), c.rank, c.passed))
+            .collect();
+        assert_eq!(summary, vec![("rank.two", Some(1), false), ("rank.one", Some(2), true)]);
+        let spread = 5.0 * 2f64.sqrt();
+        assert!((ranking[0].composite.unwrap() - (50.0 + spread)).abs() < 1e-9);
+        assert!((ranking[1].composite.unwrap() - (50.0 - spread)).abs() < 1e-9);
+    }
+
+    async fn create_tools(db: &Database, names: &[&str]) -> Vec<i64> {
+        let mut ids = Vec::new();
+        for name in names {
+            ids.push(db.create_tool(name, "choice", "cognitive", "Progress test").await.unwrap());
+        }
+        ids
+    }
 }